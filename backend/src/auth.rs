@@ -1,7 +1,9 @@
 use anyhow::Result;
 use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::collections::HashMap;
 
 use crate::models::{AuthConfig, AuthVerificationResult};
@@ -14,6 +16,8 @@ pub struct Claims {
     pub permissions: Vec<String>, // User permissions
     pub exp: i64,            // Expiration time
     pub iat: i64,            // Issued at
+    #[serde(default)]
+    pub last_used: Option<i64>, // Last authenticated request, for the idle timeout
 }
 
 impl Claims {
@@ -25,12 +29,28 @@ impl Claims {
             permissions,
             iat: now.timestamp(),
             exp: (now + Duration::hours(expires_in_hours)).timestamp(),
+            last_used: Some(now.timestamp()),
         }
     }
 }
 
+/// Response header carrying a session token with a fresh `last_used`, sent on
+/// each authenticated request while the idle timeout is on
+pub const SESSION_TOKEN_HEADER: &str = "X-Session-Token";
+
+/// How long a session may go unused before it is rejected, from
+/// `SESSION_IDLE_TIMEOUT_MINUTES`; None (sessions only expire absolutely) when unset
+fn idle_timeout() -> Option<Duration> {
+    std::env::var("SESSION_IDLE_TIMEOUT_MINUTES").ok()
+        .and_then(|minutes| minutes.trim().parse::<i64>().ok())
+        .filter(|&minutes| minutes > 0)
+        .map(Duration::minutes)
+}
+
 pub struct AuthService {
     jwt_secret: String,
+    // Read from the environment once, when the service is created
+    idle_timeout: Option<Duration>,
 }
 
 impl AuthService {
@@ -38,7 +58,7 @@ impl AuthService {
         let jwt_secret = std::env::var("JWT_SECRET")
             .unwrap_or_else(|_| "default-jwt-secret-change-in-production".to_string());
         
-        Self { jwt_secret }
+        Self { jwt_secret, idle_timeout: idle_timeout() }
     }
 
     pub fn create_token(&self, claims: &Claims) -> Result<String> {
@@ -47,13 +67,47 @@ impl AuthService {
             .map_err(|e| anyhow::anyhow!("Failed to create JWT token: {}", e))
     }
 
+    /// A stable, opaque stand-in for an identifier such as a username. It is
+    /// keyed with the JWT secret, so it can't be reversed by hashing guesses.
+    pub fn pseudonym(&self, value: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.jwt_secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(value.as_bytes());
+
+        let digest: String = mac.finalize().into_bytes().iter()
+            .take(6)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        format!("user-{}", digest)
+    }
+
     pub fn verify_token(&self, token: &str) -> Result<Claims> {
         let decoding_key = DecodingKey::from_secret(self.jwt_secret.as_ref());
         let validation = Validation::default();
         
-        decode::<Claims>(token, &decoding_key, &validation)
+        let claims = decode::<Claims>(token, &decoding_key, &validation)
             .map(|data| data.claims)
-            .map_err(|e| anyhow::anyhow!("Failed to verify JWT token: {}", e))
+            .map_err(|e| anyhow::anyhow!("Failed to verify JWT token: {}", e))?;
+
+        // Tokens issued before the idle timeout existed count from when they were issued
+        if let Some(idle_timeout) = self.idle_timeout {
+            let last_used = claims.last_used.unwrap_or(claims.iat);
+            if Utc::now().timestamp() - last_used > idle_timeout.num_seconds() {
+                return Err(anyhow::anyhow!("Session has been idle for too long"));
+            }
+        }
+
+        Ok(claims)
+    }
+
+    /// The token re-issued with `last_used` set to now, keeping its absolute
+    /// expiry. None when the idle timeout is off or the token isn't valid.
+    pub fn refresh_token(&self, token: &str) -> Option<String> {
+        self.idle_timeout?;
+
+        let mut claims = self.verify_token(token).ok()?;
+        claims.last_used = Some(Utc::now().timestamp());
+        self.create_token(&claims).ok()
     }
 
     pub async fn authenticate_request(&self, auth_config: &AuthConfig) -> Result<AuthVerificationResult> {
@@ -184,12 +238,58 @@ pub fn extract_auth_claims(authorization_header: Option<&str>) -> Result<Option<
     }
 }
 
+/// Hashes with the work factor from `BCRYPT_COST`, or `bcrypt::DEFAULT_COST`
+/// when unset or not a number
 pub fn hash_password(password: &str) -> Result<String> {
-    bcrypt::hash(password, bcrypt::DEFAULT_COST)
+    let cost = std::env::var("BCRYPT_COST")
+        .ok()
+        .and_then(|cost| cost.trim().parse::<u32>().ok())
+        .unwrap_or(bcrypt::DEFAULT_COST);
+
+    hash_password_with_cost(password, cost)
+}
+
+/// Hashes with `cost` clamped to the 4-31 range bcrypt accepts
+pub fn hash_password_with_cost(password: &str, cost: u32) -> Result<String> {
+    bcrypt::hash(password, cost.clamp(4, 31))
         .map_err(|e| anyhow::anyhow!("Failed to hash password: {}", e))
 }
 
 pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
     bcrypt::verify(password, hash)
         .map_err(|e| anyhow::anyhow!("Failed to verify password: {}", e))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_embed_the_configured_cost() {
+        let hash = hash_password_with_cost("secret", 5).unwrap();
+        assert!(hash.starts_with("$2b$05$"), "{hash}");
+        assert!(verify_password("secret", &hash).unwrap());
+
+        // Below bcrypt's minimum is clamped up to it
+        assert!(hash_password_with_cost("secret", 1).unwrap().starts_with("$2b$04$"));
+    }
+
+    #[test]
+    fn sessions_idle_past_the_timeout_are_rejected() {
+        let mut auth_service = AuthService::new();
+        let mut claims = Claims::new("user-1".to_string(), "alice".to_string(), vec![], 24);
+        claims.last_used = Some((Utc::now() - Duration::minutes(45)).timestamp());
+        let idle = auth_service.create_token(&claims).unwrap();
+
+        // Without an idle timeout only the absolute expiry applies
+        auth_service.idle_timeout = None;
+        assert!(auth_service.verify_token(&idle).is_ok());
+
+        auth_service.idle_timeout = Some(Duration::minutes(30));
+        assert!(auth_service.verify_token(&idle).is_err());
+        let active = auth_service.create_token(&Claims::new("user-1".to_string(), "alice".to_string(), vec![], 24)).unwrap();
+        let refreshed = auth_service.refresh_token(&active).unwrap();
+        assert!(auth_service.verify_token(&refreshed).is_ok());
+        assert!(auth_service.refresh_token(&idle).is_none());
+    }
+}
@@ -0,0 +1,214 @@
+use axum::http::StatusCode;
+use serde_json::json;
+use std::collections::HashMap;
+
+use crate::markdown::{self, ParsedTask};
+use crate::validation::ValidationError;
+
+/// Task fields a CSV column can fill. The export's other columns (dates,
+/// checklist progress) are ignored on import.
+pub const FIELDS: &[&str] = &[
+    "id", "title", "type", "priority", "status", "story_points", "sprint", "epic",
+    "assignee", "description", "dependencies", "blocks",
+];
+
+const REQUIRED_FIELDS: &[&str] = &["title"];
+
+/// Parses a CSV board with a header row. Headers named after a task field
+/// (case-insensitively) fill that field; `column_map` maps any other header,
+/// such as `Summary` -> `title`, and takes precedence. Rows without an `id`
+/// column are numbered `row-<n>`.
+pub fn parse_board(csv: &str, column_map: &HashMap<String, String>) -> Result<Vec<ParsedTask>, ValidationError> {
+    let mut records = parse_records(csv)?.into_iter()
+        .filter(|(_, record)| record.iter().any(|field| !field.trim().is_empty()));
+
+    let Some((_, headers)) = records.next() else {
+        return Err(invalid_csv("CSV has no header row"));
+    };
+    let columns = resolve_columns(&headers, column_map)?;
+
+    let mut tasks = Vec::new();
+    for (line, record) in records {
+        let value = |field: &str| {
+            columns.get(field)
+                .and_then(|&index| record.get(index))
+                .map(|value| value.trim())
+                .unwrap_or("")
+        };
+
+        let title = value("title");
+        if title.is_empty() {
+            return Err(invalid_csv(&format!("Row on line {} has no title", line)));
+        }
+        let id = match value("id") {
+            "" => format!("row-{}", line),
+            id => id.to_string(),
+        };
+
+        let mut task = ParsedTask::new(&id, title, markdown::non_empty(value("epic")));
+        task.r#type = markdown::parse_task_type(value("type"));
+        task.priority = markdown::parse_priority(value("priority"));
+        task.status = markdown::parse_status(value("status"));
+        task.story_points = match value("story_points") {
+            "" => None,
+            points => Some(points.parse().ok().filter(|points: &f64| points.is_finite()).ok_or_else(|| {
+                invalid_csv(&format!("Row on line {} has story points {:?}, which is not a number", line, points))
+            })?),
+        };
+        task.sprint = markdown::non_empty(value("sprint"));
+        task.assignee = markdown::non_empty(value("assignee"));
+        task.description = value("description").to_string();
+        task.dependencies = parse_id_list(value("dependencies"));
+        task.blocks = parse_id_list(value("blocks"));
+        task.provided.extend(FIELDS.iter().filter(|field| columns.contains_key(*field)));
+
+        tasks.push(task);
+    }
+
+    Ok(tasks)
+}
+
+// Task field -> column index, after applying the column map
+fn resolve_columns(headers: &[String], column_map: &HashMap<String, String>) -> Result<HashMap<&'static str, usize>, ValidationError> {
+    let field_named = |name: &str| FIELDS.iter().copied().find(|field| field.eq_ignore_ascii_case(name.trim()));
+
+    for (source, target) in column_map {
+        if field_named(target).is_none() {
+            return Err(invalid_column_map(
+                format!("column_map maps {:?} to {:?}, which is not a task field", source, target),
+            ).with_details(json!({ "fields": FIELDS })));
+        }
+        if !headers.iter().any(|header| header.trim().eq_ignore_ascii_case(source.trim())) {
+            return Err(invalid_column_map(format!("column_map refers to {:?}, which is not a column in the CSV", source))
+                .with_details(json!({ "headers": headers })));
+        }
+    }
+
+    let mut columns = HashMap::new();
+    for (index, header) in headers.iter().enumerate() {
+        let mapped = column_map.iter()
+            .find(|(source, _)| source.trim().eq_ignore_ascii_case(header.trim()))
+            .map(|(_, target)| target.as_str());
+        let Some(field) = field_named(mapped.unwrap_or(header)) else {
+            continue;
+        };
+
+        if columns.insert(field, index).is_some() {
+            return Err(invalid_column_map(format!("More than one column maps to {}", field))
+                .with_details(json!({ "field": field, "headers": headers })));
+        }
+    }
+
+    let missing: Vec<&str> = REQUIRED_FIELDS.iter().copied()
+        .filter(|field| !columns.contains_key(field))
+        .collect();
+    if !missing.is_empty() {
+        return Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "MISSING_COLUMN",
+            format!("No column maps to {}; add a matching header or a column_map entry", missing.join(", ")),
+        ).with_details(json!({ "missing": missing, "headers": headers })));
+    }
+
+    Ok(columns)
+}
+
+// RFC 4180 records paired with the line each starts on. Quoted fields may
+// hold commas, doubled quotes and line breaks; rows end in LF or CRLF.
+fn parse_records(csv: &str) -> Result<Vec<(usize, Vec<String>)>, ValidationError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut record_line = 1;
+
+    let mut chars = csv.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(invalid_csv(&format!("Quoted field starting on line {} is never closed", record_line)));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+
+    Ok(records)
+}
+
+// The export writes relationships space-separated; commas are accepted too
+fn parse_id_list(value: &str) -> Vec<String> {
+    value.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .collect()
+}
+
+fn invalid_csv(message: &str) -> ValidationError {
+    ValidationError::new(StatusCode::BAD_REQUEST, "INVALID_CSV", message)
+}
+
+fn invalid_column_map(message: String) -> ValidationError {
+    ValidationError::new(StatusCode::BAD_REQUEST, "INVALID_COLUMN_MAP", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Priority;
+
+    fn column_map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(source, target)| (source.to_string(), target.to_string())).collect()
+    }
+
+    #[test]
+    fn mapped_columns_fill_their_fields() {
+        let csv = "Key,Summary,Points,Owner,Priority\nPROJ-1,Login page,5,alice,High\n";
+        let map = column_map(&[("Key", "id"), ("Summary", "title"), ("Points", "story_points"), ("Owner", "assignee")]);
+
+        let tasks = parse_board(csv, &map).unwrap();
+        assert_eq!(tasks.len(), 1);
+        let task = &tasks[0];
+        assert_eq!((task.id.as_str(), task.title.as_str()), ("PROJ-1", "Login page"));
+        assert_eq!((task.story_points, task.assignee.as_deref()), (Some(5.0), Some("alice")));
+        assert_eq!(task.priority, Priority::High);
+    }
+
+    #[test]
+    fn unmapped_required_fields_are_reported() {
+        let csv = "Summary,Points\nLogin page,5\n";
+
+        let error = parse_board(csv, &column_map(&[("Points", "story_points")])).unwrap_err();
+        assert_eq!(error.code, "MISSING_COLUMN");
+        assert!(error.message.contains("title"), "{}", error.message);
+    }
+}
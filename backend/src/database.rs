@@ -1,6 +1,11 @@
-use sqlx::{Row, SqlitePool, migrate::MigrateDatabase, Sqlite};
+use sqlx::{FromRow, Row, SqlitePool, migrate::MigrateDatabase, Sqlite};
+use sqlx::sqlite::SqliteConnectOptions;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
 use crate::models::*;
 
@@ -15,19 +20,9 @@ impl From<TaskRow> for Task {
             _ => TaskType::Task,
         };
 
-        let priority = match row.priority.as_str() {
-            "Critical" => Priority::Critical,
-            "High" => Priority::High,
-            "Low" => Priority::Low,
-            _ => Priority::Medium,
-        };
+        let priority = Priority::parse(&row.priority);
 
-        let status = match row.status.as_str() {
-            "Todo" => TaskStatus::Todo,
-            "InProgress" => TaskStatus::InProgress,
-            "InReview" => TaskStatus::InReview,
-            _ => TaskStatus::Done,
-        };
+        let status = TaskStatus::parse(&row.status);
 
         Task {
             id: row.id,
@@ -43,9 +38,17 @@ impl From<TaskRow> for Task {
             technical_tasks: vec![], // Will be populated separately
             dependencies: vec![], // Will be populated separately
             blocks: vec![], // Will be populated separately
+            labels: vec![], // Will be populated separately
             assignee: row.assignee,
             is_favorite: row.is_favorite,
+            is_blocked: None, // Will be populated separately
             thumbnail: row.thumbnail,
+            due_date: row.due_date
+                .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+                .map(|date| date.with_timezone(&Utc)),
+            archived: row.archived,
+            created_by: row.created_by,
+            updated_by: row.updated_by,
             created_at: DateTime::parse_from_rfc3339(&row.created_at).unwrap().with_timezone(&Utc),
             updated_at: DateTime::parse_from_rfc3339(&row.updated_at).unwrap().with_timezone(&Utc),
         }
@@ -55,11 +58,24 @@ impl From<TaskRow> for Task {
 pub async fn init_db() -> Result<DbPool> {
     let database_url = std::env::var("DATABASE_URL")
         .unwrap_or_else(|_| "sqlite:taskdown.db".to_string());
+    let pool = init_db_at(&database_url).await?;
+
+    if let Ok(read_url) = std::env::var("DATABASE_READ_URL") {
+        let options = SqliteConnectOptions::from_str(&read_url)?.read_only(true);
+        let read_pool = SqlitePool::connect_with(options).await?;
+        let _ = READ_POOL.set(read_pool);
+        println!("Serving reads from {}", read_url);
+    }
 
+    Ok(pool)
+}
+
+/// Opens (creating it if needed) and migrates the database at `database_url`
+pub async fn init_db_at(database_url: &str) -> Result<DbPool> {
     // Create database if it doesn't exist
-    if !Sqlite::database_exists(&database_url).await.unwrap_or(false) {
+    if !Sqlite::database_exists(database_url).await.unwrap_or(false) {
         println!("Creating database {}", database_url);
-        match Sqlite::create_database(&database_url).await {
+        match Sqlite::create_database(database_url).await {
             Ok(_) => println!("Create db success"),
             Err(error) => panic!("error: {}", error),
         }
@@ -67,14 +83,51 @@ pub async fn init_db() -> Result<DbPool> {
         println!("Database already exists");
     }
 
-    let pool = SqlitePool::connect(&database_url).await?;
+    let pool = SqlitePool::connect(database_url).await?;
     
     // Run migrations
     create_tables(&pool).await?;
+    MIGRATED.store(true, Ordering::Release);
     
     Ok(pool)
 }
 
+/// A freshly migrated database in its own temporary file, with nothing left
+/// in the config cache from an earlier test
+#[cfg(test)]
+pub async fn test_db() -> Result<DbPool> {
+    let path = std::env::temp_dir().join(format!("taskdown-test-{}.db", uuid::Uuid::new_v4()));
+    let options = SqliteConnectOptions::new().filename(&path).create_if_missing(true);
+    let pool = SqlitePool::connect_with(options).await?;
+
+    create_tables(&pool).await?;
+    CONFIG_CACHE.write().unwrap().entry = None;
+    Ok(pool)
+}
+
+static READ_POOL: OnceLock<DbPool> = OnceLock::new();
+
+/// Set once `create_tables` has finished
+static MIGRATED: AtomicBool = AtomicBool::new(false);
+
+/// How long a trivial query takes, or an error when migrations haven't run
+/// yet or the database can't be reached
+pub async fn ping(pool: &DbPool) -> Result<Duration> {
+    if !MIGRATED.load(Ordering::Acquire) {
+        anyhow::bail!("Migrations have not run yet");
+    }
+    let started = Instant::now();
+    sqlx::query("SELECT 1").execute(pool).await?;
+    Ok(started.elapsed())
+}
+
+/// The pool read-only handlers should query: the `DATABASE_READ_URL` replica
+/// when one is configured, otherwise `pool` itself. Reads here may lag
+/// behind writes, so anything that reads back its own write uses `pool`.
+pub fn read_pool(pool: &DbPool) -> &DbPool {
+    READ_POOL.get().unwrap_or(pool)
+}
+
 async fn create_tables(pool: &SqlitePool) -> Result<()> {
     // Create tasks table
     sqlx::query(
@@ -92,12 +145,22 @@ async fn create_tables(pool: &SqlitePool) -> Result<()> {
             assignee TEXT,
             is_favorite BOOLEAN DEFAULT FALSE,
             thumbnail TEXT,
+            due_date TEXT,
+            archived BOOLEAN NOT NULL DEFAULT FALSE,
+            created_by TEXT,
+            updated_by TEXT,
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL
         )
         "#,
     ).execute(pool).await?;
 
+    // Rows from before authorship tracking keep a NULL author
+    add_column_if_missing(pool, "tasks", "created_by", "TEXT").await?;
+    add_column_if_missing(pool, "tasks", "updated_by", "TEXT").await?;
+    add_column_if_missing(pool, "tasks", "due_date", "TEXT").await?;
+    add_column_if_missing(pool, "tasks", "archived", "BOOLEAN NOT NULL DEFAULT FALSE").await?;
+
     // Create checklist_items table
     sqlx::query(
         r#"
@@ -113,6 +176,8 @@ async fn create_tables(pool: &SqlitePool) -> Result<()> {
         "#,
     ).execute(pool).await?;
 
+    add_column_if_missing(pool, "checklist_items", "source", "TEXT NOT NULL DEFAULT 'human'").await?;
+
     // Create task_dependencies table
     sqlx::query(
         r#"
@@ -141,6 +206,138 @@ async fn create_tables(pool: &SqlitePool) -> Result<()> {
         "#,
     ).execute(pool).await?;
 
+    // Create task_watchers table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_watchers (
+            task_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, user_id),
+            FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+        )
+        "#,
+    ).execute(pool).await?;
+
+    // Create task_labels table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_labels (
+            task_id TEXT NOT NULL,
+            label TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, label),
+            FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+        )
+        "#,
+    ).execute(pool).await?;
+
+    // Create epics table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS epics (
+            name TEXT PRIMARY KEY,
+            created_at TEXT NOT NULL
+        )
+        "#,
+    ).execute(pool).await?;
+
+    add_column_if_missing(pool, "epics", "milestone", "TEXT").await?;
+    add_column_if_missing(pool, "epics", "status", "TEXT").await?;
+
+    // Existing epics predate the taxonomy table
+    sqlx::query(
+        "INSERT OR IGNORE INTO epics (name, created_at)
+         SELECT DISTINCT epic, ? FROM tasks WHERE epic IS NOT NULL AND epic != ''"
+    )
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    // Create epic_sequences table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS epic_sequences (
+            epic TEXT PRIMARY KEY,
+            last_number INTEGER NOT NULL
+        )
+        "#,
+    ).execute(pool).await?;
+
+    // Create task_revisions table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_revisions (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL,
+            revision INTEGER NOT NULL,
+            snapshot TEXT NOT NULL, -- JSON string
+            created_by TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE,
+            UNIQUE(task_id, revision)
+        )
+        "#,
+    ).execute(pool).await?;
+
+    // Create thumbnails table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS thumbnails (
+            task_id TEXT PRIMARY KEY,
+            content_type TEXT NOT NULL,
+            data BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+        )
+        "#,
+    ).execute(pool).await?;
+
+    // Create webhooks table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhooks (
+            id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT NOT NULL DEFAULT '[]', -- JSON array; empty means every event
+            active BOOLEAN NOT NULL DEFAULT TRUE,
+            created_at TEXT NOT NULL
+        )
+        "#,
+    ).execute(pool).await?;
+
+    // Create webhook_deliveries table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id TEXT PRIMARY KEY,
+            webhook_id TEXT NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL, -- JSON string
+            status TEXT NOT NULL CHECK (status IN ('pending', 'delivered', 'dead')),
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at TEXT,
+            last_error TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (webhook_id) REFERENCES webhooks (id) ON DELETE CASCADE
+        )
+        "#,
+    ).execute(pool).await?;
+
+    // Create analytics_cache table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS analytics_cache (
+            dimension TEXT NOT NULL, -- status, type, priority, total or meta
+            key TEXT NOT NULL,
+            value INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (dimension, key)
+        )
+        "#,
+    ).execute(pool).await?;
+
     // Create users table
     sqlx::query(
         r#"
@@ -185,86 +382,258 @@ async fn create_tables(pool: &SqlitePool) -> Result<()> {
             date_format TEXT NOT NULL DEFAULT 'YYYY-MM-DD',
             features TEXT NOT NULL DEFAULT '{}', -- JSON string
             limits TEXT NOT NULL DEFAULT '{}', -- JSON string
+            workflow TEXT NOT NULL DEFAULT '{}', -- JSON string
+            default_sort TEXT NOT NULL DEFAULT 'updated_at:desc',
+            available_statuses TEXT, -- JSON array; NULL for the built-in statuses
+            available_priorities TEXT, -- JSON array, most urgent first; NULL for the built-in priorities
+            theme TEXT, -- JSON object; NULL for the default theme
             CHECK (id = 1)
         )
         "#,
     ).execute(pool).await?;
 
+    add_column_if_missing(pool, "workspace_config", "workflow", "TEXT NOT NULL DEFAULT '{}'").await?;
+    add_column_if_missing(pool, "workspace_config", "default_sort", "TEXT NOT NULL DEFAULT 'updated_at:desc'").await?;
+    add_column_if_missing(pool, "workspace_config", "available_statuses", "TEXT").await?;
+    add_column_if_missing(pool, "workspace_config", "available_priorities", "TEXT").await?;
+    add_column_if_missing(pool, "workspace_config", "theme", "TEXT").await?;
+    add_column_if_missing(pool, "workspace_config", "sort_tiebreaker", "TEXT NOT NULL DEFAULT 'created_at'").await?;
+    add_column_if_missing(pool, "workspace_config", "export_filename", "TEXT NOT NULL DEFAULT 'taskdown-export-{date}'").await?;
+    add_column_if_missing(pool, "workspace_config", "current_sprint", "TEXT").await?;
+    add_column_if_missing(pool, "workspace_config", "health_score_weights", "TEXT").await?;
+
     // Insert default config if not exists
     sqlx::query(
         r#"
         INSERT OR IGNORE INTO workspace_config (id, workspace_name, features, limits)
         VALUES (1, 'Taskdown Workspace', 
-               '{"realtime": false, "analytics": true, "webhooks": false, "custom_fields": false}',
-               '{"max_tasks": 10000, "max_users": 100, "api_rate_limit": 1000}')
+               '{"realtime": false, "analytics": true, "webhooks": false, "customFields": false, "ai": false}',
+               '{"maxTasks": 10000, "maxUsers": 100, "apiRateLimit": 1000}')
         "#,
     ).execute(pool).await?;
 
-    // Create indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_epic ON tasks(epic)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_sprint ON tasks(sprint)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_assignee ON tasks(assignee)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_checklist_items_task_id ON checklist_items(task_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_activities_user_id ON activities(user_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_activities_target_id ON activities(target_id)")
-        .execute(pool).await?;
+    create_indexes(pool).await?;
+
+    Ok(())
+}
+
+/// Every index the schema expects, as (name, table and columns)
+const INDEXES: &[(&str, &str)] = &[
+    ("idx_tasks_status", "tasks(status)"),
+    ("idx_tasks_epic", "tasks(epic)"),
+    ("idx_tasks_sprint", "tasks(sprint)"),
+    ("idx_tasks_assignee", "tasks(assignee)"),
+    ("idx_tasks_created_by", "tasks(created_by)"),
+    ("idx_checklist_items_task_id", "checklist_items(task_id)"),
+    ("idx_task_revisions_task_id", "task_revisions(task_id)"),
+    ("idx_webhook_deliveries_status", "webhook_deliveries(status, next_attempt_at)"),
+    ("idx_task_labels_label", "task_labels(label)"),
+    ("idx_task_watchers_user_id", "task_watchers(user_id)"),
+    ("idx_activities_user_id", "activities(user_id)"),
+    ("idx_activities_target_id", "activities(target_id)"),
+    ("idx_activities_timestamp", "activities(timestamp)"),
+];
+
+/// Creates any of `INDEXES` that don't exist, returning their names
+pub async fn create_indexes(pool: &SqlitePool) -> Result<Vec<String>> {
+    let existing: Vec<String> = sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'index'")
+        .fetch_all(pool)
+        .await?;
+
+    let mut created = Vec::new();
+    for (name, target) in INDEXES.iter().filter(|(name, _)| !existing.iter().any(|index| index == name)) {
+        sqlx::query(&format!("CREATE INDEX IF NOT EXISTS {} ON {}", name, target))
+            .execute(pool)
+            .await?;
+        created.push(name.to_string());
+    }
+    Ok(created)
+}
+
+/// Bytes the database file currently takes up
+pub async fn database_size(pool: &DbPool) -> Result<i64> {
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(pool).await?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(pool).await?;
+    Ok(page_count * page_size)
+}
+
+/// Rebuilds the database file without free pages and refreshes the query
+/// planner's statistics
+pub async fn vacuum_and_analyze(pool: &DbPool) -> Result<()> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    sqlx::query("ANALYZE").execute(pool).await?;
+    Ok(())
+}
+
+// Adds a column to a table created by an earlier version of the schema
+async fn add_column_if_missing(pool: &SqlitePool, table: &str, column: &str, definition: &str) -> Result<()> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(pool)
+        .await?;
+
+    if !rows.iter().any(|row| row.get::<String, _>("name") == column) {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(pool)
+            .await?;
+    }
 
     Ok(())
 }
 
 // Task database operations
-pub async fn get_tasks(pool: &DbPool, params: &TaskQueryParams) -> Result<Vec<Task>> {
-    let mut query = "SELECT id, title, task_type, priority, status, story_points, sprint, epic, 
-                           description, assignee, is_favorite, thumbnail, created_at, updated_at
-                     FROM tasks WHERE 1=1".to_string();
-    let mut conditions = Vec::new();
-    let mut bind_values: Vec<String> = Vec::new();
+const TASK_COLUMNS: &str = "id, title, task_type, priority, status, CAST(story_points AS REAL) AS story_points, sprint, epic,
+    description, assignee, is_favorite, thumbnail, due_date, archived, created_by, updated_by, created_at, updated_at";
+
+// A task is overdue once its due date has passed, unless it is already Done
+const OVERDUE_CONDITION: &str =
+    "(due_date IS NOT NULL AND julianday(due_date) < julianday('now') AND status != 'Done')";
+
+// A task has dependencies, or blocks others, whichever side recorded the relationship
+const HAS_DEPENDENCIES_CONDITION: &str =
+    "(EXISTS (SELECT 1 FROM task_dependencies WHERE task_dependencies.task_id = tasks.id) \
+      OR EXISTS (SELECT 1 FROM task_blocks WHERE task_blocks.blocks_task_id = tasks.id))";
+const IS_BLOCKING_CONDITION: &str =
+    "(EXISTS (SELECT 1 FROM task_blocks WHERE task_blocks.task_id = tasks.id) \
+      OR EXISTS (SELECT 1 FROM task_dependencies WHERE task_dependencies.depends_on_task_id = tasks.id))";
+
+// Fraction of a task's checklist items (acceptance criteria and technical tasks)
+// that are done; tasks without any items count as 0. Computed in SQL rather than
+// after the fetch so LIMIT/OFFSET page through the sorted order, at the cost of a
+// correlated subquery per row.
+const COMPLETION_EXPRESSION: &str =
+    "(SELECT CASE WHEN COUNT(*) = 0 THEN 0.0 ELSE CAST(SUM(completed) AS REAL) / COUNT(*) END \
+     FROM checklist_items WHERE checklist_items.task_id = tasks.id)";
+
+/// Columns tasks can be sorted by. Sort values are interpolated into SQL, so
+/// anything not listed here is never used.
+pub const SORT_COLUMNS: &[&str] = &[
+    "id", "title", "task_type", "priority", "status", "story_points", "sprint", "epic",
+    "assignee", "is_favorite", "due_date", "created_at", "updated_at", "completion",
+];
+
+/// Turns `column[:asc|desc]` into an ORDER BY expression and direction, or None
+/// if the column isn't in SORT_COLUMNS or the direction is unknown
+pub fn parse_sort(sort: &str) -> Option<(&'static str, &'static str)> {
+    match parse_sort_in(sort, SORT_COLUMNS)? {
+        ("completion", direction) => Some((COMPLETION_EXPRESSION, direction)),
+        sort => Some(sort),
+    }
+}
 
-    // Add filtering conditions
-    if let Some(epic) = &params.epic {
-        conditions.push("epic = ?");
-        bind_values.push(epic.clone());
+/// Columns users can be sorted by
+pub const USER_SORT_COLUMNS: &[&str] = &["username", "display_name", "email", "role", "is_active", "last_seen"];
+
+/// `parse_sort` for the users list, against USER_SORT_COLUMNS
+pub fn parse_user_sort(sort: &str) -> Option<(&'static str, &'static str)> {
+    parse_sort_in(sort, USER_SORT_COLUMNS)
+}
+
+fn parse_sort_in(sort: &str, columns: &[&'static str]) -> Option<(&'static str, &'static str)> {
+    let (column, direction) = sort.split_once(':').unwrap_or((sort, "asc"));
+    let direction = match direction.to_lowercase().as_str() {
+        "asc" => "ASC",
+        "desc" => "DESC",
+        _ => return None,
+    };
+
+    let column = *columns.iter().find(|&&allowed| allowed == column)?;
+    Some((column, direction))
+}
+
+// Ranks priorities by the workspace's scale, so `priority:desc` lists the
+// most urgent first. Priorities since dropped from the scale rank lowest.
+fn priority_rank(priorities: &[String], bind_values: &mut Vec<String>) -> String {
+    let mut rank = String::from("CASE priority");
+    for (index, priority) in priorities.iter().enumerate() {
+        rank.push_str(&format!(" WHEN ? THEN {}", priorities.len() - index));
+        bind_values.push(Priority::parse(priority).name().to_string());
     }
+    rank.push_str(" ELSE 0 END");
+    rank
+}
 
-    if let Some(status) = &params.status {
-        conditions.push("status = ?");
-        bind_values.push(status.clone());
+// Ranks statuses in the workspace's workflow order, so `status:asc` runs
+// from the first status to the last. Statuses since dropped rank last.
+fn status_rank(statuses: &[String], bind_values: &mut Vec<String>) -> String {
+    let mut rank = String::from("CASE status");
+    for (index, status) in statuses.iter().enumerate() {
+        rank.push_str(&format!(" WHEN ? THEN {}", index));
+        bind_values.push(TaskStatus::parse(status).stored_name().to_string());
     }
+    rank.push_str(&format!(" ELSE {} END", statuses.len()));
+    rank
+}
 
-    if let Some(assignee) = &params.assignee {
-        conditions.push("assignee = ?");
-        bind_values.push(assignee.clone());
+// `column IN (...)` over every value of a repeatable, comma-separated filter
+fn push_any_of(conditions: &mut Vec<String>, bind_values: &mut Vec<String>, column: &str, filter: &[String]) {
+    if let Some(placeholders) = bind_any_of(bind_values, filter) {
+        conditions.push(format!("{} IN ({})", column, placeholders));
     }
+}
 
-    if let Some(search) = &params.search {
-        conditions.push("(title LIKE ? OR description LIKE ?)");
-        let search_pattern = format!("%{}%", search);
-        bind_values.push(search_pattern.clone());
-        bind_values.push(search_pattern);
+// `column NOT IN (...)` over every value of a repeatable, comma-separated filter
+fn push_none_of(conditions: &mut Vec<String>, bind_values: &mut Vec<String>, column: &str, filter: &[String]) {
+    if let Some(placeholders) = bind_any_of(bind_values, filter) {
+        conditions.push(format!("{} NOT IN ({})", column, placeholders));
     }
+}
+
+/// The individual values of a repeatable, comma-separated filter
+pub fn filter_values(filter: &[String]) -> Vec<&str> {
+    filter.iter()
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// The stored form of a status filter value, whichever form it was given in:
+/// "In Progress", "InProgress" and "inprogress" all filter on `InProgress`.
+/// Custom statuses are matched as given.
+pub fn normalize_status_filter(value: &str) -> String {
+    TaskStatus::parse(value).stored_name().to_string()
+}
+
+/// The stored form of a priority filter value, ignoring the case of
+/// built-in priorities
+pub fn normalize_priority_filter(value: &str) -> String {
+    Priority::parse(value).name().to_string()
+}
+
+/// The stored form of a task type filter value, ignoring case
+pub fn normalize_type_filter(value: &str) -> String {
+    let value = value.trim();
+    ["Epic", "Story", "Task", "Bug"].into_iter()
+        .find(|task_type| task_type.eq_ignore_ascii_case(value))
+        .unwrap_or(value)
+        .to_string()
+}
+
+// The values of a repeatable, comma-separated filter in their stored form
+pub fn normalize_filter(filter: &[String], normalize: fn(&str) -> String) -> Vec<String> {
+    filter_values(filter).into_iter().map(normalize).collect()
+}
 
-    // Add conditions to query
-    for condition in conditions {
-        query.push_str(" AND ");
-        query.push_str(condition);
+// Binds the values of a repeatable, comma-separated filter, returning their
+// placeholders, or None when the filter is empty
+fn bind_any_of(bind_values: &mut Vec<String>, filter: &[String]) -> Option<String> {
+    let values = filter_values(filter);
+    if values.is_empty() {
+        return None;
     }
 
-    // Add sorting
-    let sort_column = params.sort.as_ref()
-        .and_then(|s| s.split(':').next())
-        .unwrap_or("updated_at");
-    let sort_direction = params.sort.as_ref()
-        .and_then(|s| s.split(':').nth(1))
-        .unwrap_or("desc");
-    
-    query.push_str(&format!(" ORDER BY {} {}", sort_column, sort_direction));
+    let placeholders = vec!["?"; values.len()].join(", ");
+    bind_values.extend(values.into_iter().map(String::from));
+    Some(placeholders)
+}
+
+pub async fn get_tasks(pool: &DbPool, params: &TaskQueryParams) -> Result<Vec<Task>> {
+    let (filter, mut bind_values) = task_filter(params);
+    let mut query = format!("SELECT {} FROM tasks WHERE 1=1{}", TASK_COLUMNS, filter);
+
+    // Add sorting, falling back to the workspace default
+    let config = get_workspace_config(pool).await?;
+    query.push_str(&format!(" ORDER BY {}", task_order(&config, params.sort.as_deref(), &mut bind_values)));
 
     // Add pagination
     if let Some(limit) = params.limit {
@@ -281,6 +650,160 @@ pub async fn get_tasks(pool: &DbPool, params: &TaskQueryParams) -> Result<Vec<Ta
     }
 
     let rows = sqlx_query.fetch_all(pool).await?;
+    load_task_details(pool, rows).await
+}
+
+// The ORDER BY terms for `sort`, falling back to the workspace default. Ties
+// are broken the same way every time so pages don't overlap.
+fn task_order(config: &WorkspaceConfig, sort: Option<&str>, bind_values: &mut Vec<String>) -> String {
+    task_order_keys(config, sort, bind_values).iter()
+        .map(|(expression, direction)| format!("{} {}", expression, direction))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// `task_order` as (expression, direction) pairs, ending in `id`
+fn task_order_keys(config: &WorkspaceConfig, sort: Option<&str>, bind_values: &mut Vec<String>) -> Vec<(String, &'static str)> {
+    let (sort_column, sort_direction) = sort.and_then(parse_sort)
+        .or_else(|| parse_sort(&config.default_sort))
+        .unwrap_or(("updated_at", "DESC"));
+
+    let sort_expression = match sort_column {
+        "priority" => priority_rank(&config.available_priorities, bind_values),
+        "status" => status_rank(&config.available_statuses, bind_values),
+        column => column.to_string(),
+    };
+
+    let tiebreaker = SORT_TIEBREAKERS.iter()
+        .find(|column| **column == config.sort_tiebreaker)
+        .unwrap_or(&"created_at");
+    vec![
+        (sort_expression, sort_direction),
+        (tiebreaker.to_string(), "ASC"),
+        ("id".to_string(), "ASC"),
+    ]
+}
+
+/// How many tasks match the filters in `params`, ignoring its pagination
+pub async fn count_tasks(pool: &DbPool, params: &TaskQueryParams) -> Result<u32> {
+    let (filter, bind_values) = task_filter(params);
+    let query = format!("SELECT COUNT(*) FROM tasks WHERE 1=1{}", filter);
+
+    let mut sqlx_query = sqlx::query_scalar::<_, i64>(&query);
+    for value in bind_values {
+        sqlx_query = sqlx_query.bind(value);
+    }
+
+    Ok(sqlx_query.fetch_one(pool).await? as u32)
+}
+
+// The ` AND ...` conditions for the filters in `params`, and their bind values
+fn task_filter(params: &TaskQueryParams) -> (String, Vec<String>) {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut bind_values: Vec<String> = Vec::new();
+
+    // Add filtering conditions
+    push_any_of(&mut conditions, &mut bind_values, "epic", &params.epic);
+
+    if let Some(status) = &params.status {
+        conditions.push("status = ?".to_string());
+        bind_values.push(normalize_status_filter(status));
+    }
+
+    let priorities = normalize_filter(&params.priority, normalize_priority_filter);
+    push_any_of(&mut conditions, &mut bind_values, "priority", &priorities);
+    let types = normalize_filter(&params.r#type, normalize_type_filter);
+    push_any_of(&mut conditions, &mut bind_values, "task_type", &types);
+
+    let excluded_statuses = normalize_filter(&params.exclude_status, normalize_status_filter);
+    push_none_of(&mut conditions, &mut bind_values, "status", &excluded_statuses);
+    let excluded_types = normalize_filter(&params.exclude_type, normalize_type_filter);
+    push_none_of(&mut conditions, &mut bind_values, "task_type", &excluded_types);
+
+    push_any_of(&mut conditions, &mut bind_values, "assignee", &params.assignee);
+
+    if let Some(placeholders) = bind_any_of(&mut bind_values, &params.label) {
+        conditions.push(format!("id IN (SELECT task_id FROM task_labels WHERE label IN ({}))", placeholders));
+    }
+
+    if let Some(created_by) = &params.created_by {
+        conditions.push("created_by = ?".to_string());
+        bind_values.push(created_by.clone());
+    }
+
+    if !params.include_archived.unwrap_or(false) {
+        conditions.push("archived = 0".to_string());
+    }
+
+    if !params.include_done.unwrap_or(true) {
+        conditions.push("status != 'Done'".to_string());
+    }
+
+    if params.missing_criteria.unwrap_or(false) {
+        conditions.push(
+            "NOT EXISTS (SELECT 1 FROM checklist_items WHERE checklist_items.task_id = tasks.id \
+             AND item_type = 'acceptance_criteria')".to_string()
+        );
+    }
+
+    if params.missing_estimate.unwrap_or(false) {
+        conditions.push("story_points IS NULL".to_string());
+    }
+
+    let mut point_bounds = Vec::new();
+    if let Some(min) = params.min_points {
+        point_bounds.push("story_points >= CAST(? AS REAL)");
+        bind_values.push(min.to_string());
+    }
+    if let Some(max) = params.max_points {
+        point_bounds.push("story_points <= CAST(? AS REAL)");
+        bind_values.push(max.to_string());
+    }
+    if !point_bounds.is_empty() {
+        let in_range = point_bounds.join(" AND ");
+        if params.include_unestimated.unwrap_or(false) {
+            conditions.push(format!("(story_points IS NULL OR ({}))", in_range));
+        } else {
+            conditions.push(format!("(story_points IS NOT NULL AND {})", in_range));
+        }
+    }
+
+    match params.overdue {
+        Some(true) => conditions.push(OVERDUE_CONDITION.to_string()),
+        Some(false) => conditions.push(format!("NOT {}", OVERDUE_CONDITION)),
+        None => {}
+    }
+
+    match params.has_dependencies {
+        Some(true) => conditions.push(HAS_DEPENDENCIES_CONDITION.to_string()),
+        Some(false) => conditions.push(format!("NOT {}", HAS_DEPENDENCIES_CONDITION)),
+        None => {}
+    }
+
+    match params.is_blocking {
+        Some(true) => conditions.push(IS_BLOCKING_CONDITION.to_string()),
+        Some(false) => conditions.push(format!("NOT {}", IS_BLOCKING_CONDITION)),
+        None => {}
+    }
+
+    if let Some(search) = &params.search {
+        conditions.push("(title LIKE ? OR description LIKE ?)".to_string());
+        let search_pattern = format!("%{}%", search);
+        bind_values.push(search_pattern.clone());
+        bind_values.push(search_pattern);
+    }
+
+    let filter = conditions.iter().map(|condition| format!(" AND {}", condition)).collect();
+    (filter, bind_values)
+}
+
+// Turns rows into full tasks with their checklists and relationships
+async fn load_task_details(pool: &DbPool, rows: Vec<TaskRow>) -> Result<Vec<Task>> {
+    let blocked = if get_workspace_config(pool).await?.workflow.flag_blocked_tasks {
+        Some(get_blocked_task_ids(pool, None).await?)
+    } else {
+        None
+    };
 
     let mut tasks = Vec::new();
     for row in rows {
@@ -293,6 +816,8 @@ pub async fn get_tasks(pool: &DbPool, params: &TaskQueryParams) -> Result<Vec<Ta
         // Load dependencies and blocks
         task.dependencies = get_task_relationships(pool, &task.id, "task_dependencies", "depends_on_task_id").await?;
         task.blocks = get_task_relationships(pool, &task.id, "task_blocks", "blocks_task_id").await?;
+        task.labels = get_task_labels(pool, &task.id).await?;
+        task.is_blocked = blocked.as_ref().map(|blocked| blocked.contains(&task.id));
         
         tasks.push(task);
     }
@@ -300,13 +825,159 @@ pub async fn get_tasks(pool: &DbPool, params: &TaskQueryParams) -> Result<Vec<Ta
     Ok(tasks)
 }
 
-pub async fn get_task_by_id(pool: &DbPool, task_id: &str) -> Result<Option<Task>> {
-    let row = sqlx::query_as::<_, TaskRow>(
-        "SELECT id, title, task_type, priority, status, story_points, sprint, epic, 
-                description, assignee, is_favorite, thumbnail, created_at, updated_at
-         FROM tasks WHERE id = ?"
+/// One page of unarchived tasks for the streaming export, in epic order with
+/// tasks without an epic last, and the cursor to pass for the next page.
+/// Pages pick up after the cursor's sort keys rather than at an offset, so
+/// tasks added or removed mid-export don't shift later pages.
+pub async fn get_export_page(
+    pool: &DbPool,
+    include_done: bool,
+    sort: Option<&str>,
+    after: Option<&str>,
+    limit: u32,
+) -> Result<(Vec<Task>, Option<String>)> {
+    // Epics stay contiguous so each is written under one heading; `sort`
+    // orders the tasks within them
+    let mut bind_values = Vec::new();
+    let mut keys = vec![("epic IS NULL".to_string(), "ASC"), ("epic".to_string(), "ASC")];
+    match sort {
+        Some(sort) => keys.extend(task_order_keys(&get_workspace_config(pool).await?, Some(sort), &mut bind_values)),
+        None => keys.extend([("created_at".to_string(), "ASC"), ("id".to_string(), "ASC")]),
+    }
+
+    let key_columns: Vec<String> = keys.iter().enumerate()
+        .map(|(i, (expression, _))| format!("{} AS sort_key_{}", expression, i))
+        .collect();
+    let key_names: Vec<String> = (0..keys.len()).map(|i| format!("sort_key_{}", i)).collect();
+
+    // The cursor is the last task's sort keys as a JSON array, which keeps
+    // each key's SQL type for the comparison
+    let mut query = format!(
+        "WITH keyed AS (SELECT {}, {} FROM tasks WHERE archived = 0{})
+         SELECT *, json_array({}) AS export_cursor FROM keyed",
+        TASK_COLUMNS,
+        key_columns.join(", "),
+        if include_done { "" } else { " AND status != 'Done'" },
+        key_names.join(", "),
+    );
+    if let Some(after) = after {
+        let condition = after_sort_keys(&keys);
+        bind_values.extend(std::iter::repeat_n(after.to_string(), condition.matches('?').count()));
+        query.push_str(&format!(" WHERE {}", condition));
+    }
+    let order: Vec<String> = keys.iter().zip(&key_names)
+        .map(|((_, direction), name)| format!("{} {}", name, direction))
+        .collect();
+    query.push_str(&format!(" ORDER BY {} LIMIT ?", order.join(", ")));
+
+    let mut sqlx_query = sqlx::query(&query);
+    for value in bind_values {
+        sqlx_query = sqlx_query.bind(value);
+    }
+    let rows = sqlx_query.bind(limit).fetch_all(pool).await?;
+
+    let cursor = rows.last().map(|row| row.get("export_cursor"));
+    let rows = rows.iter().map(TaskRow::from_row).collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok((load_task_details(pool, rows).await?, cursor))
+}
+
+// Matches rows that sort after the cursor bound to every `?`: equal on the
+// first keys and past it on the next. NULLs sort first ascending and last
+// descending, as SQLite orders them.
+fn after_sort_keys(keys: &[(String, &'static str)]) -> String {
+    let cursor_key = |i: usize| format!("json_extract(?, '$[{}]')", i);
+    let alternatives: Vec<String> = keys.iter().enumerate()
+        .map(|(i, (_, direction))| {
+            let mut terms: Vec<String> = (0..i)
+                .map(|j| format!("sort_key_{} IS {}", j, cursor_key(j)))
+                .collect();
+            terms.push(match *direction {
+                "DESC" => format!(
+                    "coalesce(sort_key_{i} < {c}, sort_key_{i} IS NULL AND {c} IS NOT NULL)",
+                    i = i, c = cursor_key(i),
+                ),
+                _ => format!(
+                    "coalesce(sort_key_{i} > {c}, sort_key_{i} IS NOT NULL AND {c} IS NULL)",
+                    i = i, c = cursor_key(i),
+                ),
+            });
+            format!("({})", terms.join(" AND "))
+        })
+        .collect();
+    format!("({})", alternatives.join(" OR "))
+}
+
+/// `(id, title, priority)` of unarchived tasks not Done whose priority was
+/// last set before `cutoff`: by its latest `priority_changed` activity, or
+/// when it was created if it has none. Oldest first.
+pub async fn get_open_tasks_with_priority_set_before(pool: &DbPool, cutoff: DateTime<Utc>) -> Result<Vec<(String, String, String)>> {
+    let rows = sqlx::query(
+        "SELECT id, title, priority FROM (
+             SELECT id, title, priority, created_at, COALESCE(
+                 (SELECT MAX(julianday(timestamp)) FROM activities
+                  WHERE target_id = tasks.id AND action = 'priority_changed'),
+                 julianday(created_at)
+             ) AS priority_set
+             FROM tasks WHERE archived = 0 AND status != 'Done'
+         )
+         WHERE priority_set < julianday(?)
+         ORDER BY created_at, id"
     )
-    .bind(task_id)
+    .bind(cutoff.to_rfc3339())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|row| (row.get("id"), row.get("title"), row.get("priority"))).collect())
+}
+
+/// Unarchived tasks `user_id` created or last updated, most recently updated first
+pub async fn get_recent_tasks(pool: &DbPool, user_id: &str, limit: u32) -> Result<Vec<Task>> {
+    let rows = sqlx::query_as::<_, TaskRow>(&format!(
+        "SELECT {} FROM tasks WHERE archived = 0 AND (created_by = ? OR updated_by = ?)
+         ORDER BY updated_at DESC, id ASC LIMIT ?",
+        TASK_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    load_task_details(pool, rows).await
+}
+
+/// The stored id of the task `task_id` refers to. With `case_insensitive_ids`
+/// off this is `task_id` as given, whether or not the task exists. With it on,
+/// an exact match wins, and an id that matches one task ignoring case
+/// resolves to it too.
+pub async fn canonical_task_id(pool: &DbPool, task_id: &str) -> Result<Option<String>> {
+    if !get_workspace_config(pool).await?.workflow.case_insensitive_ids {
+        return Ok(Some(task_id.to_string()));
+    }
+
+    if task_exists(pool, task_id).await? {
+        return Ok(Some(task_id.to_string()));
+    }
+
+    let rows = sqlx::query("SELECT id FROM tasks WHERE id = ? COLLATE NOCASE LIMIT 2")
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+
+    // Ids differing only in case are ambiguous
+    match rows.as_slice() {
+        [row] => Ok(Some(row.get("id"))),
+        _ => Ok(None),
+    }
+}
+
+pub async fn get_task_by_id(pool: &DbPool, task_id: &str) -> Result<Option<Task>> {
+    let Some(task_id) = canonical_task_id(pool, task_id).await? else {
+        return Ok(None);
+    };
+
+    let row = sqlx::query_as::<_, TaskRow>(&format!("SELECT {} FROM tasks WHERE id = ?", TASK_COLUMNS))
+    .bind(&task_id)
     .fetch_optional(pool)
     .await?;
 
@@ -320,6 +991,10 @@ pub async fn get_task_by_id(pool: &DbPool, task_id: &str) -> Result<Option<Task>
         // Load dependencies and blocks
         task.dependencies = get_task_relationships(pool, &task.id, "task_dependencies", "depends_on_task_id").await?;
         task.blocks = get_task_relationships(pool, &task.id, "task_blocks", "blocks_task_id").await?;
+        task.labels = get_task_labels(pool, &task.id).await?;
+        if get_workspace_config(pool).await?.workflow.flag_blocked_tasks {
+            task.is_blocked = Some(get_blocked_task_ids(pool, Some(&task.id)).await?.contains(&task.id));
+        }
         
         Ok(Some(task))
     } else {
@@ -327,20 +1002,95 @@ pub async fn get_task_by_id(pool: &DbPool, task_id: &str) -> Result<Option<Task>
     }
 }
 
-pub async fn create_task(pool: &DbPool, request: &CreateTaskRequest) -> Result<Task> {
-    let id = uuid::Uuid::new_v4().to_string();
-    let now = Utc::now();
+pub async fn create_task(pool: &DbPool, request: &CreateTaskRequest, user_id: Option<&str>) -> Result<Task> {
+    let config = get_workspace_config(pool).await?;
+    let scoped = match &request.epic {
+        Some(epic) if config.workflow.epic_scoped_ids => epic_id_prefix(epic).map(|prefix| (epic, prefix)),
+        _ => None,
+    };
+    let Some((epic, prefix)) = scoped else {
+        return create_task_with_id(pool, &uuid::Uuid::new_v4().to_string(), request, user_id).await;
+    };
+
+    // The number is only taken if the task row is written with it
+    ensure_epic(pool, epic).await?;
+    let mut tx = pool.begin().await?;
+    let id = next_epic_task_id(&mut tx, &prefix).await?;
+    insert_task_row(&mut *tx, &id, request, user_id).await?;
+    tx.commit().await?;
+
+    finish_task_create(pool, &id, request, user_id).await
+}
 
-    sqlx::query(
+/// The prefix of epic-scoped ids for tasks in `epic`: the name upper-cased,
+/// with anything but letters and digits turned into dashes. None when the
+/// name has no letters or digits at all.
+fn epic_id_prefix(epic: &str) -> Option<String> {
+    let prefix = epic.to_uppercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    (!prefix.is_empty()).then_some(prefix)
+}
+
+/// The next `<PREFIX>-<n>` id, skipping numbers already taken (e.g. by
+/// imported tasks)
+async fn next_epic_task_id(conn: &mut sqlx::SqliteConnection, prefix: &str) -> Result<String> {
+    loop {
+        // A single upsert, so concurrent creates never get the same number
+        let number: i64 = sqlx::query(
+            "INSERT INTO epic_sequences (epic, last_number) VALUES (?, 1)
+             ON CONFLICT (epic) DO UPDATE SET last_number = last_number + 1
+             RETURNING last_number"
+        )
+        .bind(prefix)
+        .fetch_one(&mut *conn)
+        .await?
+        .get("last_number");
+
+        let id = format!("{}-{}", prefix, number);
+        let taken = sqlx::query("SELECT 1 FROM tasks WHERE id = ?")
+            .bind(&id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if taken.is_none() {
+            return Ok(id);
+        }
+    }
+}
+
+pub async fn create_task_with_id(
+    pool: &DbPool,
+    id: &str,
+    request: &CreateTaskRequest,
+    user_id: Option<&str>,
+) -> Result<Task> {
+    if let Some(epic) = &request.epic {
+        ensure_epic(pool, epic).await?;
+    }
+
+    insert_task_row(pool, id, request, user_id).await?;
+    finish_task_create(pool, id, request, user_id).await
+}
+
+async fn insert_task_row<'e, E>(executor: E, id: &str, request: &CreateTaskRequest, user_id: Option<&str>) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let now = Utc::now();
+
+    sqlx::query(
         "INSERT INTO tasks (id, title, task_type, priority, status, story_points, sprint, epic, 
-                           description, assignee, is_favorite, thumbnail, created_at, updated_at)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                           description, assignee, is_favorite, thumbnail, due_date, created_by, updated_by,
+                           created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
-    .bind(&id)
+    .bind(id)
     .bind(&request.title)
     .bind(format!("{:?}", request.r#type))
-    .bind(format!("{:?}", request.priority))
-    .bind(format!("{:?}", request.status))
+    .bind(request.priority.name())
+    .bind(request.status.stored_name())
     .bind(request.story_points)
     .bind(&request.sprint)
     .bind(&request.epic)
@@ -348,83 +1098,156 @@ pub async fn create_task(pool: &DbPool, request: &CreateTaskRequest) -> Result<T
     .bind(&request.assignee)
     .bind(request.is_favorite.unwrap_or(false))
     .bind(&request.thumbnail)
+    .bind(request.due_date.map(|date| date.to_rfc3339()))
+    .bind(user_id)
+    .bind(user_id)
     .bind(now.to_rfc3339())
     .bind(now.to_rfc3339())
-    .execute(pool)
+    .execute(executor)
     .await?;
 
+    Ok(())
+}
+
+/// Saves the rest of a task whose row was just inserted (checklists,
+/// relationships, the assignee as a watcher) and returns it
+async fn finish_task_create(pool: &DbPool, id: &str, request: &CreateTaskRequest, user_id: Option<&str>) -> Result<Task> {
     // Save checklist items
-    save_checklist_items(pool, &id, &request.acceptance_criteria, "acceptance_criteria").await?;
-    save_checklist_items(pool, &id, &request.technical_tasks, "technical_tasks").await?;
+    save_checklist_items(pool, id, &request.acceptance_criteria, "acceptance_criteria").await?;
+    save_checklist_items(pool, id, &request.technical_tasks, "technical_tasks").await?;
 
     // Save dependencies and blocks
-    save_task_relationships(pool, &id, &request.dependencies, "task_dependencies", "depends_on_task_id").await?;
-    save_task_relationships(pool, &id, &request.blocks, "task_blocks", "blocks_task_id").await?;
+    save_task_relationships(pool, id, &request.dependencies, "task_dependencies", "depends_on_task_id").await?;
+    save_task_relationships(pool, id, &request.blocks, "task_blocks", "blocks_task_id").await?;
+
+    if let Some(assignee) = &request.assignee {
+        add_task_watcher(pool, id, assignee).await?;
+    }
+
+    adjust_analytics_cache(pool, None, counted_facts(pool, id).await?.as_ref()).await?;
+    sync_epic_status(pool, request.epic.as_deref()).await?;
 
     // Fetch and return the created task
-    let task = get_task_by_id(pool, &id).await?.unwrap();
+    let task = get_task_by_id(pool, id).await?.unwrap();
+    record_task_revision(pool, &task, user_id).await?;
     Ok(task)
 }
 
-pub async fn update_task(pool: &DbPool, task_id: &str, request: &UpdateTaskRequest) -> Result<()> {
+pub async fn update_task(
+    pool: &DbPool,
+    task_id: &str,
+    request: &UpdateTaskRequest,
+    user_id: Option<&str>,
+) -> Result<()> {
     let now = Utc::now();
-    
-    let mut update_fields = Vec::new();
-    let mut params: Vec<Box<dyn sqlx::Encode<'_, Sqlite> + Send + 'static>> = Vec::new();
+    let counted_before = counted_facts(pool, task_id).await?;
+    let epic_before = task_epic(pool, task_id).await?;
+
+    let mut query = sqlx::QueryBuilder::<Sqlite>::new("UPDATE tasks SET ");
+    let mut fields = query.separated(", ");
 
     if let Some(title) = &request.title {
-        update_fields.push("title = ?");
-        params.push(Box::new(title.clone()));
+        fields.push("title = ").push_bind_unseparated(title.clone());
     }
 
     if let Some(task_type) = &request.r#type {
-        update_fields.push("task_type = ?");
-        params.push(Box::new(format!("{:?}", task_type)));
+        fields.push("task_type = ").push_bind_unseparated(format!("{:?}", task_type));
     }
 
     if let Some(priority) = &request.priority {
-        update_fields.push("priority = ?");
-        params.push(Box::new(format!("{:?}", priority)));
+        fields.push("priority = ").push_bind_unseparated(priority.name().to_string());
     }
 
     if let Some(status) = &request.status {
-        update_fields.push("status = ?");
-        params.push(Box::new(format!("{:?}", status)));
+        fields.push("status = ").push_bind_unseparated(status.stored_name().to_string());
     }
 
     if let Some(story_points) = &request.story_points {
-        update_fields.push("story_points = ?");
-        params.push(Box::new(*story_points));
+        fields.push("story_points = ").push_bind_unseparated(*story_points);
+    }
+
+    if let Some(sprint) = &request.sprint {
+        fields.push("sprint = ").push_bind_unseparated(sprint.clone());
+    }
+
+    if let Some(epic) = &request.epic {
+        fields.push("epic = ").push_bind_unseparated(epic.clone());
     }
 
     if let Some(description) = &request.description {
-        update_fields.push("description = ?");
-        params.push(Box::new(description.clone()));
+        fields.push("description = ").push_bind_unseparated(description.clone());
     }
 
-    update_fields.push("updated_at = ?");
-    params.push(Box::new(now.to_rfc3339()));
+    if let Some(assignee) = &request.assignee {
+        fields.push("assignee = ").push_bind_unseparated(assignee.clone());
+    }
 
-    if !update_fields.is_empty() {
-        // Simple update - in real implementation you'd build the query dynamically
-        sqlx::query(
-            "UPDATE tasks SET updated_at = ? WHERE id = ?"
-        )
-        .bind(now.to_rfc3339())
-        .bind(task_id)
-        .execute(pool)
-        .await?;
+    if let Some(is_favorite) = &request.is_favorite {
+        fields.push("is_favorite = ").push_bind_unseparated(*is_favorite);
+    }
+
+    if let Some(thumbnail) = &request.thumbnail {
+        fields.push("thumbnail = ").push_bind_unseparated(thumbnail.clone());
+    }
+
+    if let Some(due_date) = &request.due_date {
+        fields.push("due_date = ").push_bind_unseparated(due_date.map(|date| date.to_rfc3339()));
+    }
+
+    if let Some(Some(epic)) = &request.epic {
+        ensure_epic(pool, epic).await?;
+    }
+
+    fields.push("updated_by = ").push_bind_unseparated(user_id.map(str::to_string));
+    fields.push("updated_at = ").push_bind_unseparated(now.to_rfc3339());
+
+    query.push(" WHERE id = ").push_bind(task_id);
+    query.build().execute(pool).await?;
+
+    adjust_analytics_cache(pool, counted_before.as_ref(), counted_facts(pool, task_id).await?.as_ref()).await?;
+    let epic_after = task_epic(pool, task_id).await?;
+    sync_epic_status(pool, epic_before.as_deref()).await?;
+    if epic_after != epic_before {
+        sync_epic_status(pool, epic_after.as_deref()).await?;
+    }
+
+    // Replace checklist items and relationships only when provided
+    if let Some(items) = &request.acceptance_criteria {
+        save_checklist_items(pool, task_id, items, "acceptance_criteria").await?;
+    }
+    if let Some(items) = &request.technical_tasks {
+        save_checklist_items(pool, task_id, items, "technical_tasks").await?;
+    }
+    if let Some(dependencies) = &request.dependencies {
+        save_task_relationships(pool, task_id, dependencies, "task_dependencies", "depends_on_task_id").await?;
+    }
+    if let Some(blocks) = &request.blocks {
+        save_task_relationships(pool, task_id, blocks, "task_blocks", "blocks_task_id").await?;
+    }
+
+    // Assignees automatically follow the tasks they're given
+    if let Some(Some(assignee)) = &request.assignee {
+        add_task_watcher(pool, task_id, assignee).await?;
+    }
+
+    if let Some(task) = get_task_by_id(pool, task_id).await? {
+        record_task_revision(pool, &task, user_id).await?;
     }
 
     Ok(())
 }
 
 pub async fn delete_task(pool: &DbPool, task_id: &str) -> Result<()> {
+    let counted_before = counted_facts(pool, task_id).await?;
+    let epic = task_epic(pool, task_id).await?;
+
     sqlx::query("DELETE FROM tasks WHERE id = ?")
         .bind(task_id)
         .execute(pool)
         .await?;
-    
+
+    adjust_analytics_cache(pool, counted_before.as_ref(), None).await?;
+    sync_epic_status(pool, epic.as_deref()).await?;
     Ok(())
 }
 
@@ -434,7 +1257,7 @@ async fn get_checklist_items(
     item_type: &str,
 ) -> Result<Vec<ChecklistItem>> {
     let rows = sqlx::query(
-        "SELECT id, text, completed FROM checklist_items 
+        "SELECT id, text, completed, source FROM checklist_items 
          WHERE task_id = ? AND item_type = ? ORDER BY sort_order"
     )
     .bind(task_id)
@@ -448,6 +1271,7 @@ async fn get_checklist_items(
             id: Some(row.get::<String, _>("id")),
             text: row.get::<String, _>("text"),
             completed: row.get::<bool, _>("completed"),
+            source: Some(row.get::<String, _>("source")),
         });
     }
 
@@ -478,6 +1302,22 @@ async fn get_task_relationships(
     Ok(relationships)
 }
 
+/// Ids of tasks with at least one dependency that isn't Done, limited to
+/// `task_id` when given; one query however many tasks are being loaded
+async fn get_blocked_task_ids(pool: &DbPool, task_id: Option<&str>) -> Result<std::collections::HashSet<String>> {
+    let mut query = sqlx::QueryBuilder::<Sqlite>::new(
+        "SELECT DISTINCT d.task_id FROM task_dependencies d
+         JOIN tasks t ON t.id = d.depends_on_task_id
+         WHERE t.status != 'Done'"
+    );
+    if let Some(task_id) = task_id {
+        query.push(" AND d.task_id = ").push_bind(task_id);
+    }
+
+    let rows = query.build().fetch_all(pool).await?;
+    Ok(rows.iter().map(|row| row.get("task_id")).collect())
+}
+
 async fn save_checklist_items(
     pool: &DbPool,
     task_id: &str,
@@ -491,12 +1331,22 @@ async fn save_checklist_items(
         .execute(pool)
         .await?;
 
+    // Keep only the first of items whose text matches ignoring case and spacing
+    let mut items: Vec<&ChecklistItem> = items.iter().collect();
+    if get_workspace_config(pool).await?.workflow.dedupe_checklist_items {
+        let mut seen = std::collections::HashSet::new();
+        items.retain(|item| {
+            let text = item.text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            seen.insert(text)
+        });
+    }
+
     // Insert new items
     for (index, item) in items.iter().enumerate() {
         let id = item.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
         sqlx::query(
-            "INSERT INTO checklist_items (id, task_id, item_type, text, completed, sort_order)
-             VALUES (?, ?, ?, ?, ?, ?)"
+            "INSERT INTO checklist_items (id, task_id, item_type, text, completed, sort_order, source)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(id)
         .bind(task_id)
@@ -504,6 +1354,7 @@ async fn save_checklist_items(
         .bind(&item.text)
         .bind(item.completed)
         .bind(index as i32)
+        .bind(item.source.as_deref().unwrap_or("human"))
         .execute(pool)
         .await?;
     }
@@ -511,6 +1362,47 @@ async fn save_checklist_items(
     Ok(())
 }
 
+/// Changes one checklist item in place, leaving the task's other items
+/// alone. False when the task has no such item.
+pub async fn update_checklist_item(
+    pool: &DbPool,
+    task_id: &str,
+    item_type: &str,
+    item_id: &str,
+    patch: &ChecklistItemPatch,
+    user_id: Option<&str>,
+) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        "UPDATE checklist_items SET text = COALESCE(?, text), completed = COALESCE(?, completed)
+         WHERE id = ? AND task_id = ? AND item_type = ?"
+    )
+    .bind(&patch.text)
+    .bind(patch.completed)
+    .bind(item_id)
+    .bind(task_id)
+    .bind(item_type)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("UPDATE tasks SET updated_by = ?, updated_at = ? WHERE id = ?")
+        .bind(user_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(task_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    if let Some(task) = get_task_by_id(pool, task_id).await? {
+        record_task_revision(pool, &task, user_id).await?;
+    }
+    Ok(true)
+}
+
 async fn save_task_relationships(
     pool: &DbPool,
     task_id: &str,
@@ -518,11 +1410,23 @@ async fn save_task_relationships(
     table_name: &str,
     column_name: &str,
 ) -> Result<()> {
+    let symmetric = get_workspace_config(pool).await?.workflow.enforce_symmetric_relations;
+    let mut tx = pool.begin().await?;
+
+    let previous: Vec<String> = if symmetric {
+        sqlx::query_scalar(&format!("SELECT {} FROM {} WHERE task_id = ?", column_name, table_name))
+            .bind(task_id)
+            .fetch_all(&mut *tx)
+            .await?
+    } else {
+        Vec::new()
+    };
+
     // Delete existing relationships
     let delete_query = format!("DELETE FROM {} WHERE task_id = ?", table_name);
     sqlx::query(&delete_query)
         .bind(task_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
     // Insert new relationships
@@ -536,140 +1440,2222 @@ async fn save_task_relationships(
             .bind(&id)
             .bind(task_id)
             .bind(related_id)
-            .execute(pool)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // Keep the other side in step: A blocking B means B depends on A
+    if symmetric {
+        let (inverse_table, inverse_column) = match table_name {
+            "task_blocks" => ("task_dependencies", "depends_on_task_id"),
+            _ => ("task_blocks", "blocks_task_id"),
+        };
+
+        for removed_id in previous.iter().filter(|id| !related_ids.contains(id)) {
+            sqlx::query(&format!("DELETE FROM {} WHERE task_id = ? AND {} = ?", inverse_table, inverse_column))
+                .bind(removed_id)
+                .bind(task_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for related_id in related_ids.iter().filter(|id| id.as_str() != task_id) {
+            sqlx::query(&format!(
+                "INSERT INTO {table} (id, task_id, {column})
+                 SELECT ?, ?, ?
+                 WHERE EXISTS (SELECT 1 FROM tasks WHERE id = ?)
+                   AND NOT EXISTS (SELECT 1 FROM {table} WHERE task_id = ? AND {column} = ?)",
+                table = inverse_table,
+                column = inverse_column,
+            ))
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(related_id)
+            .bind(task_id)
+            .bind(related_id)
+            .bind(related_id)
+            .bind(task_id)
+            .execute(&mut *tx)
             .await?;
+        }
     }
 
+    tx.commit().await?;
     Ok(())
 }
 
-pub async fn get_task_count(pool: &DbPool) -> Result<u32> {
-    let row = sqlx::query("SELECT COUNT(*) as count FROM tasks")
-        .fetch_one(pool)
+pub async fn task_exists(pool: &DbPool, task_id: &str) -> Result<bool> {
+    let row = sqlx::query("SELECT 1 FROM tasks WHERE id = ?")
+        .bind(task_id)
+        .fetch_optional(pool)
         .await?;
-    
-    Ok(row.get::<i64, _>("count") as u32)
+
+    Ok(row.is_some())
 }
 
-pub async fn get_workspace_config(pool: &DbPool) -> Result<WorkspaceConfig> {
-    let row = sqlx::query(
-        "SELECT workspace_name, timezone, date_format, features, limits FROM workspace_config WHERE id = 1"
+pub async fn set_task_relationships(
+    pool: &DbPool,
+    task_id: &str,
+    dependencies: &[String],
+    blocks: &[String],
+) -> Result<()> {
+    save_task_relationships(pool, task_id, dependencies, "task_dependencies", "depends_on_task_id").await?;
+    save_task_relationships(pool, task_id, blocks, "task_blocks", "blocks_task_id").await
+}
+
+/// Every relationship as a `(blocker, blocked)` pair, whichever side recorded it
+pub async fn get_dependency_edges(pool: &DbPool) -> Result<Vec<(String, String)>> {
+    let rows = sqlx::query(
+        "SELECT depends_on_task_id AS blocker, task_id AS blocked FROM task_dependencies
+         UNION
+         SELECT task_id AS blocker, blocks_task_id AS blocked FROM task_blocks"
     )
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await?;
 
-    let features_json: String = row.get("features");
-    let limits_json: String = row.get("limits");
+    Ok(rows.iter().map(|row| (row.get("blocker"), row.get("blocked"))).collect())
+}
 
-    let features: WorkspaceFeatures = serde_json::from_str(&features_json)?;
-    let limits: WorkspaceLimits = serde_json::from_str(&limits_json)?;
+// Epic taxonomy operations
+pub async fn ensure_epic(pool: &DbPool, name: &str) -> Result<()> {
+    sqlx::query("INSERT OR IGNORE INTO epics (name, created_at) VALUES (?, ?)")
+        .bind(name)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
 
-    Ok(WorkspaceConfig {
-        workspace_name: row.get("workspace_name"),
-        timezone: row.get("timezone"),
-        date_format: row.get("date_format"),
-        features,
-        limits,
-    })
+    Ok(())
 }
 
-// Analytics functions
-pub async fn get_tasks_by_status(pool: &DbPool) -> Result<std::collections::HashMap<String, u32>> {
-    let rows = sqlx::query("SELECT status, COUNT(*) as count FROM tasks GROUP BY status")
+// Epics with the status counts of their unarchived tasks
+const EPIC_SELECT: &str =
+    "SELECT e.name, e.milestone, e.status, e.created_at, COUNT(t.id) AS total,
+            COUNT(CASE WHEN t.status = 'Done' THEN 1 END) AS done,
+            COUNT(CASE WHEN t.status = 'Todo' THEN 1 END) AS todo
+     FROM epics e LEFT JOIN tasks t ON t.epic = e.name AND t.archived = 0";
+
+pub async fn get_epics(pool: &DbPool) -> Result<Vec<Epic>> {
+    let rows = sqlx::query(&format!("{} GROUP BY e.name ORDER BY e.name", EPIC_SELECT))
         .fetch_all(pool)
         .await?;
-    
-    let mut result = std::collections::HashMap::new();
-    for row in rows {
-        let status: String = row.get("status");
-        let count: i64 = row.get("count");
-        result.insert(status, count as u32);
-    }
-    
-    Ok(result)
+
+    rows.iter().map(epic_from_row).collect()
 }
 
-pub async fn get_tasks_by_type(pool: &DbPool) -> Result<std::collections::HashMap<String, u32>> {
-    let rows = sqlx::query("SELECT task_type, COUNT(*) as count FROM tasks GROUP BY task_type")
-        .fetch_all(pool)
+pub async fn get_epic(pool: &DbPool, name: &str) -> Result<Option<Epic>> {
+    let row = sqlx::query(&format!("{} WHERE e.name = ? GROUP BY e.name", EPIC_SELECT))
+        .bind(name)
+        .fetch_optional(pool)
         .await?;
-    
-    let mut result = std::collections::HashMap::new();
-    for row in rows {
-        let task_type: String = row.get("task_type");
-        let count: i64 = row.get("count");
-        result.insert(task_type, count as u32);
-    }
-    
-    Ok(result)
+
+    row.as_ref().map(epic_from_row).transpose()
 }
 
-pub async fn get_tasks_by_priority(pool: &DbPool) -> Result<std::collections::HashMap<String, u32>> {
-    let rows = sqlx::query("SELECT priority, COUNT(*) as count FROM tasks GROUP BY priority")
-        .fetch_all(pool)
-        .await?;
-    
-    let mut result = std::collections::HashMap::new();
-    for row in rows {
-        let priority: String = row.get("priority");
-        let count: i64 = row.get("count");
-        result.insert(priority, count as u32);
+fn epic_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Epic> {
+    let created_at: String = row.get("created_at");
+    let status: Option<String> = row.get("status");
+    Ok(Epic {
+        name: row.get("name"),
+        milestone: row.get("milestone"),
+        status: status.as_deref().map(TaskStatus::parse),
+        derived_status: derived_epic_status(row.get("total"), row.get("done"), row.get("todo")),
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+    })
+}
+
+/// Done once every task is Done, Todo while none has started, and In
+/// Progress in between; None for an epic without tasks
+fn derived_epic_status(total: i64, done: i64, todo: i64) -> Option<TaskStatus> {
+    match total {
+        0 => None,
+        _ if done == total => Some(TaskStatus::Done),
+        _ if todo == total => Some(TaskStatus::Todo),
+        _ => Some(TaskStatus::InProgress),
     }
-    
-    Ok(result)
 }
 
-pub async fn get_average_story_points(pool: &DbPool) -> Result<f32> {
-    let row = sqlx::query("SELECT AVG(CAST(story_points as REAL)) as avg_points FROM tasks WHERE story_points IS NOT NULL")
-        .fetch_one(pool)
+async fn task_epic(pool: &DbPool, task_id: &str) -> Result<Option<String>> {
+    let epic = sqlx::query_scalar::<_, Option<String>>("SELECT epic FROM tasks WHERE id = ?")
+        .bind(task_id)
+        .fetch_optional(pool)
         .await?;
-    
-    Ok(row.get::<Option<f64>, _>("avg_points").unwrap_or(0.0) as f32)
+
+    Ok(epic.flatten())
 }
 
-pub async fn get_completion_rate(pool: &DbPool) -> Result<f32> {
-    let total_row = sqlx::query("SELECT COUNT(*) as total FROM tasks")
-        .fetch_one(pool)
-        .await?;
-    let total: i64 = total_row.get("total");
-    
-    if total == 0 {
-        return Ok(0.0);
+/// With `workflow.auto_epic_status` on, stores the epic's derived status as
+/// its taxonomy status after one of its tasks changed
+async fn sync_epic_status(pool: &DbPool, epic: Option<&str>) -> Result<()> {
+    let Some(epic) = epic else {
+        return Ok(());
+    };
+    if !get_workspace_config(pool).await?.workflow.auto_epic_status {
+        return Ok(());
     }
-    
-    let completed_row = sqlx::query("SELECT COUNT(*) as completed FROM tasks WHERE status = 'Done'")
-        .fetch_one(pool)
+
+    let status = get_epic(pool, epic).await?.and_then(|epic| epic.derived_status);
+    sqlx::query("UPDATE epics SET status = ? WHERE name = ?")
+        .bind(status.as_ref().map(TaskStatus::stored_name))
+        .bind(epic)
+        .execute(pool)
         .await?;
-    let completed: i64 = completed_row.get("completed");
-    
-    Ok((completed as f32 / total as f32) * 100.0)
+
+    Ok(())
 }
 
-pub async fn get_active_sprints(pool: &DbPool) -> Result<Vec<String>> {
-    let rows = sqlx::query("SELECT DISTINCT sprint FROM tasks WHERE sprint IS NOT NULL AND sprint != '' AND status != 'Done'")
-        .fetch_all(pool)
+/// False when no epic has this name
+pub async fn set_epic_milestone(pool: &DbPool, name: &str, milestone: Option<&str>) -> Result<bool> {
+    let result = sqlx::query("UPDATE epics SET milestone = ? WHERE name = ?")
+        .bind(milestone)
+        .bind(name)
+        .execute(pool)
         .await?;
-    
-    let mut sprints = Vec::new();
-    for row in rows {
-        let sprint: String = row.get("sprint");
-        sprints.push(sprint);
-    }
-    
-    Ok(sprints)
+
+    Ok(result.rows_affected() > 0)
 }
 
-// Import/Export functions
-pub async fn clear_all_tasks(pool: &DbPool) -> Result<()> {
-    // Delete in correct order due to foreign key constraints
-    sqlx::query("DELETE FROM task_dependencies").execute(pool).await?;
-    sqlx::query("DELETE FROM task_blocks").execute(pool).await?;
-    sqlx::query("DELETE FROM checklist_items").execute(pool).await?;
-    sqlx::query("DELETE FROM tasks").execute(pool).await?;
+// Task revision operations
+async fn record_task_revision(pool: &DbPool, task: &Task, user_id: Option<&str>) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO task_revisions (id, task_id, revision, snapshot, created_by, created_at)
+         SELECT ?, ?, COALESCE(MAX(revision), 0) + 1, ?, ?, ? FROM task_revisions WHERE task_id = ?"
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&task.id)
+    .bind(serde_json::to_string(task)?)
+    .bind(user_id)
+    .bind(Utc::now().to_rfc3339())
+    .bind(&task.id)
+    .execute(pool)
+    .await?;
+
     Ok(())
 }
 
-pub async fn get_all_tasks_for_export(pool: &DbPool) -> Result<Vec<Task>> {
-    let params = TaskQueryParams::default();
-    get_tasks(pool, &params).await
-}
\ No newline at end of file
+fn task_revision_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<TaskRevision> {
+    let snapshot: String = row.get("snapshot");
+    let created_at: String = row.get("created_at");
+
+    Ok(TaskRevision {
+        task_id: row.get("task_id"),
+        revision: row.get::<i64, _>("revision") as u32,
+        snapshot: serde_json::from_str(&snapshot)?,
+        created_by: row.get("created_by"),
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+    })
+}
+
+pub async fn get_task_revisions(pool: &DbPool, task_id: &str) -> Result<Vec<TaskRevision>> {
+    let rows = sqlx::query(
+        "SELECT task_id, revision, snapshot, created_by, created_at FROM task_revisions
+         WHERE task_id = ? ORDER BY revision"
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(task_revision_from_row).collect()
+}
+
+pub async fn get_task_revision(pool: &DbPool, task_id: &str, revision: u32) -> Result<Option<TaskRevision>> {
+    let row = sqlx::query(
+        "SELECT task_id, revision, snapshot, created_by, created_at FROM task_revisions
+         WHERE task_id = ? AND revision = ?"
+    )
+    .bind(task_id)
+    .bind(revision as i64)
+    .fetch_optional(pool)
+    .await?;
+
+    row.as_ref().map(task_revision_from_row).transpose()
+}
+
+// Puts a task back to a stored revision; the restore itself becomes a new revision
+pub async fn restore_task_revision(
+    pool: &DbPool,
+    task_id: &str,
+    revision: u32,
+    user_id: Option<&str>,
+) -> Result<Option<Task>> {
+    let Some(revision) = get_task_revision(pool, task_id, revision).await? else {
+        return Ok(None);
+    };
+
+    update_task(pool, task_id, &UpdateTaskRequest::from(revision.snapshot), user_id).await?;
+    get_task_by_id(pool, task_id).await
+}
+
+// Task label operations
+pub async fn get_task_labels(pool: &DbPool, task_id: &str) -> Result<Vec<String>> {
+    let rows = sqlx::query("SELECT label FROM task_labels WHERE task_id = ? ORDER BY label")
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(|row| row.get::<String, _>("label")).collect())
+}
+
+/// Adds (or, with `assign` false, removes) `label` on every task in one
+/// transaction. Returns how many tasks changed; only those get a new `updated_at`.
+pub async fn set_task_label(pool: &DbPool, label: &str, task_ids: &[String], assign: bool) -> Result<u64> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    let mut changed = 0;
+    for task_id in task_ids {
+        let result = if assign {
+            sqlx::query("INSERT OR IGNORE INTO task_labels (task_id, label, created_at) VALUES (?, ?, ?)")
+                .bind(task_id)
+                .bind(label)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?
+        } else {
+            sqlx::query("DELETE FROM task_labels WHERE task_id = ? AND label = ?")
+                .bind(task_id)
+                .bind(label)
+                .execute(&mut *tx)
+                .await?
+        };
+
+        if result.rows_affected() > 0 {
+            sqlx::query("UPDATE tasks SET updated_at = ? WHERE id = ?")
+                .bind(&now)
+                .bind(task_id)
+                .execute(&mut *tx)
+                .await?;
+            changed += 1;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(changed)
+}
+
+// Task watcher operations
+pub async fn add_task_watcher(pool: &DbPool, task_id: &str, user_id: &str) -> Result<()> {
+    sqlx::query("INSERT OR IGNORE INTO task_watchers (task_id, user_id, created_at) VALUES (?, ?, ?)")
+        .bind(task_id)
+        .bind(user_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn remove_task_watcher(pool: &DbPool, task_id: &str, user_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM task_watchers WHERE task_id = ? AND user_id = ?")
+        .bind(task_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_task_watchers(pool: &DbPool, task_id: &str) -> Result<Vec<String>> {
+    let rows = sqlx::query("SELECT user_id FROM task_watchers WHERE task_id = ? ORDER BY created_at")
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(|row| row.get::<String, _>("user_id")).collect())
+}
+
+/// Stores a task's thumbnail image and points the task's `thumbnail` at `url`
+pub async fn save_task_thumbnail(pool: &DbPool, task_id: &str, content_type: &str, data: &[u8], url: &str) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO thumbnails (task_id, content_type, data, created_at) VALUES (?, ?, ?, ?)
+         ON CONFLICT(task_id) DO UPDATE SET content_type = excluded.content_type,
+                                            data = excluded.data,
+                                            created_at = excluded.created_at"
+    )
+    .bind(task_id)
+    .bind(content_type)
+    .bind(data)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE tasks SET thumbnail = ?, updated_at = ? WHERE id = ?")
+        .bind(url)
+        .bind(&now)
+        .bind(task_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Content type and bytes of a task's stored thumbnail
+pub async fn get_task_thumbnail(pool: &DbPool, task_id: &str) -> Result<Option<(String, Vec<u8>)>> {
+    let row = sqlx::query("SELECT content_type, data FROM thumbnails WHERE task_id = ?")
+        .bind(task_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| (row.get("content_type"), row.get("data"))))
+}
+
+// Webhook subscriptions
+pub async fn create_webhook(pool: &DbPool, webhook: &Webhook) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO webhooks (id, url, secret, events, active, created_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&webhook.id)
+    .bind(&webhook.url)
+    .bind(&webhook.secret)
+    .bind(serde_json::to_string(&webhook.events)?)
+    .bind(webhook.active)
+    .bind(webhook.created_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_webhooks(pool: &DbPool) -> Result<Vec<Webhook>> {
+    let rows = sqlx::query("SELECT id, url, secret, events, active, created_at FROM webhooks ORDER BY created_at")
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(webhook_from_row).collect()
+}
+
+/// Active webhooks subscribed to `event`, either by name or by subscribing to everything
+pub async fn get_webhooks_for_event(pool: &DbPool, event: &str) -> Result<Vec<Webhook>> {
+    Ok(get_webhooks(pool).await?
+        .into_iter()
+        .filter(|webhook| webhook.active && (webhook.events.is_empty() || webhook.events.iter().any(|e| e == event)))
+        .collect())
+}
+
+pub async fn delete_webhook(pool: &DbPool, id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+const DELIVERY_COLUMNS: &str =
+    "id, webhook_id, event, payload, status, attempts, next_attempt_at, last_error, created_at, updated_at";
+
+pub async fn enqueue_webhook_delivery(pool: &DbPool, webhook_id: &str, event: &str, payload: &str) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO webhook_deliveries (id, webhook_id, event, payload, status, attempts, next_attempt_at, created_at, updated_at)
+         VALUES (?, ?, ?, ?, 'pending', 0, ?, ?, ?)"
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(webhook_id)
+    .bind(event)
+    .bind(payload)
+    .bind(&now)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Pending deliveries whose next attempt is due, oldest first
+pub async fn get_due_webhook_deliveries(pool: &DbPool, limit: u32) -> Result<Vec<WebhookDelivery>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM webhook_deliveries
+         WHERE status = 'pending' AND julianday(next_attempt_at) <= julianday(?)
+         ORDER BY next_attempt_at LIMIT ?",
+        DELIVERY_COLUMNS
+    ))
+    .bind(Utc::now().to_rfc3339())
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(delivery_from_row).collect()
+}
+
+pub async fn get_webhook_deliveries(
+    pool: &DbPool,
+    status: Option<&str>,
+    webhook_id: Option<&str>,
+    limit: u32,
+) -> Result<Vec<WebhookDelivery>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM webhook_deliveries
+         WHERE (? IS NULL OR status = ?) AND (? IS NULL OR webhook_id = ?)
+         ORDER BY created_at DESC LIMIT ?",
+        DELIVERY_COLUMNS
+    ))
+    .bind(status)
+    .bind(status)
+    .bind(webhook_id)
+    .bind(webhook_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(delivery_from_row).collect()
+}
+
+pub async fn get_webhook(pool: &DbPool, id: &str) -> Result<Option<Webhook>> {
+    let row = sqlx::query("SELECT id, url, secret, events, active, created_at FROM webhooks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(webhook_from_row).transpose()
+}
+
+pub async fn mark_webhook_delivered(pool: &DbPool, id: &str, attempts: u32) -> Result<()> {
+    sqlx::query(
+        "UPDATE webhook_deliveries
+         SET status = 'delivered', attempts = ?, next_attempt_at = NULL, last_error = NULL, updated_at = ?
+         WHERE id = ?"
+    )
+    .bind(attempts)
+    .bind(Utc::now().to_rfc3339())
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Records a failed attempt. With no `retry_at` the delivery is dead-lettered.
+pub async fn mark_webhook_failed(
+    pool: &DbPool,
+    id: &str,
+    attempts: u32,
+    error: &str,
+    retry_at: Option<DateTime<Utc>>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE webhook_deliveries
+         SET status = ?, attempts = ?, next_attempt_at = ?, last_error = ?, updated_at = ?
+         WHERE id = ?"
+    )
+    .bind(if retry_at.is_some() { "pending" } else { "dead" })
+    .bind(attempts)
+    .bind(retry_at.map(|at| at.to_rfc3339()))
+    .bind(error)
+    .bind(Utc::now().to_rfc3339())
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn delivery_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<WebhookDelivery> {
+    let payload: String = row.get("payload");
+    let next_attempt_at: Option<String> = row.get("next_attempt_at");
+    let created_at: String = row.get("created_at");
+    let updated_at: String = row.get("updated_at");
+
+    Ok(WebhookDelivery {
+        id: row.get("id"),
+        webhook_id: row.get("webhook_id"),
+        event: row.get("event"),
+        payload: serde_json::from_str(&payload)?,
+        status: row.get("status"),
+        attempts: row.get("attempts"),
+        next_attempt_at: next_attempt_at
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| at.with_timezone(&Utc)),
+        last_error: row.get("last_error"),
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+    })
+}
+
+fn webhook_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Webhook> {
+    let events: String = row.get("events");
+    let created_at: String = row.get("created_at");
+
+    Ok(Webhook {
+        id: row.get("id"),
+        url: row.get("url"),
+        secret: row.get("secret"),
+        events: serde_json::from_str(&events)?,
+        active: row.get("active"),
+        created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Utc),
+    })
+}
+
+pub async fn create_user(pool: &DbPool, user: &User, password_hash: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO users (id, username, display_name, email, role, avatar, is_active, last_seen, password_hash)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&user.id)
+    .bind(&user.username)
+    .bind(&user.display_name)
+    .bind(&user.email)
+    .bind(&user.role)
+    .bind(&user.avatar)
+    .bind(user.is_active)
+    .bind(user.last_seen.to_rfc3339())
+    .bind(password_hash)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Every users column except password_hash, which never leaves the database
+const USER_COLUMNS: &str = "id, username, display_name, email, role, avatar, is_active, last_seen";
+
+/// One page of users matching the filters, and how many match in total
+pub async fn get_users(pool: &DbPool, params: &UserQueryParams) -> Result<(Vec<User>, u32)> {
+    let mut conditions = vec!["1=1".to_string()];
+    let mut bind_values: Vec<String> = Vec::new();
+
+    if let Some(search) = params.search.as_deref().filter(|search| !search.is_empty()) {
+        conditions.push("(username LIKE ? OR display_name LIKE ? OR email LIKE ?)".to_string());
+        let search_pattern = format!("%{}%", search);
+        bind_values.extend(std::iter::repeat_n(search_pattern, 3));
+    }
+
+    if let Some(role) = &params.role {
+        conditions.push("role = ?".to_string());
+        bind_values.push(format!("{:?}", role).to_lowercase());
+    }
+
+    if let Some(active) = params.active {
+        conditions.push(format!("is_active = {}", active as i32));
+    }
+
+    let filter = conditions.join(" AND ");
+
+    let count_sql = format!("SELECT COUNT(*) as count FROM users WHERE {}", filter);
+    let mut count_query = sqlx::query(&count_sql);
+    for value in &bind_values {
+        count_query = count_query.bind(value);
+    }
+    let total = count_query.fetch_one(pool).await?.get::<i64, _>("count") as u32;
+
+    let (sort_column, sort_direction) = params.sort.as_deref()
+        .and_then(parse_user_sort)
+        .unwrap_or(("username", "ASC"));
+    let mut query = format!(
+        "SELECT {} FROM users WHERE {} ORDER BY {} {}, id",
+        USER_COLUMNS, filter, sort_column, sort_direction
+    );
+
+    if let Some(limit) = params.limit {
+        query.push_str(&format!(" LIMIT {}", limit));
+        if let Some(offset) = params.offset {
+            query.push_str(&format!(" OFFSET {}", offset));
+        }
+    }
+
+    let mut users_query = sqlx::query_as::<_, User>(&query);
+    for value in bind_values {
+        users_query = users_query.bind(value);
+    }
+
+    Ok((users_query.fetch_all(pool).await?, total))
+}
+
+pub async fn count_active_users(pool: &DbPool) -> Result<u32> {
+    let row = sqlx::query("SELECT COUNT(*) as count FROM users WHERE is_active = 1")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.get::<i64, _>("count") as u32)
+}
+
+/// Whether a username or email is already taken
+pub async fn user_exists(pool: &DbPool, username: &str, email: &str) -> Result<bool> {
+    let row = sqlx::query("SELECT 1 FROM users WHERE username = ? OR email = ?")
+        .bind(username)
+        .bind(email)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.is_some())
+}
+
+/// Email address of the user with this id or username, if any
+pub async fn get_user_email(pool: &DbPool, user: &str) -> Result<Option<String>> {
+    let row = sqlx::query("SELECT email FROM users WHERE id = ? OR username = ? LIMIT 1")
+        .bind(user)
+        .bind(user)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| row.get("email")))
+}
+
+/// Archives or unarchives a task, logging the change and recording a revision
+pub async fn set_task_archived(pool: &DbPool, id: &str, archived: bool, actor: &Actor) -> Result<()> {
+    let counted_before = counted_facts(pool, id).await?;
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query("UPDATE tasks SET archived = ?, updated_by = ?, updated_at = ? WHERE id = ? AND archived != ?")
+        .bind(archived)
+        .bind(&actor.user_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .bind(archived)
+        .execute(&mut *tx)
+        .await?;
+
+    // Archiving an already archived task is a no-op
+    if result.rows_affected() == 0 {
+        return Ok(());
+    }
+
+    let title: String = sqlx::query("SELECT title FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?
+        .get("title");
+    let action = if archived { "archived" } else { "unarchived" };
+    log_activity(&mut *tx, actor, action, "task", id, &title, None).await?;
+
+    tx.commit().await?;
+
+    adjust_analytics_cache(pool, counted_before.as_ref(), counted_facts(pool, id).await?.as_ref()).await?;
+    sync_epic_status(pool, task_epic(pool, id).await?.as_deref()).await?;
+
+    if let Some(task) = get_task_by_id(pool, id).await? {
+        record_task_revision(pool, &task, Some(&actor.user_id)).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn reassign_tasks(pool: &DbPool, from: &str, to: Option<&str>, actor: &Actor) -> Result<u32> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    let rows = sqlx::query("SELECT id, title FROM tasks WHERE assignee = ?")
+        .bind(from)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    sqlx::query("UPDATE tasks SET assignee = ?, updated_by = ?, updated_at = ? WHERE assignee = ?")
+        .bind(to)
+        .bind(&actor.user_id)
+        .bind(&now)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+
+    let details = ActivityDetails {
+        field: Some("assignee".to_string()),
+        old_value: Some(serde_json::json!(from)),
+        new_value: Some(serde_json::json!(to)),
+    };
+
+    for row in &rows {
+        let task_id: String = row.get("id");
+        let title: String = row.get("title");
+
+        if let Some(to) = to {
+            sqlx::query("INSERT OR IGNORE INTO task_watchers (task_id, user_id, created_at) VALUES (?, ?, ?)")
+                .bind(&task_id)
+                .bind(to)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        log_activity(&mut *tx, actor, "assigned", "task", &task_id, &title, Some(&details)).await?;
+    }
+
+    tx.commit().await?;
+
+    for row in &rows {
+        if let Some(task) = get_task_by_id(pool, row.get("id")).await? {
+            record_task_revision(pool, &task, Some(&actor.user_id)).await?;
+        }
+    }
+
+    Ok(rows.len() as u32)
+}
+
+// Activity operations
+pub async fn log_activity<'e, E>(
+    executor: E,
+    actor: &Actor,
+    action: &str,
+    target_type: &str,
+    target_id: &str,
+    target_name: &str,
+    details: Option<&ActivityDetails>,
+) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let details = details.map(serde_json::to_string).transpose()?;
+
+    sqlx::query(
+        "INSERT INTO activities (id, user_id, user_name, action, target_type, target_id, target_name, details, timestamp)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&actor.user_id)
+    .bind(&actor.user_name)
+    .bind(action)
+    .bind(target_type)
+    .bind(target_id)
+    .bind(target_name)
+    .bind(details)
+    .bind(Utc::now().to_rfc3339())
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn purge_old_activities(pool: &DbPool, older_than: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM activities WHERE julianday(timestamp) < julianday(?)")
+        .bind(older_than.to_rfc3339())
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn get_activities(pool: &DbPool, params: &ActivityQueryParams) -> Result<(Vec<Activity>, u32)> {
+    let mut conditions = String::new();
+    let mut bind_values: Vec<String> = Vec::new();
+
+    if let Some(user_id) = &params.user_id {
+        conditions.push_str(" AND user_id = ?");
+        bind_values.push(user_id.clone());
+    }
+
+    if let Some(task_id) = &params.task_id {
+        conditions.push_str(" AND target_type = 'task' AND target_id = ?");
+        bind_values.push(task_id.clone());
+    }
+
+    if let Some(action) = &params.action {
+        conditions.push_str(" AND action = ?");
+        bind_values.push(action.clone());
+    }
+
+    let count_query = format!("SELECT COUNT(*) as count FROM activities WHERE 1=1{}", conditions);
+    let mut count_sql = sqlx::query(&count_query);
+    for value in &bind_values {
+        count_sql = count_sql.bind(value);
+    }
+    let total: i64 = count_sql.fetch_one(pool).await?.get("count");
+
+    let query = format!(
+        "SELECT id, user_id, user_name, action, target_type, target_id, target_name, details, timestamp
+         FROM activities WHERE 1=1{} ORDER BY timestamp DESC LIMIT {} OFFSET {}",
+        conditions,
+        params.limit.unwrap_or(50),
+        params.offset.unwrap_or(0)
+    );
+    let mut sql = sqlx::query(&query);
+    for value in &bind_values {
+        sql = sql.bind(value);
+    }
+
+    let activities = sql.fetch_all(pool).await?.iter()
+        .map(activity_from_row)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((activities, total as u32))
+}
+
+/// One page of the activity log for the export, oldest first, optionally
+/// limited to entries from `from` up to (not including) `to`
+pub async fn get_activity_export_page(
+    pool: &DbPool,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    offset: u32,
+    limit: u32,
+) -> Result<Vec<Activity>> {
+    let rows = sqlx::query(
+        "SELECT id, user_id, user_name, action, target_type, target_id, target_name, details, timestamp
+         FROM activities
+         WHERE (? IS NULL OR julianday(timestamp) >= julianday(?))
+           AND (? IS NULL OR julianday(timestamp) < julianday(?))
+         ORDER BY julianday(timestamp), id LIMIT ? OFFSET ?"
+    )
+    .bind(from.map(|from| from.to_rfc3339()))
+    .bind(from.map(|from| from.to_rfc3339()))
+    .bind(to.map(|to| to.to_rfc3339()))
+    .bind(to.map(|to| to.to_rfc3339()))
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(activity_from_row).collect()
+}
+
+fn activity_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Activity> {
+    let details: Option<String> = row.get("details");
+    let timestamp: String = row.get("timestamp");
+
+    Ok(Activity {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        user_name: row.get("user_name"),
+        action: row.get("action"),
+        target_type: row.get("target_type"),
+        target_id: row.get("target_id"),
+        target_name: row.get("target_name"),
+        details: details.and_then(|d| serde_json::from_str(&d).ok()),
+        timestamp: DateTime::parse_from_rfc3339(&timestamp)?.with_timezone(&Utc),
+    })
+}
+
+pub async fn get_task_count(pool: &DbPool) -> Result<u32> {
+    let row = sqlx::query("SELECT COUNT(*) as count FROM tasks WHERE archived = 0")
+        .fetch_one(pool)
+        .await?;
+    
+    Ok(row.get::<i64, _>("count") as u32)
+}
+
+/// Every stored task, archived ones included
+pub async fn count_stored_tasks(pool: &DbPool) -> Result<u32> {
+    let row = sqlx::query("SELECT COUNT(*) as count FROM tasks")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.get::<i64, _>("count") as u32)
+}
+
+pub async fn count_tasks_with_status(pool: &DbPool, status: &str) -> Result<u32> {
+    let row = sqlx::query("SELECT COUNT(*) as count FROM tasks WHERE status = ? AND archived = 0")
+        .bind(status)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.get::<i64, _>("count") as u32)
+}
+
+/// Id of a task under `epic` titled `title`, ignoring case and surrounding
+/// whitespace, other than `except_id`. Archived tasks count.
+pub async fn find_task_titled_in_epic(pool: &DbPool, epic: &str, title: &str, except_id: Option<&str>) -> Result<Option<String>> {
+    let id = sqlx::query_scalar(
+        "SELECT id FROM tasks WHERE epic = ? AND LOWER(TRIM(title)) = LOWER(TRIM(?)) AND id != COALESCE(?, '') LIMIT 1"
+    )
+    .bind(epic)
+    .bind(title)
+    .bind(except_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(id)
+}
+
+// Config is read on nearly every request (feature gates, limits, validation),
+// so reads are served from memory for a few seconds. Writes through
+// update_workspace_config replace the cached copy immediately.
+const CONFIG_CACHE_TTL: Duration = Duration::from_secs(5);
+
+struct ConfigCache {
+    /// Bumped by every write, so a read that started before one doesn't
+    /// cache what it loaded over the newer copy
+    generation: u64,
+    entry: Option<(Instant, WorkspaceConfig)>,
+}
+
+static CONFIG_CACHE: RwLock<ConfigCache> = RwLock::new(ConfigCache { generation: 0, entry: None });
+
+pub async fn get_workspace_config(pool: &DbPool) -> Result<WorkspaceConfig> {
+    let generation = {
+        let cache = CONFIG_CACHE.read().unwrap();
+        if let Some((loaded_at, config)) = &cache.entry {
+            if loaded_at.elapsed() < CONFIG_CACHE_TTL {
+                return Ok(config.clone());
+            }
+        }
+        cache.generation
+    };
+
+    let config = load_workspace_config(pool).await?;
+    cache_loaded_config(generation, &config);
+    Ok(config)
+}
+
+// Caches a config loaded while the cache was at `generation`, unless a write
+// has happened since
+fn cache_loaded_config(generation: u64, config: &WorkspaceConfig) {
+    let mut cache = CONFIG_CACHE.write().unwrap();
+    if cache.generation == generation {
+        cache.entry = Some((Instant::now(), config.clone()));
+    }
+}
+
+async fn load_workspace_config(pool: &DbPool) -> Result<WorkspaceConfig> {
+    let row = sqlx::query(
+        "SELECT workspace_name, timezone, date_format, features, limits, workflow, default_sort, available_statuses,
+                available_priorities, theme, export_filename, sort_tiebreaker, current_sprint, health_score_weights
+         FROM workspace_config WHERE id = 1"
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let features_json: String = row.get("features");
+    let limits_json: String = row.get("limits");
+    let workflow_json: String = row.get("workflow");
+
+    let features: WorkspaceFeatures = serde_json::from_str(&features_json)?;
+    let limits: WorkspaceLimits = serde_json::from_str(&limits_json)?;
+    let workflow: WorkspaceWorkflow = serde_json::from_str(&workflow_json)?;
+    let available_statuses = match row.get::<Option<String>, _>("available_statuses") {
+        Some(statuses) => serde_json::from_str(&statuses)?,
+        None => default_statuses(),
+    };
+    let available_priorities = match row.get::<Option<String>, _>("available_priorities") {
+        Some(priorities) => serde_json::from_str(&priorities)?,
+        None => default_priorities(),
+    };
+    let theme = match row.get::<Option<String>, _>("theme") {
+        Some(theme) => serde_json::from_str(&theme)?,
+        None => WorkspaceTheme::default(),
+    };
+    let health_score_weights = match row.get::<Option<String>, _>("health_score_weights") {
+        Some(weights) => serde_json::from_str(&weights)?,
+        None => HealthScoreWeights::default(),
+    };
+
+    Ok(WorkspaceConfig {
+        workspace_name: row.get("workspace_name"),
+        timezone: row.get("timezone"),
+        date_format: row.get("date_format"),
+        features,
+        limits,
+        workflow,
+        default_sort: row.get("default_sort"),
+        sort_tiebreaker: row.get("sort_tiebreaker"),
+        available_statuses,
+        available_priorities,
+        theme,
+        export_filename: row.get("export_filename"),
+        current_sprint: row.get("current_sprint"),
+        health_score_weights,
+    })
+}
+
+pub async fn update_workspace_config(pool: &DbPool, config: &WorkspaceConfig) -> Result<()> {
+    sqlx::query(
+        "UPDATE workspace_config
+         SET workspace_name = ?, timezone = ?, date_format = ?, features = ?, limits = ?, workflow = ?, default_sort = ?,
+             available_statuses = ?, available_priorities = ?, theme = ?, export_filename = ?,
+             sort_tiebreaker = ?, current_sprint = ?, health_score_weights = ?
+         WHERE id = 1"
+    )
+    .bind(&config.workspace_name)
+    .bind(&config.timezone)
+    .bind(&config.date_format)
+    .bind(serde_json::to_string(&config.features)?)
+    .bind(serde_json::to_string(&config.limits)?)
+    .bind(serde_json::to_string(&config.workflow)?)
+    .bind(&config.default_sort)
+    .bind(serde_json::to_string(&config.available_statuses)?)
+    .bind(serde_json::to_string(&config.available_priorities)?)
+    .bind(serde_json::to_string(&config.theme)?)
+    .bind(&config.export_filename)
+    .bind(&config.sort_tiebreaker)
+    .bind(&config.current_sprint)
+    .bind(serde_json::to_string(&config.health_score_weights)?)
+    .execute(pool)
+    .await?;
+
+    let mut cache = CONFIG_CACHE.write().unwrap();
+    cache.generation += 1;
+    cache.entry = Some((Instant::now(), config.clone()));
+    Ok(())
+}
+
+// Analytics functions
+/// How well specified the open (unarchived, not Done) tasks are. With no
+/// open tasks every factor is at 100%.
+pub async fn get_health_score(pool: &DbPool, weights: &HealthScoreWeights) -> Result<HealthScore> {
+    let row = sqlx::query(&format!(
+        "SELECT COUNT(*) AS total,
+                COALESCE(SUM(story_points IS NOT NULL), 0) AS estimated,
+                COALESCE(SUM(EXISTS (SELECT 1 FROM checklist_items WHERE checklist_items.task_id = tasks.id
+                                     AND item_type = 'acceptance_criteria')), 0) AS with_criteria,
+                COALESCE(SUM(assignee IS NOT NULL AND assignee != ''), 0) AS assigned,
+                COALESCE(SUM(NOT {}), 0) AS on_time
+         FROM tasks WHERE archived = 0 AND status != 'Done'",
+        OVERDUE_CONDITION
+    ))
+    .fetch_one(pool)
+    .await?;
+
+    let total = row.get::<i64, _>("total");
+    let percent = |column: &str| match total {
+        0 => 100.0,
+        total => (row.get::<i64, _>(column) as f64 * 1000.0 / total as f64).round() / 10.0,
+    };
+    let factors = vec![
+        HealthFactor { factor: "estimated".to_string(), percent: percent("estimated"), weight: weights.estimated },
+        HealthFactor { factor: "withCriteria".to_string(), percent: percent("with_criteria"), weight: weights.with_criteria },
+        HealthFactor { factor: "assigned".to_string(), percent: percent("assigned"), weight: weights.assigned },
+        HealthFactor { factor: "onTime".to_string(), percent: percent("on_time"), weight: weights.on_time },
+    ];
+
+    let total_weight: f64 = factors.iter().map(|factor| factor.weight as f64).sum();
+    let weighted: f64 = factors.iter().map(|factor| factor.percent * factor.weight as f64).sum();
+    let score = if total_weight == 0.0 { 100.0 } else { weighted / total_weight };
+
+    Ok(HealthScore { score: score.round() as u32, tasks: total as u32, factors })
+}
+
+/// Unfinished, unarchived tasks and their story points per assignee, busiest first
+pub async fn get_workload(pool: &DbPool) -> Result<Vec<WorkloadEntry>> {
+    let rows = sqlx::query(
+        "SELECT assignee, status, COUNT(*) as count, CAST(COALESCE(SUM(story_points), 0) AS REAL) as points
+         FROM tasks WHERE archived = 0 AND status != 'Done'
+         GROUP BY assignee, status"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut entries: Vec<WorkloadEntry> = Vec::new();
+    for row in rows {
+        let assignee: Option<String> = row.get("assignee");
+        let count = row.get::<i64, _>("count") as u32;
+
+        let index = match entries.iter().position(|entry| entry.assignee == assignee) {
+            Some(index) => index,
+            None => {
+                entries.push(WorkloadEntry {
+                    assignee,
+                    tasks: 0,
+                    story_points: 0.0,
+                    tasks_by_status: std::collections::HashMap::new(),
+                });
+                entries.len() - 1
+            }
+        };
+
+        let entry = &mut entries[index];
+        entry.tasks += count;
+        entry.story_points += row.get::<f64, _>("points");
+        entry.tasks_by_status.insert(row.get("status"), count);
+    }
+
+    entries.sort_by(|a, b| b.tasks.cmp(&a.tasks).then_with(|| a.assignee.cmp(&b.assignee)));
+    Ok(entries)
+}
+
+pub async fn get_tasks_by_status(pool: &DbPool) -> Result<std::collections::HashMap<String, u32>> {
+    let rows = sqlx::query("SELECT status, COUNT(*) as count FROM tasks WHERE archived = 0 GROUP BY status")
+        .fetch_all(pool)
+        .await?;
+    
+    let mut result = std::collections::HashMap::new();
+    for row in rows {
+        let status: String = row.get("status");
+        let count: i64 = row.get("count");
+        result.insert(status, count as u32);
+    }
+    
+    Ok(result)
+}
+
+pub async fn get_points_by_status(pool: &DbPool) -> Result<std::collections::HashMap<String, f64>> {
+    let rows = sqlx::query(
+        "SELECT status, CAST(COALESCE(SUM(story_points), 0) AS REAL) as points FROM tasks WHERE archived = 0 GROUP BY status"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut result = std::collections::HashMap::new();
+    for row in rows {
+        let status: String = row.get("status");
+        let points: f64 = row.get("points");
+        result.insert(status, points);
+    }
+
+    Ok(result)
+}
+
+pub async fn get_tasks_by_type(pool: &DbPool) -> Result<std::collections::HashMap<String, u32>> {
+    let rows = sqlx::query("SELECT task_type, COUNT(*) as count FROM tasks WHERE archived = 0 GROUP BY task_type")
+        .fetch_all(pool)
+        .await?;
+    
+    let mut result = std::collections::HashMap::new();
+    for row in rows {
+        let task_type: String = row.get("task_type");
+        let count: i64 = row.get("count");
+        result.insert(task_type, count as u32);
+    }
+    
+    Ok(result)
+}
+
+pub async fn get_tasks_by_priority(pool: &DbPool) -> Result<std::collections::HashMap<String, u32>> {
+    let rows = sqlx::query("SELECT priority, COUNT(*) as count FROM tasks WHERE archived = 0 GROUP BY priority")
+        .fetch_all(pool)
+        .await?;
+    
+    let mut result = std::collections::HashMap::new();
+    for row in rows {
+        let priority: String = row.get("priority");
+        let count: i64 = row.get("count");
+        result.insert(priority, count as u32);
+    }
+    
+    Ok(result)
+}
+
+pub async fn get_average_story_points(pool: &DbPool) -> Result<f32> {
+    let row = sqlx::query("SELECT AVG(CAST(story_points as REAL)) as avg_points FROM tasks WHERE story_points IS NOT NULL AND archived = 0")
+        .fetch_one(pool)
+        .await?;
+    
+    Ok(row.get::<Option<f64>, _>("avg_points").unwrap_or(0.0) as f32)
+}
+
+pub async fn get_completion_rate(pool: &DbPool) -> Result<f32> {
+    let total_row = sqlx::query("SELECT COUNT(*) as total FROM tasks WHERE archived = 0")
+        .fetch_one(pool)
+        .await?;
+    let total: i64 = total_row.get("total");
+    
+    if total == 0 {
+        return Ok(0.0);
+    }
+    
+    let completed_row = sqlx::query("SELECT COUNT(*) as completed FROM tasks WHERE status = 'Done' AND archived = 0")
+        .fetch_one(pool)
+        .await?;
+    let completed: i64 = completed_row.get("completed");
+    
+    Ok((completed as f32 / total as f32) * 100.0)
+}
+
+pub async fn get_overdue_count(pool: &DbPool) -> Result<u32> {
+    let row = sqlx::query(&format!("SELECT COUNT(*) as count FROM tasks WHERE archived = 0 AND {}", OVERDUE_CONDITION))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.get::<i64, _>("count") as u32)
+}
+
+pub async fn get_active_sprints(pool: &DbPool) -> Result<Vec<String>> {
+    let rows = sqlx::query("SELECT DISTINCT sprint FROM tasks WHERE sprint IS NOT NULL AND sprint != '' AND status != 'Done' AND archived = 0")
+        .fetch_all(pool)
+        .await?;
+    
+    let mut sprints = Vec::new();
+    for row in rows {
+        let sprint: String = row.get("sprint");
+        sprints.push(sprint);
+    }
+    
+    Ok(sprints)
+}
+
+/// Tasks reaching Done per week or month, from oldest to newest with empty
+/// periods filled in. Completions come from `status_changed` activities;
+/// Done tasks with no such activity count at their `updated_at`.
+pub async fn get_throughput(pool: &DbPool, interval: ThroughputInterval) -> Result<Vec<ThroughputDataPoint>> {
+    let rows = sqlx::query(
+        "SELECT a.timestamp AS completed_at FROM activities a
+         JOIN tasks t ON t.id = a.target_id
+         WHERE a.action = 'status_changed' AND json_extract(a.details, '$.newValue') = 'Done' AND t.archived = 0
+         UNION ALL
+         SELECT t.updated_at AS completed_at FROM tasks t
+         WHERE t.status = 'Done' AND t.archived = 0 AND NOT EXISTS (
+             SELECT 1 FROM activities a
+             WHERE a.target_id = t.id AND a.action = 'status_changed' AND json_extract(a.details, '$.newValue') = 'Done'
+         )"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut counts = std::collections::BTreeMap::new();
+    for row in rows {
+        let completed_at: String = row.get("completed_at");
+        let Ok(completed_at) = DateTime::parse_from_rfc3339(&completed_at) else {
+            continue;
+        };
+        let period = throughput_period(completed_at.with_timezone(&Utc).date_naive(), interval);
+        *counts.entry(period).or_insert(0u32) += 1;
+    }
+
+    let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut data = Vec::new();
+    let mut period = first;
+    while period <= last {
+        data.push(ThroughputDataPoint {
+            period_start: period.format("%Y-%m-%d").to_string(),
+            completed: counts.get(&period).copied().unwrap_or(0),
+        });
+        period = match interval {
+            ThroughputInterval::Week => period + chrono::Duration::days(7),
+            ThroughputInterval::Month => period + chrono::Months::new(1),
+        };
+    }
+
+    Ok(data)
+}
+
+/// Unfinished tasks and story points in a sprint, plus its latest due date.
+/// None when no unarchived task is in the sprint.
+pub async fn get_sprint_remaining(pool: &DbPool, sprint: &str) -> Result<Option<(u32, f64, Option<DateTime<Utc>>)>> {
+    let row = sqlx::query(
+        "SELECT COUNT(*) AS total,
+                COUNT(CASE WHEN status != 'Done' THEN 1 END) AS remaining_tasks,
+                CAST(COALESCE(SUM(CASE WHEN status != 'Done' THEN story_points END), 0) AS REAL) AS remaining_points,
+                MAX(due_date) AS end_date
+         FROM tasks WHERE sprint = ? AND archived = 0"
+    )
+    .bind(sprint)
+    .fetch_one(pool)
+    .await?;
+
+    if row.get::<i64, _>("total") == 0 {
+        return Ok(None);
+    }
+    let end_date: Option<DateTime<Utc>> = row.get("end_date");
+    Ok(Some((row.get::<i64, _>("remaining_tasks") as u32, row.get("remaining_points"), end_date)))
+}
+
+/// Story points of tasks completed since `since`, timed the same way as
+/// `get_throughput`
+pub async fn get_completed_points_since(pool: &DbPool, since: DateTime<Utc>) -> Result<f64> {
+    let rows = sqlx::query(
+        "SELECT a.timestamp AS completed_at, CAST(COALESCE(t.story_points, 0) AS REAL) AS points FROM activities a
+         JOIN tasks t ON t.id = a.target_id
+         WHERE a.action = 'status_changed' AND json_extract(a.details, '$.newValue') = 'Done' AND t.archived = 0
+         UNION ALL
+         SELECT t.updated_at AS completed_at, CAST(COALESCE(t.story_points, 0) AS REAL) AS points FROM tasks t
+         WHERE t.status = 'Done' AND t.archived = 0 AND NOT EXISTS (
+             SELECT 1 FROM activities a
+             WHERE a.target_id = t.id AND a.action = 'status_changed' AND json_extract(a.details, '$.newValue') = 'Done'
+         )"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut points = 0.0;
+    for row in rows {
+        let completed_at: String = row.get("completed_at");
+        if DateTime::parse_from_rfc3339(&completed_at).is_ok_and(|completed_at| completed_at >= since) {
+            points += row.get::<f64, _>("points");
+        }
+    }
+    Ok(points)
+}
+
+fn throughput_period(date: chrono::NaiveDate, interval: ThroughputInterval) -> chrono::NaiveDate {
+    use chrono::Datelike;
+
+    match interval {
+        ThroughputInterval::Week => date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64),
+        ThroughputInterval::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+// Analytics cache
+//
+// Precomputed counts behind the analytics summary, stored as (dimension, key,
+// value) rows. Nothing is cached until an admin rebuilds it; from then on every
+// task write applies its change to the counts, so the summary never has to
+// scan the tasks table. A `meta`/`rebuilt_at` row marks the cache as present.
+
+/// The parts of a task that analytics counts. Archived tasks have none.
+#[derive(Debug)]
+struct CountedFacts {
+    status: String,
+    task_type: String,
+    priority: String,
+    story_points: Option<f64>,
+}
+
+async fn counted_facts(pool: &DbPool, task_id: &str) -> Result<Option<CountedFacts>> {
+    let row = sqlx::query("SELECT status, task_type, priority, CAST(story_points AS REAL) AS story_points FROM tasks WHERE id = ? AND archived = 0")
+        .bind(task_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| CountedFacts {
+        status: row.get("status"),
+        task_type: row.get("task_type"),
+        priority: row.get("priority"),
+        story_points: row.get("story_points"),
+    }))
+}
+
+async fn adjust_analytics_cache(pool: &DbPool, before: Option<&CountedFacts>, after: Option<&CountedFacts>) -> Result<()> {
+    if before.is_none() && after.is_none() {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    let built = sqlx::query("SELECT 1 FROM analytics_cache WHERE dimension = 'meta' AND key = 'rebuilt_at'")
+        .fetch_optional(&mut *tx)
+        .await?
+        .is_some();
+    if !built {
+        return Ok(());
+    }
+
+    // Counts are whole, but story points may be fractional
+    for (facts, sign) in [(before, -1.0), (after, 1.0)] {
+        let Some(facts) = facts else { continue };
+
+        let mut deltas = vec![
+            ("status", facts.status.as_str(), sign),
+            ("type", facts.task_type.as_str(), sign),
+            ("priority", facts.priority.as_str(), sign),
+            ("total", "tasks", sign),
+        ];
+        if facts.status == "Done" {
+            deltas.push(("total", "done", sign));
+        }
+        if let Some(points) = facts.story_points {
+            deltas.push(("total", "estimated", sign));
+            deltas.push(("total", "story_points", sign * points));
+        }
+
+        for (dimension, key, delta) in deltas {
+            sqlx::query(
+                "INSERT INTO analytics_cache (dimension, key, value) VALUES (?, ?, ?)
+                 ON CONFLICT (dimension, key) DO UPDATE SET value = value + excluded.value"
+            )
+            .bind(dimension)
+            .bind(key)
+            .bind(delta)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Recomputes every cached count from the tasks table
+pub async fn rebuild_analytics_cache(pool: &DbPool) -> Result<CachedAnalytics> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM analytics_cache").execute(&mut *tx).await?;
+    for (dimension, column) in [("status", "status"), ("type", "task_type"), ("priority", "priority")] {
+        sqlx::query(&format!(
+            "INSERT INTO analytics_cache (dimension, key, value)
+             SELECT ?, {column}, COUNT(*) FROM tasks WHERE archived = 0 GROUP BY {column}"
+        ))
+        .bind(dimension)
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query(
+        "INSERT INTO analytics_cache (dimension, key, value)
+         SELECT 'total', 'tasks', COUNT(*) FROM tasks WHERE archived = 0
+         UNION ALL SELECT 'total', 'done', COUNT(*) FROM tasks WHERE archived = 0 AND status = 'Done'
+         UNION ALL SELECT 'total', 'estimated', COUNT(story_points) FROM tasks WHERE archived = 0
+         UNION ALL SELECT 'total', 'story_points', COALESCE(SUM(story_points), 0) FROM tasks WHERE archived = 0"
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("INSERT INTO analytics_cache (dimension, key, value) VALUES ('meta', 'rebuilt_at', ?)")
+        .bind(Utc::now().timestamp())
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    get_cached_analytics(pool).await?
+        .ok_or_else(|| anyhow::anyhow!("analytics cache missing right after rebuild"))
+}
+
+/// The same counts computed from the tasks table, for when there's no cache
+pub async fn compute_analytics(pool: &DbPool) -> Result<CachedAnalytics> {
+    Ok(CachedAnalytics {
+        total_tasks: get_task_count(pool).await?,
+        tasks_by_status: get_tasks_by_status(pool).await?,
+        tasks_by_type: get_tasks_by_type(pool).await?,
+        tasks_by_priority: get_tasks_by_priority(pool).await?,
+        average_story_points: get_average_story_points(pool).await?,
+        completion_rate: get_completion_rate(pool).await?,
+    })
+}
+
+/// The cached counts, or None until the cache has been built
+pub async fn get_cached_analytics(pool: &DbPool) -> Result<Option<CachedAnalytics>> {
+    let rows = sqlx::query("SELECT dimension, key, CAST(value AS REAL) AS value FROM analytics_cache")
+        .fetch_all(pool)
+        .await?;
+
+    let mut cached = CachedAnalytics::default();
+    let mut built = false;
+    let (mut done, mut estimated, mut story_points) = (0.0, 0.0, 0.0);
+
+    for row in rows {
+        let dimension: String = row.get("dimension");
+        let key: String = row.get("key");
+        let value: f64 = row.get("value");
+
+        // Groups whose last task moved away stay behind as zeros; the live
+        // queries wouldn't report them at all
+        let counts = match dimension.as_str() {
+            "status" => &mut cached.tasks_by_status,
+            "type" => &mut cached.tasks_by_type,
+            "priority" => &mut cached.tasks_by_priority,
+            "total" => {
+                match key.as_str() {
+                    "tasks" => cached.total_tasks = value.max(0.0) as u32,
+                    "done" => done = value,
+                    "estimated" => estimated = value,
+                    "story_points" => story_points = value,
+                    _ => {}
+                }
+                continue;
+            }
+            "meta" => {
+                built = true;
+                continue;
+            }
+            _ => continue,
+        };
+        if value > 0.0 {
+            counts.insert(key, value as u32);
+        }
+    }
+
+    if !built {
+        return Ok(None);
+    }
+
+    if estimated > 0.0 {
+        cached.average_story_points = (story_points / estimated) as f32;
+    }
+    if cached.total_tasks > 0 {
+        cached.completion_rate = (done as f32 / cached.total_tasks as f32) * 100.0;
+    }
+
+    Ok(Some(cached))
+}
+
+// Import/Export functions
+pub async fn clear_all_tasks(pool: &DbPool) -> Result<()> {
+    // Delete in correct order due to foreign key constraints
+    sqlx::query("DELETE FROM task_dependencies").execute(pool).await?;
+    sqlx::query("DELETE FROM task_blocks").execute(pool).await?;
+    sqlx::query("DELETE FROM checklist_items").execute(pool).await?;
+    sqlx::query("DELETE FROM task_watchers").execute(pool).await?;
+    sqlx::query("DELETE FROM task_labels").execute(pool).await?;
+    sqlx::query("DELETE FROM task_revisions").execute(pool).await?;
+    sqlx::query("DELETE FROM thumbnails").execute(pool).await?;
+    sqlx::query("DELETE FROM tasks").execute(pool).await?;
+
+    if get_cached_analytics(pool).await?.is_some() {
+        rebuild_analytics_cache(pool).await?;
+    }
+    Ok(())
+}
+
+// Exports take the task list's filters, but never its pagination
+pub async fn get_all_tasks_for_export(pool: &DbPool, params: &TaskQueryParams) -> Result<Vec<Task>> {
+    let params = TaskQueryParams { limit: None, offset: None, ..params.clone() };
+    get_tasks(pool, &params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+
+    #[tokio::test]
+    async fn health_score_tells_well_specified_tasks_from_poorly_specified_ones() {
+        let db = db().await;
+        let weights = HealthScoreWeights::default();
+
+        let mut specified = new_task("Specified");
+        specified.story_points = Some(3.0);
+        specified.assignee = Some("alice".to_string());
+        specified.acceptance_criteria = vec![checklist_item("Works")];
+        specified.due_date = Some(Utc::now() + chrono::Duration::days(7));
+        add_task(&db, specified).await;
+
+        let well = get_health_score(&db, &weights).await.unwrap();
+        assert_eq!(well.score, 100);
+
+        // Not estimated, no criteria, unassigned, and overdue
+        let mut vague = new_task("Vague");
+        vague.due_date = Some(Utc::now() - chrono::Duration::days(1));
+        add_task(&db, vague).await;
+
+        let mixed = get_health_score(&db, &weights).await.unwrap();
+        assert_eq!((mixed.score, mixed.tasks), (50, 2));
+        assert!(mixed.factors.iter().all(|factor| factor.percent == 50.0));
+    }
+
+    #[tokio::test]
+    async fn health_score_is_full_without_open_tasks() {
+        let db = db().await;
+
+        let score = get_health_score(&db, &HealthScoreWeights::default()).await.unwrap();
+        assert_eq!((score.score, score.tasks), (100, 0));
+    }
+
+    #[tokio::test]
+    async fn priority_sort_follows_a_configured_five_level_scale() {
+        let db = db().await;
+        let scale = ["Blocker", "Critical", "High", "Medium", "Low"];
+        update_config(&db, |config| config.available_priorities = scale.iter().map(|p| p.to_string()).collect()).await;
+
+        for priority in ["Medium", "Blocker", "Low", "Critical", "High"] {
+            let mut request = new_task(priority);
+            request.priority = Priority::parse(priority);
+            add_task(&db, request).await;
+        }
+
+        let titles = |tasks: Vec<Task>| tasks.into_iter().map(|task| task.title).collect::<Vec<_>>();
+        assert_eq!(titles(list_tasks(&db, "sort=priority:desc").await), scale);
+        assert_eq!(titles(list_tasks(&db, "sort=priority:asc").await), ["Low", "Medium", "High", "Critical", "Blocker"]);
+    }
+
+    #[tokio::test]
+    async fn config_updates_are_visible_on_the_next_read() {
+        let db = db().await;
+        assert_eq!(get_workspace_config(&db).await.unwrap().workspace_name, "Taskdown Workspace");
+
+        update_config(&db, |config| config.workspace_name = "Renamed".to_string()).await;
+        assert_eq!(get_workspace_config(&db).await.unwrap().workspace_name, "Renamed");
+    }
+
+    #[tokio::test]
+    async fn reads_that_overlap_a_config_update_do_not_cache_the_old_copy() {
+        let db = db().await;
+
+        // A read starts, loading the config before the update lands...
+        let generation = CONFIG_CACHE.read().unwrap().generation;
+        let stale = load_workspace_config(&db).await.unwrap();
+        update_config(&db, |config| config.workspace_name = "Renamed".to_string()).await;
+        // ...and finishes after it
+        cache_loaded_config(generation, &stale);
+
+        assert_eq!(get_workspace_config(&db).await.unwrap().workspace_name, "Renamed");
+    }
+
+    fn in_epic(title: &str, epic: &str) -> CreateTaskRequest {
+        let mut request = new_task(title);
+        request.epic = Some(epic.to_string());
+        request
+    }
+
+    #[tokio::test]
+    async fn tasks_under_an_epic_get_sequential_epic_scoped_ids() {
+        let db = db().await;
+        update_config(&db, |config| config.workflow.epic_scoped_ids = true).await;
+
+        let mut ids = Vec::new();
+        for title in ["First", "Second", "Third"] {
+            ids.push(add_task(&db, in_epic(title, "Auth flow")).await.id);
+        }
+        assert_eq!(ids, ["AUTH-FLOW-1", "AUTH-FLOW-2", "AUTH-FLOW-3"]);
+
+        // Tasks without an epic keep UUIDs
+        let loose = add_task(&db, new_task("Loose")).await;
+        assert!(uuid::Uuid::parse_str(&loose.id).is_ok());
+    }
+
+    #[tokio::test]
+    async fn failed_creates_do_not_use_up_an_epic_number() {
+        let db = db().await;
+        update_config(&db, |config| config.workflow.epic_scoped_ids = true).await;
+        sqlx::query(
+            "CREATE TRIGGER reject_broken BEFORE INSERT ON tasks WHEN NEW.title = 'Broken'
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END"
+        ).execute(&db.pool).await.unwrap();
+
+        assert_eq!(add_task(&db, in_epic("First", "Auth")).await.id, "AUTH-1");
+        assert!(create_task(&db, &in_epic("Broken", "Auth"), None).await.is_err());
+        assert_eq!(add_task(&db, in_epic("Second", "Auth")).await.id, "AUTH-2");
+    }
+
+    async fn add_tasks(pool: &DbPool, tasks: &[(&str, &str, &str, &str)]) {
+        for (title, status, priority, task_type) in tasks {
+            let request = serde_json::json!({
+                "title": title, "type": task_type, "priority": priority, "status": status,
+                "description": "", "acceptance_criteria": [], "technical_tasks": [], "dependencies": [], "blocks": [],
+            });
+            add_task(pool, serde_json::from_value(request).unwrap()).await;
+        }
+    }
+
+    async fn listed_titles(pool: &DbPool, query: &str) -> Vec<String> {
+        let mut titles: Vec<String> = list_tasks(pool, query).await.into_iter().map(|task| task.title).collect();
+        titles.sort();
+        titles
+    }
+
+    #[tokio::test]
+    async fn filters_accept_the_human_readable_forms_the_api_returns() {
+        let db = db().await;
+        add_tasks(&db, &[
+            ("Started", "In Progress", "High", "Bug"),
+            ("Reviewing", "In Review", "Low", "Story"),
+            ("Waiting", "Todo", "Medium", "Task"),
+        ]).await;
+
+        assert_eq!(listed_titles(&db, "status=In Progress").await, ["Started"]);
+        assert_eq!(listed_titles(&db, "status=InProgress").await, ["Started"]);
+        assert_eq!(listed_titles(&db, "status=in review").await, ["Reviewing"]);
+        assert_eq!(listed_titles(&db, "priority=high,low").await, ["Reviewing", "Started"]);
+        assert_eq!(listed_titles(&db, "type=bug").await, ["Started"]);
+    }
+
+    #[tokio::test]
+    async fn excluded_statuses_and_types_are_left_out() {
+        let db = db().await;
+        add_tasks(&db, &[
+            ("Shipped", "Done", "High", "Bug"),
+            ("Started", "In Progress", "High", "Bug"),
+            ("Waiting", "Todo", "Medium", "Story"),
+        ]).await;
+
+        assert_eq!(listed_titles(&db, "include_done=false&exclude_status=Done").await, ["Started", "Waiting"]);
+        assert_eq!(listed_titles(&db, "exclude_status=Done&type=Bug").await, ["Started"]);
+        assert_eq!(listed_titles(&db, "exclude_type=Bug").await, ["Waiting"]);
+    }
+
+    #[tokio::test]
+    async fn checklist_item_sources_survive_a_round_trip() {
+        let db = db().await;
+
+        let mut request = new_task("Sourced");
+        request.acceptance_criteria = vec![
+            ChecklistItem { source: Some("ai".to_string()), ..checklist_item("Suggested") },
+            checklist_item("Written"),
+        ];
+        let created = add_task(&db, request).await;
+
+        let stored = get_task_by_id(&db, &created.id).await.unwrap().unwrap();
+        let sources: Vec<_> = stored.acceptance_criteria.iter()
+            .map(|item| (item.text.as_str(), item.source.as_deref()))
+            .collect();
+        assert_eq!(sources, [("Suggested", Some("ai")), ("Written", Some("human"))]);
+    }
+
+    async fn export_titles(pool: &DbPool, sort: Option<&str>, page_size: u32) -> Vec<String> {
+        let mut titles = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = get_export_page(pool, true, sort, cursor.as_deref(), page_size).await.unwrap();
+            titles.extend(page.into_iter().map(|task| task.title));
+            if next.is_none() {
+                return titles;
+            }
+            cursor = next;
+        }
+    }
+
+    #[tokio::test]
+    async fn exports_sorted_by_priority_put_higher_priorities_first_within_each_epic() {
+        let db = db().await;
+        for (title, epic, priority) in [
+            ("Alpha low", Some("Alpha"), Priority::Low),
+            ("Loose medium", None, Priority::Medium),
+            ("Alpha critical", Some("Alpha"), Priority::Critical),
+            ("Beta medium", Some("Beta"), Priority::Medium),
+            ("Loose high", None, Priority::High),
+            ("Beta high", Some("Beta"), Priority::High),
+        ] {
+            let request = match epic {
+                Some(epic) => in_epic(title, epic),
+                None => new_task(title),
+            };
+            add_task(&db, CreateTaskRequest { priority, ..request }).await;
+        }
+
+        assert_eq!(export_titles(&db, Some("priority:desc"), 2).await, [
+            "Alpha critical", "Alpha low",
+            "Beta high", "Beta medium",
+            "Loose high", "Loose medium",
+        ]);
+        // Without a sort each epic keeps creation order
+        assert_eq!(export_titles(&db, None, 2).await, [
+            "Alpha low", "Alpha critical",
+            "Beta medium", "Beta high",
+            "Loose medium", "Loose high",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn export_pages_join_up_into_the_whole_export() {
+        let db = db().await;
+        for (title, epic, points) in [
+            ("A", Some("Beta"), Some(3.0)), ("B", None, None), ("C", Some("Alpha"), Some(1.0)),
+            ("D", Some("Beta"), None), ("E", None, Some(5.0)), ("F", Some("Alpha"), Some(1.0)),
+        ] {
+            let mut request = new_task(title);
+            request.epic = epic.map(String::from);
+            request.story_points = points;
+            add_task(&db, request).await;
+        }
+
+        for sort in [None, Some("story_points:desc"), Some("story_points:asc"), Some("priority:desc")] {
+            let whole = export_titles(&db, sort, 100).await;
+            assert_eq!(whole.len(), 6);
+            assert_eq!(export_titles(&db, sort, 2).await, whole, "sort {:?}", sort);
+        }
+    }
+
+    #[tokio::test]
+    async fn deleting_an_exported_task_does_not_skip_the_next_page() {
+        let db = db().await;
+        for title in ["A", "B", "C", "D"] {
+            add_task(&db, new_task(title)).await;
+        }
+
+        let (first, cursor) = get_export_page(&db, true, None, None, 2).await.unwrap();
+        delete_task(&db, &first[0].id).await.unwrap();
+        let (second, _) = get_export_page(&db, true, None, cursor.as_deref(), 2).await.unwrap();
+
+        let titles: Vec<_> = second.into_iter().map(|task| task.title).collect();
+        assert_eq!(titles, ["C", "D"]);
+    }
+
+    #[tokio::test]
+    async fn task_ids_ignore_case_only_when_configured() {
+        let db = db().await;
+        let task = create_task_with_id(&db, "PROJ-1", &new_task("Cased"), None).await.unwrap();
+
+        assert_eq!(canonical_task_id(&db, "proj-1").await.unwrap(), Some("proj-1".to_string()));
+        assert!(get_task_by_id(&db, "proj-1").await.unwrap().is_none());
+
+        update_config(&db, |config| config.workflow.case_insensitive_ids = true).await;
+        assert_eq!(canonical_task_id(&db, "proj-1").await.unwrap(), Some(task.id.clone()));
+        assert_eq!(get_task_by_id(&db, "proj-1").await.unwrap().unwrap().id, task.id);
+        assert_eq!(canonical_task_id(&db, "NOPE-1").await.unwrap(), None);
+    }
+
+    // The analytics as JSON, without the zero counts the cache keeps for
+    // groups that have emptied
+    fn analytics_json(analytics: &CachedAnalytics) -> serde_json::Value {
+        let mut json = serde_json::to_value(analytics).unwrap();
+        for counts in ["tasksByStatus", "tasksByType", "tasksByPriority"] {
+            json[counts].as_object_mut().unwrap().retain(|_, count| count != 0);
+        }
+        json
+    }
+
+    #[tokio::test]
+    async fn the_analytics_cache_keeps_up_with_task_writes() {
+        let db = db().await;
+        let first = add_task(&db, new_task("First")).await;
+        rebuild_analytics_cache(&db).await.unwrap();
+
+        let mut estimated = new_task("Estimated");
+        estimated.story_points = Some(5.0);
+        let estimated = add_task(&db, estimated).await;
+        let archived = add_task(&db, new_task("Archived")).await;
+        let update = UpdateTaskRequest { status: Some(TaskStatus::Done), ..Default::default() };
+        update_task(&db, &first.id, &update, None).await.unwrap();
+        set_task_archived(&db, &archived.id, true, &Actor::system()).await.unwrap();
+        delete_task(&db, &estimated.id).await.unwrap();
+        add_task(&db, new_task("Last")).await;
+
+        let cached = get_cached_analytics(&db).await.unwrap().expect("cache built");
+        let live = compute_analytics(&db).await.unwrap();
+        assert_eq!(analytics_json(&cached), analytics_json(&live));
+        assert_eq!(live.total_tasks, 2);
+    }
+
+    async fn set_status(pool: &DbPool, task_id: &str, status: TaskStatus) {
+        let update = UpdateTaskRequest { status: Some(status), ..Default::default() };
+        update_task(pool, task_id, &update, None).await.unwrap();
+    }
+
+    async fn epic_statuses(pool: &DbPool, epic: &str) -> (Option<TaskStatus>, Option<TaskStatus>) {
+        let epic = get_epic(pool, epic).await.unwrap().unwrap();
+        (epic.status, epic.derived_status)
+    }
+
+    #[tokio::test]
+    async fn epic_status_rolls_up_to_done_once_every_task_is_done() {
+        let db = db().await;
+        update_config(&db, |config| config.workflow.auto_epic_status = true).await;
+        let first = add_task(&db, in_epic("First", "Auth")).await;
+        let second = add_task(&db, in_epic("Second", "Auth")).await;
+        assert_eq!(epic_statuses(&db, "Auth").await, (Some(TaskStatus::Todo), Some(TaskStatus::Todo)));
+
+        set_status(&db, &first.id, TaskStatus::Done).await;
+        assert_eq!(epic_statuses(&db, "Auth").await, (Some(TaskStatus::InProgress), Some(TaskStatus::InProgress)));
+
+        set_status(&db, &second.id, TaskStatus::Done).await;
+        assert_eq!(epic_statuses(&db, "Auth").await, (Some(TaskStatus::Done), Some(TaskStatus::Done)));
+
+        delete_task(&db, &first.id).await.unwrap();
+        delete_task(&db, &second.id).await.unwrap();
+        assert_eq!(epic_statuses(&db, "Auth").await, (None, None));
+    }
+
+    #[tokio::test]
+    async fn epic_status_is_only_stored_when_configured() {
+        let db = db().await;
+        let task = add_task(&db, in_epic("Only", "Auth")).await;
+        set_status(&db, &task.id, TaskStatus::Done).await;
+
+        assert_eq!(epic_statuses(&db, "Auth").await, (None, Some(TaskStatus::Done)));
+    }
+
+    async fn relationships(pool: &DbPool, task_id: &str) -> (Vec<String>, Vec<String>) {
+        let task = get_task_by_id(pool, task_id).await.unwrap().unwrap();
+        (task.dependencies, task.blocks)
+    }
+
+    #[tokio::test]
+    async fn symmetric_relations_add_and_remove_the_inverse_edge() {
+        let db = db().await;
+        update_config(&db, |config| config.workflow.enforce_symmetric_relations = true).await;
+        let a = add_task(&db, new_task("A")).await.id;
+        let b = add_task(&db, new_task("B")).await.id;
+        let c = add_task(&db, new_task("C")).await.id;
+
+        set_task_relationships(&db, &a, std::slice::from_ref(&c), std::slice::from_ref(&b)).await.unwrap();
+        assert_eq!(relationships(&db, &b).await, (vec![a.clone()], vec![]));
+        assert_eq!(relationships(&db, &c).await, (vec![], vec![a.clone()]));
+
+        set_task_relationships(&db, &a, &[], &[]).await.unwrap();
+        assert_eq!(relationships(&db, &b).await, (vec![], vec![]));
+        assert_eq!(relationships(&db, &c).await, (vec![], vec![]));
+    }
+
+    #[tokio::test]
+    async fn relations_are_one_sided_unless_configured() {
+        let db = db().await;
+        let a = add_task(&db, new_task("A")).await.id;
+        let b = add_task(&db, new_task("B")).await.id;
+
+        set_task_relationships(&db, &a, &[], std::slice::from_ref(&b)).await.unwrap();
+        assert_eq!(relationships(&db, &b).await, (vec![], vec![]));
+    }
+
+    #[tokio::test]
+    async fn tasks_are_blocked_until_their_dependencies_are_done() {
+        let db = db().await;
+        let dependency = add_task(&db, new_task("Dependency")).await.id;
+        let mut blocked = new_task("Blocked");
+        blocked.dependencies = vec![dependency.clone()];
+        let blocked = add_task(&db, blocked).await.id;
+        let is_blocked = |task: Task| task.is_blocked;
+
+        assert_eq!(is_blocked(get_task_by_id(&db, &blocked).await.unwrap().unwrap()), None);
+
+        update_config(&db, |config| config.workflow.flag_blocked_tasks = true).await;
+        assert_eq!(is_blocked(get_task_by_id(&db, &blocked).await.unwrap().unwrap()), Some(true));
+        assert_eq!(is_blocked(get_task_by_id(&db, &dependency).await.unwrap().unwrap()), Some(false));
+
+        set_status(&db, &dependency, TaskStatus::Done).await;
+        assert_eq!(is_blocked(get_task_by_id(&db, &blocked).await.unwrap().unwrap()), Some(false));
+        let listed: Vec<_> = list_tasks(&db, "").await.into_iter().map(is_blocked).collect();
+        assert_eq!(listed, [Some(false), Some(false)]);
+    }
+
+    #[tokio::test]
+    async fn tasks_record_who_created_and_last_updated_them() {
+        let db = db().await;
+        let task = create_task(&db, &new_task("Authored"), Some("alice")).await.unwrap();
+        assert_eq!((task.created_by.as_deref(), task.updated_by.as_deref()), (Some("alice"), Some("alice")));
+
+        let update = UpdateTaskRequest { title: Some("Edited".to_string()), ..Default::default() };
+        update_task(&db, &task.id, &update, Some("bob")).await.unwrap();
+
+        let task = get_task_by_id(&db, &task.id).await.unwrap().unwrap();
+        assert_eq!((task.created_by.as_deref(), task.updated_by.as_deref()), (Some("alice"), Some("bob")));
+    }
+
+    fn assigned_to(title: &str, assignee: &str) -> CreateTaskRequest {
+        CreateTaskRequest { assignee: Some(assignee.to_string()), ..new_task(title) }
+    }
+
+    #[tokio::test]
+    async fn reassigning_moves_every_task_to_the_new_assignee() {
+        let db = db().await;
+        add_task(&db, assigned_to("First", "alice")).await;
+        add_task(&db, assigned_to("Second", "alice")).await;
+        add_task(&db, assigned_to("Other", "carol")).await;
+
+        assert_eq!(reassign_tasks(&db, "alice", Some("bob"), &Actor::system()).await.unwrap(), 2);
+        assert_eq!(listed_titles(&db, "assignee=alice").await, Vec::<String>::new());
+        assert_eq!(listed_titles(&db, "assignee=bob&sort=title").await, ["First", "Second"]);
+        assert_eq!(listed_titles(&db, "assignee=carol").await, ["Other"]);
+    }
+
+    async fn retitle(pool: &DbPool, task_id: &str, title: &str) {
+        let update = UpdateTaskRequest { title: Some(title.to_string()), ..Default::default() };
+        update_task(pool, task_id, &update, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn revisions_keep_earlier_versions_and_can_be_restored() {
+        let db = db().await;
+        let task = add_task(&db, new_task("Original")).await;
+        retitle(&db, &task.id, "Second").await;
+        retitle(&db, &task.id, "Third").await;
+
+        let revisions = get_task_revisions(&db, &task.id).await.unwrap();
+        let titles: Vec<_> = revisions.iter().map(|revision| revision.snapshot.title.as_str()).collect();
+        assert_eq!(titles, ["Original", "Second", "Third"]);
+
+        let restored = restore_task_revision(&db, &task.id, revisions[0].revision, None).await.unwrap().unwrap();
+        assert_eq!(restored.title, "Original");
+        assert!(restore_task_revision(&db, &task.id, 99, None).await.unwrap().is_none());
+    }
+
+    fn due_in(title: &str, days: i64, status: TaskStatus) -> CreateTaskRequest {
+        CreateTaskRequest {
+            due_date: Some(Utc::now() + chrono::Duration::days(days)),
+            status,
+            ..new_task(title)
+        }
+    }
+
+    #[tokio::test]
+    async fn only_unfinished_tasks_past_their_due_date_are_overdue() {
+        let db = db().await;
+        let late = add_task(&db, due_in("Late", -2, TaskStatus::Todo)).await;
+        add_task(&db, due_in("Finished late", -2, TaskStatus::Done)).await;
+        add_task(&db, due_in("Upcoming", 2, TaskStatus::Todo)).await;
+        add_task(&db, new_task("Undated")).await;
+        assert!(late.due_date.is_some());
+
+        assert_eq!(listed_titles(&db, "overdue=true").await, ["Late"]);
+        assert_eq!(listed_titles(&db, "overdue=false&sort=title").await, ["Finished late", "Undated", "Upcoming"]);
+        assert_eq!(get_overdue_count(&db).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn repeated_assignees_and_epics_match_any_of_them() {
+        let db = db().await;
+        add_task(&db, assigned_to("Alice's", "alice")).await;
+        add_task(&db, assigned_to("Bob's", "bob")).await;
+        add_task(&db, assigned_to("Carol's", "carol")).await;
+        add_task(&db, CreateTaskRequest { assignee: Some("alice".to_string()), ..in_epic("Alice's epic task", "Epic1") }).await;
+        add_task(&db, CreateTaskRequest { assignee: Some("bob".to_string()), ..in_epic("Bob's other epic task", "Epic3") }).await;
+
+        assert_eq!(listed_titles(&db, "assignee=alice").await, ["Alice's", "Alice's epic task"]);
+        let union = ["Alice's", "Alice's epic task", "Bob's", "Bob's other epic task"];
+        assert_eq!(listed_titles(&db, "assignee=alice&assignee=bob").await, union);
+        assert_eq!(listed_titles(&db, "assignee=alice,bob").await, union);
+        assert_eq!(listed_titles(&db, "assignee=alice,bob&epic=Epic1,Epic2").await, ["Alice's epic task"]);
+    }
+
+    fn with_checklist(title: &str, done: usize, total: usize) -> CreateTaskRequest {
+        let items = (0..total).map(|n| ChecklistItem { completed: n < done, ..checklist_item("Item") }).collect();
+        CreateTaskRequest { acceptance_criteria: items, ..new_task(title) }
+    }
+
+    #[tokio::test]
+    async fn completion_sorts_by_the_fraction_of_checklist_items_done() {
+        let db = db().await;
+        add_task(&db, with_checklist("Half", 1, 2)).await;
+        add_task(&db, with_checklist("Empty", 0, 0)).await;
+        add_task(&db, with_checklist("Finished", 3, 3)).await;
+        add_task(&db, with_checklist("Third", 1, 3)).await;
+
+        let titles = |tasks: Vec<Task>| tasks.into_iter().map(|task| task.title).collect::<Vec<_>>();
+        assert_eq!(titles(list_tasks(&db, "sort=completion:desc").await), ["Finished", "Half", "Third", "Empty"]);
+        assert_eq!(titles(list_tasks(&db, "sort=completion:asc").await), ["Empty", "Third", "Half", "Finished"]);
+    }
+
+    #[tokio::test]
+    async fn archived_tasks_leave_the_default_list_and_analytics() {
+        let db = db().await;
+        let archived = add_task(&db, new_task("Archived")).await;
+        add_task(&db, new_task("Kept")).await;
+
+        set_task_archived(&db, &archived.id, true, &Actor::system()).await.unwrap();
+        assert_eq!(listed_titles(&db, "").await, ["Kept"]);
+        assert_eq!(listed_titles(&db, "include_archived=true").await, ["Archived", "Kept"]);
+        assert_eq!(compute_analytics(&db).await.unwrap().total_tasks, 1);
+
+        set_task_archived(&db, &archived.id, false, &Actor::system()).await.unwrap();
+        assert_eq!(listed_titles(&db, "").await, ["Archived", "Kept"]);
+        assert_eq!(compute_analytics(&db).await.unwrap().total_tasks, 2);
+    }
+
+    #[tokio::test]
+    async fn under_specified_tasks_can_be_singled_out() {
+        let db = db().await;
+        let specified = CreateTaskRequest { story_points: Some(3.0), ..with_checklist("Specified", 0, 1) };
+        add_task(&db, specified).await;
+        add_task(&db, CreateTaskRequest { story_points: Some(5.0), ..new_task("No criteria") }).await;
+        add_task(&db, with_checklist("No estimate", 0, 1)).await;
+        add_task(&db, CreateTaskRequest { status: TaskStatus::Done, ..new_task("Neither") }).await;
+
+        assert_eq!(listed_titles(&db, "missing_criteria=true").await, ["Neither", "No criteria"]);
+        assert_eq!(listed_titles(&db, "missing_estimate=true").await, ["Neither", "No estimate"]);
+        assert_eq!(listed_titles(&db, "missing_criteria=true&missing_estimate=true").await, ["Neither"]);
+        assert_eq!(listed_titles(&db, "missing_criteria=true&status=Todo").await, ["No criteria"]);
+    }
+
+    // A Done task whose completion was logged on `date`, as the update handler logs it
+    async fn complete_on(pool: &DbPool, title: &str, date: &str) {
+        let task = add_task(pool, CreateTaskRequest { status: TaskStatus::Done, ..new_task(title) }).await;
+        let details = ActivityDetails {
+            field: Some("status".to_string()),
+            old_value: Some(serde_json::json!("Todo")),
+            new_value: Some(serde_json::json!("Done")),
+        };
+        log_activity(pool, &Actor::system(), "status_changed", "task", &task.id, title, Some(&details)).await.unwrap();
+        sqlx::query("UPDATE activities SET timestamp = ? WHERE target_id = ?")
+            .bind(format!("{}T12:00:00+00:00", date))
+            .bind(&task.id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn throughput_counts_completions_per_week() {
+        let db = db().await;
+        // 2026-01-05 is a Monday
+        complete_on(&db, "Monday", "2026-01-05").await;
+        complete_on(&db, "Wednesday", "2026-01-07").await;
+        complete_on(&db, "Next week", "2026-01-14").await;
+        complete_on(&db, "Two weeks on", "2026-01-25").await;
+        // Created as Done, so there's no activity and updated_at is used
+        let imported = add_task(&db, CreateTaskRequest { status: TaskStatus::Done, ..new_task("Imported") }).await;
+        sqlx::query("UPDATE tasks SET updated_at = '2026-01-18T09:00:00+00:00' WHERE id = ?")
+            .bind(&imported.id)
+            .execute(&*db)
+            .await
+            .unwrap();
+
+        let weeks: Vec<(String, u32)> = get_throughput(&db, ThroughputInterval::Week).await.unwrap()
+            .into_iter()
+            .map(|point| (point.period_start, point.completed))
+            .collect();
+        assert_eq!(weeks, [
+            ("2026-01-05".to_string(), 2),
+            ("2026-01-12".to_string(), 2),
+            ("2026-01-19".to_string(), 1),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn listings_without_a_sort_use_the_workspace_default() {
+        let db = db().await;
+        add_tasks(&db, &[("Low", "Todo", "Low", "Task"), ("Critical", "Todo", "Critical", "Task"), ("Medium", "Todo", "Medium", "Task")]).await;
+        update_config(&db, |config| config.default_sort = "priority:asc".to_string()).await;
+
+        let titles = |tasks: Vec<Task>| tasks.into_iter().map(|task| task.title).collect::<Vec<_>>();
+        assert_eq!(titles(list_tasks(&db, "").await), ["Low", "Medium", "Critical"]);
+        assert_eq!(titles(list_tasks(&db, "sort=priority:desc").await), ["Critical", "Medium", "Low"]);
+    }
+
+    #[tokio::test]
+    async fn point_ranges_return_only_estimated_tasks_in_range() {
+        let db = db().await;
+        for (title, points) in [("One", Some(1.0)), ("Three", Some(3.0)), ("Five", Some(5.0)), ("Thirteen", Some(13.0)), ("Unestimated", None)] {
+            add_task(&db, CreateTaskRequest { story_points: points, ..new_task(title) }).await;
+        }
+
+        assert_eq!(listed_titles(&db, "min_points=2&max_points=8").await, ["Five", "Three"]);
+        assert_eq!(listed_titles(&db, "min_points=8").await, ["Thirteen"]);
+        assert_eq!(listed_titles(&db, "max_points=1&include_unestimated=true").await, ["One", "Unestimated"]);
+    }
+
+    #[tokio::test]
+    async fn exports_can_leave_out_done_tasks() {
+        let db = db().await;
+        add_tasks(&db, &[("Open", "Todo", "Medium", "Task"), ("Finished", "Done", "Medium", "Task"), ("Started", "In Progress", "Medium", "Task")]).await;
+
+        let (all, _) = get_export_page(&db, true, Some("title"), None, 10).await.unwrap();
+        assert_eq!(all.into_iter().map(|task| task.title).collect::<Vec<_>>(), ["Finished", "Open", "Started"]);
+        let (open, _) = get_export_page(&db, false, Some("title"), None, 10).await.unwrap();
+        assert_eq!(open.into_iter().map(|task| task.title).collect::<Vec<_>>(), ["Open", "Started"]);
+    }
+
+    #[tokio::test]
+    async fn reads_work_through_a_read_only_pool_on_the_same_database() {
+        let db = db().await;
+        add_task(&db, new_task("Replicated")).await;
+        // No DATABASE_READ_URL in tests, so reads fall back to the primary
+        assert!(std::ptr::eq(read_pool(&db), &*db));
+
+        // Opened the way init_db opens DATABASE_READ_URL
+        let options = SqliteConnectOptions::new().filename(db.connect_options().get_filename()).read_only(true);
+        let replica = SqlitePool::connect_with(options).await.unwrap();
+        let tasks = get_tasks(&replica, &TaskQueryParams::default()).await.unwrap();
+        assert_eq!(tasks.into_iter().map(|task| task.title).collect::<Vec<_>>(), ["Replicated"]);
+        assert_eq!(compute_analytics(&replica).await.unwrap().total_tasks, 1);
+        assert!(create_task(&replica, &new_task("Rejected"), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn relationship_filters_isolate_dependent_and_blocking_tasks() {
+        let db = db().await;
+        let schema = add_task(&db, new_task("Schema")).await;
+        add_task(&db, CreateTaskRequest { dependencies: vec![schema.id.clone()], ..new_task("API") }).await;
+        add_task(&db, CreateTaskRequest { status: TaskStatus::Done, ..new_task("Independent") }).await;
+
+        assert_eq!(listed_titles(&db, "has_dependencies=true").await, ["API"]);
+        assert_eq!(listed_titles(&db, "is_blocking=true").await, ["Schema"]);
+        assert_eq!(listed_titles(&db, "has_dependencies=false&is_blocking=false").await, ["Independent"]);
+        assert_eq!(listed_titles(&db, "is_blocking=true&status=Done").await, Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn equal_sort_values_page_in_a_stable_order() {
+        let db = db().await;
+        let mut ids = Vec::new();
+        for title in ["A", "B", "C", "D", "E"] {
+            ids.push(add_task(&db, new_task(title)).await.id);
+        }
+        // Identical timestamps too, leaving only the id to tell them apart
+        sqlx::query("UPDATE tasks SET created_at = '2026-01-01T00:00:00+00:00'").execute(&*db).await.unwrap();
+        ids.sort();
+
+        let page = |offset: u32| {
+            let pool = db.pool.clone();
+            async move {
+                list_tasks(&pool, &format!("sort=priority&limit=2&offset={}", offset)).await
+                    .into_iter()
+                    .map(|task| task.id)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let paged = [page(0).await, page(2).await, page(4).await].concat();
+        assert_eq!(paged, ids);
+        assert_eq!(page(2).await, paged[2..4]);
+    }
+
+    #[tokio::test]
+    async fn duplicate_checklist_items_collapse_only_when_configured() {
+        let db = db().await;
+        let criteria = || vec![
+            ChecklistItem { completed: true, ..checklist_item("Handles  errors") },
+            checklist_item("handles errors"),
+            checklist_item("Logs the result"),
+        ];
+        let saved = |task: &Task| -> Vec<(String, bool)> {
+            task.acceptance_criteria.iter().map(|item| (item.text.clone(), item.completed)).collect()
+        };
+
+        let kept = add_task(&db, CreateTaskRequest { acceptance_criteria: criteria(), ..new_task("Kept") }).await;
+        assert_eq!(kept.acceptance_criteria.len(), 3);
+
+        update_config(&db, |config| config.workflow.dedupe_checklist_items = true).await;
+        let deduped = add_task(&db, CreateTaskRequest { acceptance_criteria: criteria(), ..new_task("Deduped") }).await;
+        assert_eq!(saved(&deduped), [
+            ("Handles  errors".to_string(), true),
+            ("Logs the result".to_string(), false),
+        ]);
+    }
+}
@@ -0,0 +1,109 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::markdown::ParsedTask;
+use crate::models::*;
+
+/// Compares two parsed boards task by task, matching tasks by id. Tasks only
+/// in `after` are added, tasks only in `before` removed, and tasks in both
+/// are reported with each field whose value differs.
+pub fn diff_boards(before: &[ParsedTask], after: &[ParsedTask]) -> BoardDiff {
+    let before_by_id: HashMap<&str, &ParsedTask> = before.iter().map(|task| (task.id.as_str(), task)).collect();
+    let after_by_id: HashMap<&str, &ParsedTask> = after.iter().map(|task| (task.id.as_str(), task)).collect();
+
+    let mut diff = BoardDiff::default();
+
+    for task in after {
+        match before_by_id.get(task.id.as_str()) {
+            None => diff.added.push(summary(task)),
+            Some(old) => {
+                let changes: Vec<FieldChange> = fields(old).into_iter()
+                    .zip(fields(task))
+                    .filter(|((_, old_value), (_, new_value))| old_value != new_value)
+                    .map(|((field, old_value), (_, new_value))| FieldChange {
+                        field: field.to_string(),
+                        before: old_value,
+                        after: new_value,
+                    })
+                    .collect();
+
+                if !changes.is_empty() {
+                    diff.changed.push(TaskChange {
+                        id: task.id.clone(),
+                        title: task.title.clone(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+
+    for task in before {
+        if !after_by_id.contains_key(task.id.as_str()) {
+            diff.removed.push(summary(task));
+        }
+    }
+
+    diff
+}
+
+fn summary(task: &ParsedTask) -> TaskSummary {
+    TaskSummary {
+        id: task.id.clone(),
+        title: task.title.clone(),
+        epic: task.epic.clone(),
+        status: task.status.clone(),
+        priority: task.priority.clone(),
+        assignee: task.assignee.clone(),
+        story_points: task.story_points,
+    }
+}
+
+// Field names match the API's JSON, and values are compared as they'd be serialized
+fn fields(task: &ParsedTask) -> [(&'static str, Value); 13] {
+    let checklist = |items: &[ChecklistItem]| -> Value {
+        items.iter()
+            .map(|item| json!({ "text": item.text, "completed": item.completed }))
+            .collect()
+    };
+
+    [
+        ("title", json!(task.title)),
+        ("epic", json!(task.epic)),
+        ("type", json!(task.r#type)),
+        ("priority", json!(task.priority)),
+        ("status", json!(task.status)),
+        ("storyPoints", json!(task.story_points)),
+        ("sprint", json!(task.sprint)),
+        ("assignee", json!(task.assignee)),
+        ("description", json!(task.description)),
+        ("acceptanceCriteria", checklist(&task.acceptance_criteria)),
+        ("technicalTasks", checklist(&task.technical_tasks)),
+        ("dependencies", json!(task.dependencies)),
+        ("blocks", json!(task.blocks)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::parse_board;
+
+    #[test]
+    fn status_changes_are_reported_per_field() {
+        let before = parse_board("### PROJ-1: Login\n\n**Type**: Task\n**Status**: Todo\n\n\
+                                  ### PROJ-2: Logout\n\n**Type**: Task\n**Status**: Todo\n\n");
+        let after = parse_board("### PROJ-1: Login\n\n**Type**: Task\n**Status**: Done\n\n\
+                                 ### PROJ-3: Signup\n\n**Type**: Task\n**Status**: Todo\n\n");
+
+        let diff = diff_boards(&before, &after);
+        assert_eq!(diff.added.iter().map(|task| task.id.as_str()).collect::<Vec<_>>(), ["PROJ-3"]);
+        assert_eq!(diff.removed.iter().map(|task| task.id.as_str()).collect::<Vec<_>>(), ["PROJ-2"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].id, "PROJ-1");
+        let changes: Vec<_> = diff.changed[0].changes.iter()
+            .map(|change| (change.field.as_str(), change.before.clone(), change.after.clone()))
+            .collect();
+        assert_eq!(changes, [("status", json!("Todo"), json!("Done"))]);
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+
+use crate::database::{self, DbPool};
+use crate::models::{Actor, ActivityDetails, EscalationResult, Priority, PriorityEscalation, UpdateTaskRequest};
+
+/// Raises each open task whose priority hasn't changed (or, if it never has,
+/// that was created) in the last `limits.escalation_age_days` one step up the
+/// workspace's priority scale. An escalation is itself a change, so a task
+/// rises at most one step per period. Tasks already at the top of the scale,
+/// or at a priority no longer on it, are left alone. A dry run only reports
+/// what would change.
+pub async fn escalate_aging_tasks(pool: &DbPool, actor: &Actor, dry_run: bool) -> Result<EscalationResult> {
+    let config = database::get_workspace_config(pool).await?;
+    let older_than = Utc::now() - Duration::days(i64::from(config.limits.escalation_age_days));
+    let scale: Vec<Priority> = config.available_priorities.iter().map(|priority| Priority::parse(priority)).collect();
+
+    let mut escalated = Vec::new();
+    for (id, title, priority) in database::get_open_tasks_with_priority_set_before(pool, older_than).await? {
+        let from = Priority::parse(&priority);
+        let Some(index) = scale.iter().position(|priority| *priority == from).filter(|&index| index > 0) else {
+            continue;
+        };
+        let to = scale[index - 1].clone();
+
+        if !dry_run {
+            let request = UpdateTaskRequest {
+                priority: Some(to.clone()),
+                ..Default::default()
+            };
+            database::update_task(pool, &id, &request, Some(&actor.user_id)).await?;
+
+            let details = ActivityDetails {
+                field: Some("priority".to_string()),
+                old_value: Some(serde_json::json!(from.name())),
+                new_value: Some(serde_json::json!(to.name())),
+            };
+            database::log_activity(pool, actor, "priority_changed", "task", &id, &title, Some(&details)).await?;
+        }
+
+        escalated.push(PriorityEscalation { id, title, from, to });
+    }
+
+    Ok(EscalationResult { dry_run, older_than, escalated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+
+    async fn add_aged_task(pool: &DbPool, title: &str, priority: &str, age_days: i64) -> String {
+        let mut request = new_task(title);
+        request.priority = Priority::parse(priority);
+        let task = add_task(pool, request).await;
+
+        sqlx::query("UPDATE tasks SET created_at = ? WHERE id = ?")
+            .bind((Utc::now() - Duration::days(age_days)).to_rfc3339())
+            .bind(&task.id)
+            .execute(pool)
+            .await
+            .unwrap();
+        task.id
+    }
+
+    async fn priority_of(pool: &DbPool, id: &str) -> String {
+        database::get_task_by_id(pool, id).await.unwrap().unwrap().priority.name().to_string()
+    }
+
+    #[tokio::test]
+    async fn old_tasks_are_escalated_and_recent_ones_left_alone() {
+        let db = db().await;
+        let old = add_aged_task(&db, "Old", "Medium", 31).await;
+        let recent = add_aged_task(&db, "Recent", "Medium", 2).await;
+        let critical = add_aged_task(&db, "Critical", "Critical", 90).await;
+
+        let preview = escalate_aging_tasks(&db, &Actor::system(), true).await.unwrap();
+        assert_eq!(preview.escalated.iter().map(|task| task.id.as_str()).collect::<Vec<_>>(), [old.as_str()]);
+        assert_eq!(priority_of(&db, &old).await, "Medium");
+
+        let result = escalate_aging_tasks(&db, &Actor::system(), false).await.unwrap();
+        assert_eq!(result.escalated.len(), 1);
+        assert_eq!(priority_of(&db, &old).await, "High");
+        assert_eq!(priority_of(&db, &recent).await, "Medium");
+        assert_eq!(priority_of(&db, &critical).await, "Critical");
+        assert_eq!(activities(&db, "priority_changed").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_task_rises_only_one_level_per_period() {
+        let db = db().await;
+        let old = add_aged_task(&db, "Old", "Medium", 31).await;
+
+        escalate_aging_tasks(&db, &Actor::system(), false).await.unwrap();
+        let again = escalate_aging_tasks(&db, &Actor::system(), false).await.unwrap();
+
+        assert!(again.escalated.is_empty());
+        assert_eq!(priority_of(&db, &old).await, "High");
+    }
+}
@@ -0,0 +1,572 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use futures_util::Stream;
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+
+use crate::database::{self, DbPool};
+use crate::markdown::FrontMatter;
+use crate::models::*;
+
+/// Tasks fetched per query by the streaming export
+const STREAM_PAGE_SIZE: u32 = 200;
+
+/// Front matter for a board exported from this workspace now
+pub async fn front_matter(pool: &DbPool) -> anyhow::Result<FrontMatter> {
+    let config = database::get_workspace_config(pool).await?;
+    Ok(FrontMatter::new(&config.workspace_name))
+}
+
+/// The workspace's `export_filename` filled in for an export of `filter`,
+/// cut down to characters that are safe in a file name on any system
+pub fn filename(config: &WorkspaceConfig, filter: &str, extension: &str) -> String {
+    let date = Utc::now().with_timezone(&config.tz()).format("%Y-%m-%d").to_string();
+    let name = config.export_filename
+        .replace("{workspace}", &config.workspace_name)
+        .replace("{date}", &date)
+        .replace("{filter}", filter);
+
+    let mut safe = String::new();
+    for c in name.chars() {
+        let c = if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '-' };
+        if !(c == '-' && safe.ends_with('-')) {
+            safe.push(c);
+        }
+    }
+    let safe = safe.trim_matches(|c| c == '-' || c == '.');
+
+    if safe.is_empty() {
+        format!("taskdown-export-{}.{}", date, extension)
+    } else {
+        format!("{}.{}", safe, extension)
+    }
+}
+
+/// A short description of the filters in `params` for `{filter}`; `all`
+/// when nothing is filtered
+pub fn filter_label(params: &TaskQueryParams) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    parts.extend(params.epic.iter().map(String::as_str));
+    parts.extend(params.status.as_deref());
+    parts.extend(params.assignee.iter().map(String::as_str));
+    parts.extend(params.label.iter().map(String::as_str));
+    if params.include_done == Some(false) {
+        parts.push("open");
+    }
+
+    if parts.is_empty() {
+        "all".to_string()
+    } else {
+        parts.join("-")
+    }
+}
+
+/// The board as Markdown, grouped by epic, in the format the importer reads
+pub fn render_markdown(tasks: &[Task], front_matter: &FrontMatter, options: &MarkdownOptions) -> String {
+    let mut markdown = front_matter.render();
+    markdown.push_str("# Taskdown Export\n\n");
+    
+    // Group tasks by epic, in name order; each group keeps the order `tasks` came in
+    let mut epics: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+    let mut orphaned_tasks = Vec::new();
+    
+    for task in tasks {
+        if let Some(epic) = &task.epic {
+            epics.entry(epic.clone()).or_default().push(task);
+        } else {
+            orphaned_tasks.push(task);
+        }
+    }
+    
+    // Export epics
+    for (epic_name, epic_tasks) in epics {
+        markdown.push_str(&format!("## Epic: {}\n\n", epic_name));
+        
+        for task in epic_tasks {
+            export_task_to_markdown(task, options, &mut markdown);
+        }
+    }
+    
+    // Export orphaned tasks
+    if !orphaned_tasks.is_empty() {
+        markdown.push_str("## Miscellaneous Tasks\n\n");
+        for task in orphaned_tasks {
+            export_task_to_markdown(task, options, &mut markdown);
+        }
+    }
+
+    markdown
+}
+
+/// One task on its own, in the same form as in a board export
+pub fn render_task(task: &Task, options: &MarkdownOptions) -> String {
+    let mut markdown = String::new();
+    export_task_to_markdown(task, options, &mut markdown);
+    markdown
+}
+
+fn export_task_to_markdown(task: &Task, options: &MarkdownOptions, markdown: &mut String) {
+    markdown.push_str(&format!("### {}: {}\n\n", task.id, task.title));
+    
+    markdown.push_str(&format!("**Type**: {:?}\n", task.r#type));
+    markdown.push_str(&format!("**Priority**: {}\n", task.priority.name()));
+    markdown.push_str(&format!("**Status**: {}\n", task.status.stored_name()));
+    
+    if let Some(points) = task.story_points {
+        markdown.push_str(&format!("**Story Points**: {}\n", points));
+    }
+    
+    if let Some(sprint) = &task.sprint {
+        markdown.push_str(&format!("**Sprint**: {}\n", sprint));
+    }
+    
+    if let Some(assignee) = &task.assignee {
+        markdown.push_str(&format!("**Assignee**: {}\n", assignee));
+    }
+    
+    if !task.description.is_empty() {
+        markdown.push_str(&format!("**Description**: {}\n", task.description));
+    }
+    
+    // Export acceptance criteria
+    if !task.acceptance_criteria.is_empty() {
+        markdown.push_str("\n**Acceptance Criteria**:\n");
+        for item in &task.acceptance_criteria {
+            let checkbox = if item.completed { "x" } else { " " };
+            markdown.push_str(&format!("- [{}] {}\n", checkbox, item.text));
+        }
+    }
+    
+    // Export technical tasks
+    if !task.technical_tasks.is_empty() {
+        markdown.push_str("\n**Technical Tasks**:\n");
+        for item in &task.technical_tasks {
+            let checkbox = if item.completed { "x" } else { " " };
+            markdown.push_str(&format!("- [{}] {}\n", checkbox, item.text));
+        }
+    }
+    
+    // Export dependencies and blocks
+    if !task.dependencies.is_empty() {
+        markdown.push_str(&format!("**Dependencies**: {}\n", task.dependencies.join(", ")));
+    } else if !options.omit_empty_sections {
+        markdown.push_str("**Dependencies**: None\n");
+    }
+    
+    if !task.blocks.is_empty() {
+        markdown.push_str(&format!("**Blocks**: {}\n", task.blocks.join(", ")));
+    } else if !options.omit_empty_sections {
+        markdown.push_str("**Blocks**: None\n");
+    }
+    
+    markdown.push_str("\n---\n\n");
+}
+
+const CSV_HEADER: &[&str] = &[
+    "id", "title", "type", "priority", "status", "story_points", "sprint", "epic", "assignee",
+    "due_date", "archived", "acceptance_criteria", "technical_tasks", "dependencies", "blocks",
+    "description", "created_at", "updated_at",
+];
+
+/// One row per task. Checklists are summarised as `done/total`, and
+/// relationships as space-separated task ids.
+pub fn render_csv(tasks: &[Task]) -> String {
+    let mut csv = csv_row(CSV_HEADER.iter().map(|column| column.to_string()));
+
+    for task in tasks {
+        csv.push_str(&csv_row([
+            task.id.clone(),
+            task.title.clone(),
+            format!("{:?}", task.r#type),
+            task.priority.name().to_string(),
+            task.status.stored_name().to_string(),
+            task.story_points.map(|points| points.to_string()).unwrap_or_default(),
+            task.sprint.clone().unwrap_or_default(),
+            task.epic.clone().unwrap_or_default(),
+            task.assignee.clone().unwrap_or_default(),
+            task.due_date.map(|date| date.to_rfc3339()).unwrap_or_default(),
+            task.archived.to_string(),
+            checklist_progress(&task.acceptance_criteria),
+            checklist_progress(&task.technical_tasks),
+            task.dependencies.join(" "),
+            task.blocks.join(" "),
+            task.description.clone(),
+            task.created_at.to_rfc3339(),
+            task.updated_at.to_rfc3339(),
+        ]));
+    }
+
+    csv
+}
+
+fn checklist_progress(items: &[ChecklistItem]) -> String {
+    if items.is_empty() {
+        return String::new();
+    }
+    let done = items.iter().filter(|item| item.completed).count();
+    format!("{}/{}", done, items.len())
+}
+
+// RFC 4180: fields with commas, quotes or line breaks are quoted, with
+// quotes doubled; rows end in CRLF
+fn csv_row(fields: impl IntoIterator<Item = String>) -> String {
+    let fields: Vec<String> = fields.into_iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+
+    format!("{}\r\n", fields.join(","))
+}
+
+/// The tasks as the API returns them
+pub fn render_json(tasks: &[Task]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(tasks)
+}
+
+/// A zip of the board as `board.md`, `tasks.csv` and `tasks.json`, plus a
+/// `manifest.json` describing them
+pub fn render_bundle(tasks: &[Task], front_matter: &FrontMatter, options: &MarkdownOptions) -> anyhow::Result<Vec<u8>> {
+    let files = [
+        ("board.md", "text/markdown", render_markdown(tasks, front_matter, options)),
+        ("tasks.csv", "text/csv", render_csv(tasks)),
+        ("tasks.json", "application/json", render_json(tasks)?),
+    ];
+
+    let manifest = serde_json::json!({
+        "generatedAt": Utc::now(),
+        "taskCount": tasks.len(),
+        "files": files.iter().map(|(name, content_type, content)| serde_json::json!({
+            "name": name,
+            "contentType": content_type,
+            "bytes": content.len(),
+        })).collect::<Vec<_>>(),
+    });
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("manifest.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    for (name, _, content) in &files {
+        zip.start_file(*name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+struct StreamState {
+    pool: DbPool,
+    format: ExportFormat,
+    include_done: bool,
+    sort: Option<String>,
+    options: MarkdownOptions,
+    // Sort keys of the last task written; None before the first page
+    cursor: Option<String>,
+    // Epic of the last task written; None before the first task
+    epic: Option<Option<String>>,
+    finished: bool,
+}
+
+/// The export written a page at a time, so a large board is never held in
+/// memory all at once. Markdown matches `render_markdown`; JSON is the
+/// array `render_json` produces. Either way tasks come grouped by epic, in
+/// `sort` order within each group, or oldest first without one.
+pub fn stream(
+    pool: DbPool,
+    format: ExportFormat,
+    include_done: bool,
+    sort: Option<String>,
+    options: MarkdownOptions,
+) -> impl Stream<Item = anyhow::Result<String>> {
+    let state = StreamState { pool, format, include_done, sort, options, cursor: None, epic: None, finished: false };
+
+    futures_util::stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+
+        let first_page = state.cursor.is_none();
+        let page = match database::get_export_page(&state.pool, state.include_done, state.sort.as_deref(), state.cursor.as_deref(), STREAM_PAGE_SIZE).await {
+            Ok((page, cursor)) => {
+                state.cursor = cursor.or(state.cursor.take());
+                page
+            }
+            Err(e) => {
+                state.finished = true;
+                return Some((Err(e), state));
+            }
+        };
+        state.finished = (page.len() as u32) < STREAM_PAGE_SIZE;
+
+        let mut chunk = String::new();
+        if first_page {
+            match state.format {
+                ExportFormat::Markdown => match front_matter(&state.pool).await {
+                    Ok(front_matter) => {
+                        chunk.push_str(&front_matter.render());
+                        chunk.push_str("# Taskdown Export\n\n");
+                    }
+                    Err(e) => {
+                        state.finished = true;
+                        return Some((Err(e), state));
+                    }
+                },
+                ExportFormat::Json => chunk.push('['),
+            }
+        }
+
+        for (i, task) in page.iter().enumerate() {
+            match state.format {
+                ExportFormat::Markdown => {
+                    if state.epic.as_ref() != Some(&task.epic) {
+                        match &task.epic {
+                            Some(epic) => chunk.push_str(&format!("## Epic: {}\n\n", epic)),
+                            None => chunk.push_str("## Miscellaneous Tasks\n\n"),
+                        }
+                        state.epic = Some(task.epic.clone());
+                    }
+                    export_task_to_markdown(task, &state.options, &mut chunk);
+                }
+                ExportFormat::Json => {
+                    if !first_page || i > 0 {
+                        chunk.push(',');
+                    }
+                    match serde_json::to_string(task) {
+                        Ok(json) => chunk.push_str(&json),
+                        Err(e) => {
+                            state.finished = true;
+                            return Some((Err(e.into()), state));
+                        }
+                    }
+                }
+            }
+        }
+
+        if state.finished && state.format == ExportFormat::Json {
+            chunk.push(']');
+        }
+
+        Some((Ok(chunk), state))
+    })
+}
+
+/// Columns of the activity CSV; `details` is flattened into field/old/new
+const ACTIVITY_CSV_HEADER: &[&str] = &[
+    "id", "timestamp", "user_id", "user_name", "action", "target_type", "target_id",
+    "target_name", "field", "old_value", "new_value",
+];
+
+struct ActivityStreamState {
+    pool: DbPool,
+    format: ActivityExportFormat,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    tz: Tz,
+    offset: u32,
+    finished: bool,
+}
+
+/// The activity log, oldest first, written a page at a time. Timestamps are
+/// given in the workspace's timezone.
+pub fn activity_stream(
+    pool: DbPool,
+    params: ActivityExportParams,
+    tz: Tz,
+) -> impl Stream<Item = anyhow::Result<String>> {
+    let state = ActivityStreamState {
+        pool,
+        format: params.format.unwrap_or_default(),
+        from: params.from,
+        to: params.to,
+        tz,
+        offset: 0,
+        finished: false,
+    };
+
+    futures_util::stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+
+        let page = match database::get_activity_export_page(
+            &state.pool, state.from, state.to, state.offset, STREAM_PAGE_SIZE,
+        ).await {
+            Ok(page) => page,
+            Err(e) => {
+                state.finished = true;
+                return Some((Err(e), state));
+            }
+        };
+        state.finished = (page.len() as u32) < STREAM_PAGE_SIZE;
+
+        let mut chunk = String::new();
+        if state.offset == 0 {
+            match state.format {
+                ActivityExportFormat::Csv => chunk.push_str(&csv_row(ACTIVITY_CSV_HEADER.iter().map(|column| column.to_string()))),
+                ActivityExportFormat::Json => chunk.push('['),
+            }
+        }
+
+        for (i, activity) in page.iter().enumerate() {
+            let timestamp = activity.timestamp.with_timezone(&state.tz).to_rfc3339();
+            match state.format {
+                ActivityExportFormat::Csv => {
+                    let details = activity.details.as_ref();
+                    chunk.push_str(&csv_row([
+                        activity.id.clone(),
+                        timestamp,
+                        activity.user_id.clone(),
+                        activity.user_name.clone(),
+                        activity.action.clone(),
+                        activity.target_type.clone(),
+                        activity.target_id.clone(),
+                        activity.target_name.clone(),
+                        details.and_then(|details| details.field.clone()).unwrap_or_default(),
+                        detail_value(details.and_then(|details| details.old_value.as_ref())),
+                        detail_value(details.and_then(|details| details.new_value.as_ref())),
+                    ]));
+                }
+                ActivityExportFormat::Json => {
+                    if state.offset > 0 || i > 0 {
+                        chunk.push(',');
+                    }
+                    let mut entry = match serde_json::to_value(activity) {
+                        Ok(entry) => entry,
+                        Err(e) => {
+                            state.finished = true;
+                            return Some((Err(e.into()), state));
+                        }
+                    };
+                    entry["timestamp"] = serde_json::Value::String(timestamp);
+                    chunk.push_str(&entry.to_string());
+                }
+            }
+        }
+
+        if state.finished && state.format == ActivityExportFormat::Json {
+            chunk.push(']');
+        }
+        state.offset += page.len() as u32;
+
+        Some((Ok(chunk), state))
+    })
+}
+
+// Strings are written as-is; other values as JSON
+fn detail_value(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use std::io::Read;
+
+    #[tokio::test]
+    async fn bundles_hold_every_format_and_a_manifest() {
+        let db = db().await;
+        add_task(&db, new_task("Bundled")).await;
+        let tasks = database::get_tasks(&db, &TaskQueryParams::default()).await.unwrap();
+
+        let bundle = render_bundle(&tasks, &FrontMatter::new("Test"), &MarkdownOptions::default()).unwrap();
+        let mut zip = zip::ZipArchive::new(Cursor::new(bundle)).unwrap();
+        for name in ["manifest.json", "board.md", "tasks.csv", "tasks.json"] {
+            let mut content = String::new();
+            zip.by_name(name).unwrap().read_to_string(&mut content).unwrap();
+            assert!(!content.is_empty(), "{name} is empty");
+            if name != "manifest.json" {
+                assert!(content.contains("Bundled"), "{name} is missing the task");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn activity_csv_flattens_details_into_columns() {
+        use futures_util::TryStreamExt;
+
+        let db = db().await;
+        let details = ActivityDetails {
+            field: Some("status".to_string()),
+            old_value: Some(serde_json::json!("Todo")),
+            new_value: Some(serde_json::json!("Done")),
+        };
+        database::log_activity(&*db, &Actor::system(), "status_changed", "task", "TASK-1", "Task", Some(&details)).await.unwrap();
+        database::log_activity(&*db, &Actor::system(), "created", "task", "TASK-2", "Other", None).await.unwrap();
+
+        let params = ActivityExportParams { format: Some(ActivityExportFormat::Csv), from: None, to: None };
+        let chunks: Vec<String> = activity_stream(db.pool.clone(), params, chrono_tz::UTC).try_collect().await.unwrap();
+        let csv = chunks.concat();
+        let rows: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(rows.len(), 3, "{csv}");
+        assert!(rows[0].ends_with("field,old_value,new_value"), "{}", rows[0]);
+        let changed = rows.iter().find(|row| row.contains("status_changed")).unwrap();
+        assert!(changed.ends_with(",status,Todo,Done"), "{changed}");
+    }
+
+    #[tokio::test]
+    async fn front_matter_survives_a_round_trip() {
+        let db = db().await;
+        add_task(&db, new_task("Exported")).await;
+        let tasks = database::get_tasks(&db, &TaskQueryParams::default()).await.unwrap();
+
+        let front_matter = FrontMatter::new("Team \"Rocket\": Q3");
+        let markdown = render_markdown(&tasks, &front_matter, &MarkdownOptions::default());
+        let (parsed, board) = crate::markdown::parse_document(&markdown);
+        assert_eq!(parsed, front_matter);
+        assert_eq!(parsed.version_warning(), None);
+        assert_eq!(board.iter().map(|task| task.title.as_str()).collect::<Vec<_>>(), ["Exported"]);
+
+        let newer = markdown.replacen("version: \"1\"", "version: \"2\"\nunknown: ignored", 1);
+        let (parsed, board) = crate::markdown::parse_document(&newer);
+        assert!(parsed.version_warning().is_some_and(|warning| warning.contains("version 2")));
+        assert_eq!(board.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn filenames_follow_the_workspace_template() {
+        let db = db().await;
+        update_config(&db, |config| {
+            config.workspace_name = "Team Rocket/Q3".to_string();
+            config.export_filename = "{workspace}_{filter}_{date}".to_string();
+        }).await;
+        let config = database::get_workspace_config(&db).await.unwrap();
+        let date = Utc::now().with_timezone(&config.tz()).format("%Y-%m-%d");
+
+        let params: TaskQueryParams = multi_query("epic=Auth&include_done=false").await;
+        assert_eq!(filename(&config, &filter_label(&params), "md"), format!("Team-Rocket-Q3_Auth-open_{}.md", date));
+        assert_eq!(filter_label(&TaskQueryParams::default()), "all");
+
+        let mut unsafe_only = config.clone();
+        unsafe_only.export_filename = "../..".to_string();
+        assert_eq!(filename(&unsafe_only, "all", "csv"), format!("taskdown-export-{}.csv", date));
+    }
+
+    #[tokio::test]
+    async fn empty_sections_are_left_out_only_when_asked() {
+        let db = db().await;
+        let first = add_task(&db, new_task("First")).await;
+        let second = add_task(&db, CreateTaskRequest { dependencies: vec![first.id.clone()], ..new_task("Second") }).await;
+
+        let verbose = render_task(&first, &MarkdownOptions::default());
+        assert!(verbose.contains("**Dependencies**: None\n") && verbose.contains("**Blocks**: None\n"), "{verbose}");
+
+        let terse = MarkdownOptions { omit_empty_sections: true };
+        let omitted = render_task(&first, &terse);
+        assert!(!omitted.contains("**Dependencies**") && !omitted.contains("None"), "{omitted}");
+        let second = render_task(&second, &terse);
+        assert!(second.contains(&format!("**Dependencies**: {}\n", first.id)) && !second.contains("**Blocks**"), "{second}");
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Which tasks have to finish before which, built from both `dependencies`
+/// (X depends on Y) and `blocks` (Y blocks X), which describe the same edge
+pub struct DependencyGraph {
+    blockers: HashMap<String, Vec<String>>,
+    blocking: HashMap<String, Vec<String>>,
+}
+
+/// Tasks reachable from a starting task, nearest first
+pub struct Walk {
+    pub task_ids: Vec<String>,
+    /// The walk led back to the starting task
+    pub cycle_detected: bool,
+}
+
+impl DependencyGraph {
+    /// Builds the graph from `(blocker, blocked)` pairs
+    pub fn from_edges(edges: Vec<(String, String)>) -> Self {
+        let mut blockers: HashMap<String, Vec<String>> = HashMap::new();
+        let mut blocking: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (blocker, blocked) in edges {
+            blockers.entry(blocked.clone()).or_default().push(blocker.clone());
+            blocking.entry(blocker).or_default().push(blocked);
+        }
+
+        Self { blockers, blocking }
+    }
+
+    /// Every task that must complete before `task_id`, directly or transitively
+    pub fn blockers_of(&self, task_id: &str) -> Walk {
+        walk(&self.blockers, task_id)
+    }
+
+    /// Every task waiting on `task_id`, directly or transitively
+    pub fn blocking_of(&self, task_id: &str) -> Walk {
+        walk(&self.blocking, task_id)
+    }
+
+    /// Orders `task_ids` so every task comes after the tasks it depends on,
+    /// including through tasks outside the set (A -> X -> B puts B before A).
+    /// Unrelated tasks keep their given order. Fails with the ids on a cycle
+    /// that the set's ordering depends on.
+    pub fn topological_order(&self, task_ids: &[String]) -> Result<Vec<String>, Vec<String>> {
+        // Position in the input, so ties keep the caller's order; tasks only
+        // pulled in as blockers go after them
+        let mut rank: HashMap<&str, usize> = HashMap::new();
+        for (index, task_id) in task_ids.iter().enumerate() {
+            rank.entry(task_id.as_str()).or_insert(index);
+        }
+        let requested: HashSet<&str> = rank.keys().copied().collect();
+        for task_id in task_ids {
+            for blocker in self.blockers_of(task_id).task_ids {
+                if let Some((blocker, _)) = self.blocking.get_key_value(blocker.as_str()) {
+                    rank.entry(blocker.as_str()).or_insert(usize::MAX);
+                }
+            }
+        }
+
+        let mut pending: HashMap<&str, usize> = rank.keys()
+            .map(|&task_id| {
+                let blockers = self.blockers.get(task_id).into_iter().flatten()
+                    .filter(|blocker| rank.contains_key(blocker.as_str()))
+                    .collect::<HashSet<_>>();
+                (task_id, blockers.len())
+            })
+            .collect();
+
+        let mut ready: BTreeSet<(usize, &str)> = pending.iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&task_id, _)| (rank[task_id], task_id))
+            .collect();
+
+        let mut order = Vec::new();
+        while let Some((_, task_id)) = ready.pop_first() {
+            pending.remove(task_id);
+            if requested.contains(task_id) {
+                order.push(task_id.to_string());
+            }
+
+            let blocked: HashSet<&String> = self.blocking.get(task_id).into_iter().flatten().collect();
+            for next in blocked {
+                if let Some(count) = pending.get_mut(next.as_str()) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.insert((rank[next.as_str()], next.as_str()));
+                    }
+                }
+            }
+        }
+
+        if pending.is_empty() {
+            return Ok(order);
+        }
+
+        // Whatever is left either sits on a cycle or waits on one; only the
+        // former are reported
+        let mut cycle: Vec<String> = pending.keys()
+            .filter(|&&task_id| self.blocking_of(task_id).cycle_detected)
+            .map(|task_id| task_id.to_string())
+            .collect();
+        cycle.sort();
+        Err(cycle)
+    }
+}
+
+// Breadth-first so nearer tasks come first; each task is visited once, which
+// also keeps cycles in bad data from looping forever
+fn walk(edges: &HashMap<String, Vec<String>>, start: &str) -> Walk {
+    let mut visited: HashSet<&str> = HashSet::from([start]);
+    let mut queue: VecDeque<&str> = VecDeque::from([start]);
+    let mut task_ids = Vec::new();
+    let mut cycle_detected = false;
+
+    while let Some(current) = queue.pop_front() {
+        for next in edges.get(current).into_iter().flatten() {
+            if next == start {
+                cycle_detected = true;
+            }
+            if visited.insert(next) {
+                task_ids.push(next.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    Walk { task_ids, cycle_detected }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> DependencyGraph {
+        DependencyGraph::from_edges(edges.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect())
+    }
+
+    #[test]
+    fn blockers_include_every_ancestor() {
+        // Design blocks Build, which blocks Ship
+        let graph = graph(&[("Design", "Build"), ("Build", "Ship")]);
+
+        let blockers = graph.blockers_of("Ship");
+        assert_eq!(blockers.task_ids, ["Build", "Design"]);
+        assert!(!blockers.cycle_detected);
+        assert_eq!(graph.blocking_of("Design").task_ids, ["Build", "Ship"]);
+    }
+
+    #[test]
+    fn cycles_end_the_walk() {
+        let graph = graph(&[("A", "B"), ("B", "C"), ("C", "A")]);
+
+        let blockers = graph.blockers_of("A");
+        assert_eq!(blockers.task_ids, ["C", "B"]);
+        assert!(blockers.cycle_detected);
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn dependencies_are_ordered_before_their_dependents() {
+        // Schema -> API -> UI, and Schema -> Migration
+        let graph = graph(&[("Schema", "API"), ("API", "UI"), ("Schema", "Migration")]);
+
+        let order = graph.topological_order(&ids(&["UI", "Migration", "API", "Schema", "Docs"])).unwrap();
+        let position = |id: &str| order.iter().position(|task_id| task_id == id).unwrap();
+        assert_eq!(order.len(), 5);
+        assert!(position("Schema") < position("API") && position("API") < position("UI"));
+        assert!(position("Schema") < position("Migration"));
+    }
+
+    #[test]
+    fn cycles_are_reported_with_their_ids() {
+        let graph = graph(&[("A", "B"), ("B", "C"), ("C", "A"), ("C", "D")]);
+
+        let mut cycle = graph.topological_order(&ids(&["A", "B", "C", "D"])).unwrap_err();
+        cycle.sort();
+        assert_eq!(cycle, ["A", "B", "C"]);
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::Task;
+
+const PRODID: &str = "-//Taskdown//Taskdown Backend//EN";
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Renders an RFC 5545 VCALENDAR with one VEVENT per task that has a due date.
+/// Tasks without a due date are skipped.
+pub fn render_calendar(tasks: &[Task]) -> String {
+    let stamp = format_datetime(&Utc::now());
+    let mut calendar = String::new();
+
+    push_line(&mut calendar, "BEGIN:VCALENDAR");
+    push_line(&mut calendar, "VERSION:2.0");
+    push_line(&mut calendar, &format!("PRODID:{}", PRODID));
+    push_line(&mut calendar, "CALSCALE:GREGORIAN");
+
+    for task in tasks {
+        let Some(due_date) = task.due_date else {
+            continue;
+        };
+
+        push_line(&mut calendar, "BEGIN:VEVENT");
+        push_line(&mut calendar, &format!("UID:{}", escape_text(&task.id)));
+        push_line(&mut calendar, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut calendar, &format!("DTSTART:{}", format_datetime(&due_date)));
+        push_line(&mut calendar, &format!("SUMMARY:{}", escape_text(&task.title)));
+        if !task.description.is_empty() {
+            push_line(&mut calendar, &format!("DESCRIPTION:{}", escape_text(&task.description)));
+        }
+        push_line(&mut calendar, "END:VEVENT");
+    }
+
+    push_line(&mut calendar, "END:VCALENDAR");
+    calendar
+}
+
+fn format_datetime(date: &DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// TEXT values escape backslashes, separators and newlines (RFC 5545 §3.3.11)
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Content lines longer than 75 octets are folded onto continuation lines that
+// start with a single space, never splitting a UTF-8 character (RFC 5545 §3.1)
+fn push_line(calendar: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            calendar.push_str("\r\n ");
+            octets = 1;
+        }
+        calendar.push(c);
+        octets += c.len_utf8();
+    }
+    calendar.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use chrono::TimeZone;
+
+    // Content lines with folded continuations joined back up
+    fn unfolded_lines(calendar: &str) -> Vec<String> {
+        calendar.replace("\r\n ", "").split("\r\n").filter(|line| !line.is_empty()).map(String::from).collect()
+    }
+
+    #[tokio::test]
+    async fn tasks_with_due_dates_become_events() {
+        let db = db().await;
+        let due = Utc.with_ymd_and_hms(2026, 3, 14, 9, 30, 0).unwrap();
+        let mut dated = new_task(&format!("Ship; then celebrate, {}", "with a long title ".repeat(4)));
+        dated.due_date = Some(due);
+        let dated = add_task(&db, dated).await;
+        let undated = add_task(&db, new_task("Someday")).await;
+
+        let calendar = render_calendar(&[dated.clone(), undated]);
+        assert!(calendar.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+
+        let lines = unfolded_lines(&calendar);
+        let event: Vec<&str> = lines.iter()
+            .map(String::as_str)
+            .skip_while(|line| *line != "BEGIN:VEVENT")
+            .take_while(|line| *line != "END:VEVENT")
+            .collect();
+        assert_eq!(lines.iter().filter(|line| *line == "BEGIN:VEVENT").count(), 1);
+        assert!(event.contains(&format!("UID:{}", dated.id).as_str()));
+        assert!(event.contains(&"DTSTART:20260314T093000Z"));
+        assert!(event.iter().any(|line| line.starts_with(r"SUMMARY:Ship\; then celebrate\, with")));
+        assert_eq!(lines.first().map(String::as_str), Some("BEGIN:VCALENDAR"));
+        assert_eq!(lines.last().map(String::as_str), Some("END:VCALENDAR"));
+    }
+}
@@ -0,0 +1,318 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use crate::database::{self, DbPool};
+use crate::markdown::ParsedTask;
+use crate::models::*;
+use crate::validation::{self, ValidationError};
+
+/// Writes parsed tasks into the workspace. Relationships are saved once every
+/// task exists so that references between imported tasks resolve. `force`
+/// skips the max_tasks check and must only be set for admins.
+pub async fn import_tasks(
+    pool: &DbPool,
+    mut tasks: Vec<ParsedTask>,
+    options: &ImportOptions,
+    user_id: Option<&str>,
+    force: bool,
+) -> Result<ImportResult> {
+    let preserve_ids = options.preserve_ids.unwrap_or(false);
+    let on_conflict = options.on_conflict.unwrap_or_default();
+    let mut result = ImportResult {
+        imported: 0,
+        updated: 0,
+        skipped: 0,
+        outcomes: vec![],
+        errors: vec![],
+        warnings: vec![],
+    };
+
+    // Nothing is written when any supplied id, status, priority or estimate is rejected
+    let config = database::get_workspace_config(pool).await?;
+    if preserve_ids {
+        let ids: Vec<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+        validation::validate_task_ids(&config, &ids)?;
+    }
+    for task in &tasks {
+        validation::validate_status(&config, &task.status)
+            .and_then(|_| validation::validate_priority(&config, &task.priority))
+            .and_then(|_| validation::validate_story_points(&config, task.story_points))
+            .map_err(|e| ValidationError { message: format!("{}: {}", task.id, e.message), ..e })?;
+    }
+
+    let overwrite = options.overwrite.unwrap_or(false);
+    if !force {
+        check_capacity(pool, &tasks, preserve_ids, overwrite).await?;
+    }
+
+    if overwrite {
+        database::clear_all_tasks(pool).await?;
+    }
+
+    if options.infer_epic_from_prefix.unwrap_or(false) {
+        for task in tasks.iter_mut().filter(|task| task.epic.is_none()) {
+            task.epic = epic_from_prefix(&task.id);
+            if task.epic.is_some() {
+                task.provided.insert("epic");
+            }
+        }
+    }
+
+    if let Some(default_assignee) = options.default_assignee.as_deref().filter(|a| !a.is_empty()) {
+        for task in tasks.iter_mut().filter(|task| task.assignee.is_none()) {
+            task.assignee = Some(default_assignee.to_string());
+            task.provided.insert("assignee");
+        }
+    }
+
+    // Ids used in the board mapped to the ids the tasks are stored under
+    let id_map: HashMap<String, String> = tasks.iter()
+        .map(|task| {
+            let id = if preserve_ids {
+                task.id.clone()
+            } else {
+                uuid::Uuid::new_v4().to_string()
+            };
+            (task.id.clone(), id)
+        })
+        .collect();
+
+    let mut saved = Vec::new();
+    for task in &tasks {
+        let id = &id_map[&task.id];
+        let mut request = task.to_create_request();
+        request.dependencies.clear();
+        request.blocks.clear();
+
+        let exists = preserve_ids && database::task_exists(pool, id).await?;
+        let outcome = match (exists, on_conflict) {
+            (false, _) => database::create_task_with_id(pool, id, &request, user_id).await
+                .map(|_| ImportOutcome::Created),
+            (true, ConflictStrategy::Skip) => Ok(ImportOutcome::Skipped),
+            (true, ConflictStrategy::Overwrite) => database::update_task(pool, id, &update_request(request), user_id).await
+                .map(|_| ImportOutcome::Updated),
+            (true, ConflictStrategy::Merge) => database::update_task(pool, id, &merge_request(request, &task.provided), user_id).await
+                .map(|_| ImportOutcome::Merged),
+        };
+
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                result.errors.push(format!("{}: {}", task.id, e));
+                ImportOutcome::Failed
+            }
+        };
+        match outcome {
+            ImportOutcome::Created => result.imported += 1,
+            ImportOutcome::Updated | ImportOutcome::Merged => result.updated += 1,
+            ImportOutcome::Skipped => result.skipped += 1,
+            ImportOutcome::Failed => {}
+        }
+        if matches!(outcome, ImportOutcome::Created | ImportOutcome::Updated | ImportOutcome::Merged) {
+            saved.push((task, outcome));
+        }
+        result.outcomes.push(TaskImportOutcome { id: task.id.clone(), outcome });
+    }
+
+    let resolve = |ids: &[String]| -> Vec<String> {
+        ids.iter()
+            .map(|id| id_map.get(id).cloned().unwrap_or_else(|| id.clone()))
+            .collect()
+    };
+
+    for (task, outcome) in saved {
+        let id = &id_map[&task.id];
+        let mut dependencies = resolve(&task.dependencies);
+        let mut blocks = resolve(&task.blocks);
+
+        // A merge keeps whichever relationship list the board leaves out
+        let keeps_dependencies = !task.provided.contains("dependencies");
+        let keeps_blocks = !task.provided.contains("blocks");
+        if outcome == ImportOutcome::Merged && (keeps_dependencies || keeps_blocks) {
+            let Some(existing) = database::get_task_by_id(pool, id).await? else {
+                continue;
+            };
+            if keeps_dependencies {
+                dependencies = existing.dependencies;
+            }
+            if keeps_blocks {
+                blocks = existing.blocks;
+            }
+        }
+
+        if let Err(e) = database::set_task_relationships(pool, id, &dependencies, &blocks).await {
+            result.errors.push(format!("{}: failed to save relationships: {}", task.id, e));
+        }
+    }
+
+    Ok(result)
+}
+
+// Only tasks that will be created count against max_tasks; preserved ids that
+// already exist are updated in place, and an overwrite starts from empty
+async fn check_capacity(pool: &DbPool, tasks: &[ParsedTask], preserve_ids: bool, overwrite: bool) -> Result<()> {
+    let mut adding = tasks.len() as u32;
+    if preserve_ids && !overwrite {
+        for task in tasks {
+            if database::task_exists(pool, &task.id).await? {
+                adding -= 1;
+            }
+        }
+    }
+
+    validation::check_task_capacity(pool, adding, overwrite).await
+}
+
+// "AUTH-12" -> "AUTH"
+fn epic_from_prefix(task_id: &str) -> Option<String> {
+    match task_id.split_once('-') {
+        Some((prefix, _)) if !prefix.is_empty() => Some(prefix.to_string()),
+        _ => None,
+    }
+}
+
+// Relationships are written separately, so they're left untouched here
+fn update_request(request: CreateTaskRequest) -> UpdateTaskRequest {
+    UpdateTaskRequest {
+        title: Some(request.title),
+        r#type: Some(request.r#type),
+        priority: Some(request.priority),
+        status: Some(request.status),
+        story_points: Some(request.story_points),
+        sprint: Some(request.sprint),
+        epic: Some(request.epic),
+        description: Some(request.description),
+        acceptance_criteria: Some(request.acceptance_criteria),
+        technical_tasks: Some(request.technical_tasks),
+        dependencies: None,
+        blocks: None,
+        assignee: Some(request.assignee),
+        is_favorite: None,
+        thumbnail: None,
+        due_date: None,
+    }
+}
+
+// Only the fields the board gave; relationships are written separately
+fn merge_request(request: CreateTaskRequest, provided: &HashSet<&'static str>) -> UpdateTaskRequest {
+    let given = |field: &str| provided.contains(field);
+    UpdateTaskRequest {
+        title: Some(request.title),
+        r#type: given("type").then_some(request.r#type),
+        priority: given("priority").then_some(request.priority),
+        status: given("status").then_some(request.status),
+        story_points: given("story_points").then_some(request.story_points),
+        sprint: given("sprint").then_some(request.sprint),
+        epic: given("epic").then_some(request.epic),
+        description: given("description").then_some(request.description),
+        acceptance_criteria: given("acceptance_criteria").then_some(request.acceptance_criteria),
+        technical_tasks: given("technical_tasks").then_some(request.technical_tasks),
+        assignee: given("assignee").then_some(request.assignee),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::parse_board;
+    use crate::test_support::*;
+
+    const BOARD: &str = "### PROJ-1: New title\n\n**Type**: Task\n**Priority**: Low\n\n### PROJ-2: Brand new\n\n**Type**: Task\n**Priority**: Medium\n\n";
+
+    // Imports BOARD over an existing PROJ-1, returning the outcomes and PROJ-1 afterwards
+    async fn import_over_existing(on_conflict: ConflictStrategy) -> (Vec<ImportOutcome>, Task) {
+        let db = db().await;
+        let mut existing = new_task("Old title");
+        existing.priority = Priority::High;
+        existing.description = "Keep me".to_string();
+        existing.assignee = Some("alice".to_string());
+        database::create_task_with_id(&db, "PROJ-1", &existing, None).await.unwrap();
+
+        let options = ImportOptions { preserve_ids: Some(true), on_conflict: Some(on_conflict), ..Default::default() };
+        let result = import_tasks(&db, parse_board(BOARD), &options, None, false).await.unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(database::task_exists(&db, "PROJ-2").await.unwrap());
+
+        let outcomes = result.outcomes.iter().map(|outcome| outcome.outcome).collect();
+        (outcomes, database::get_task_by_id(&db, "PROJ-1").await.unwrap().unwrap())
+    }
+
+    #[tokio::test]
+    async fn skip_leaves_existing_tasks_alone() {
+        let (outcomes, task) = import_over_existing(ConflictStrategy::Skip).await;
+        assert_eq!(outcomes, [ImportOutcome::Skipped, ImportOutcome::Created]);
+        assert_eq!((task.title.as_str(), task.priority.name()), ("Old title", "High"));
+    }
+
+    #[tokio::test]
+    async fn overwrite_replaces_every_field() {
+        let (outcomes, task) = import_over_existing(ConflictStrategy::Overwrite).await;
+        assert_eq!(outcomes, [ImportOutcome::Updated, ImportOutcome::Created]);
+        assert_eq!((task.title.as_str(), task.priority.name()), ("New title", "Low"));
+        assert_eq!((task.description.as_str(), task.assignee), ("", None));
+    }
+
+    #[tokio::test]
+    async fn merge_updates_only_the_fields_the_board_gives() {
+        let (outcomes, task) = import_over_existing(ConflictStrategy::Merge).await;
+        assert_eq!(outcomes, [ImportOutcome::Merged, ImportOutcome::Created]);
+        assert_eq!((task.title.as_str(), task.priority.name()), ("New title", "Low"));
+        assert_eq!((task.description.as_str(), task.assignee.as_deref()), ("Keep me", Some("alice")));
+    }
+
+    async fn epics_after_import(pool: &DbPool, board: &str, options: &ImportOptions) -> Vec<(String, Option<String>)> {
+        let result = import_tasks(pool, parse_board(board), options, None, false).await.unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let mut tasks = database::get_tasks(pool, &TaskQueryParams::default()).await.unwrap();
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+        tasks.into_iter().map(|task| (task.id, task.epic)).collect()
+    }
+
+    #[tokio::test]
+    async fn epics_are_inferred_from_id_prefixes_when_asked() {
+        let db = db().await;
+        let board = "## Epic: Billing\n\n### PAY-1: Invoices\n\n**Type**: Task\n\n\
+                     ## Miscellaneous Tasks\n\n### AUTH-1: Login\n\n**Type**: Task\n\n### AUTH-2: Logout\n\n**Type**: Task\n\n";
+        let options = ImportOptions { preserve_ids: Some(true), infer_epic_from_prefix: Some(true), ..Default::default() };
+
+        let epics = epics_after_import(&db, board, &options).await;
+        assert_eq!(epics, [
+            ("AUTH-1".to_string(), Some("AUTH".to_string())),
+            ("AUTH-2".to_string(), Some("AUTH".to_string())),
+            ("PAY-1".to_string(), Some("Billing".to_string())),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn unassigned_tasks_get_the_default_assignee() {
+        let db = db().await;
+        let board = "### PROJ-1: Unassigned\n\n**Type**: Task\n\n\
+                     ### PROJ-2: Assigned\n\n**Type**: Task\n**Assignee**: bob\n\n";
+        let options = ImportOptions {
+            preserve_ids: Some(true),
+            default_assignee: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let result = import_tasks(&db, parse_board(board), &options, None, false).await.unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let assignee = |task: Option<Task>| task.unwrap().assignee;
+        assert_eq!(assignee(database::get_task_by_id(&db, "PROJ-1").await.unwrap()).as_deref(), Some("alice"));
+        assert_eq!(assignee(database::get_task_by_id(&db, "PROJ-2").await.unwrap()).as_deref(), Some("bob"));
+    }
+
+    #[tokio::test]
+    async fn ids_outside_the_workspace_pattern_are_rejected() {
+        let db = db().await;
+        update_config(&db, |config| config.workflow.task_id_pattern = Some("^[A-Z]+-[0-9]+$".to_string())).await;
+        let board = "### PROJ-1: Fine\n\n**Type**: Task\n\n### proj_2: Lowercase\n\n**Type**: Task\n\n";
+        let options = ImportOptions { preserve_ids: Some(true), ..Default::default() };
+
+        let error = import_tasks(&db, parse_board(board), &options, None, false).await.unwrap_err();
+        let error = error.downcast_ref::<ValidationError>().expect("validation error");
+        assert_eq!(error.code, "INVALID_TASK_ID");
+        assert_eq!(task_count(&db).await, 0);
+    }
+}
@@ -1,10 +1,12 @@
 use axum::{
-    http::{header, Method},
-    routing::{get, post, put, delete},
-    Router,
+    extract::{DefaultBodyLimit, Request},
+    http::{header, HeaderName, Method},
+    middleware::{from_fn, from_fn_with_state},
+    routing::{get, post, put, patch, delete},
+    Router, ServiceExt,
 };
 use std::collections::HashMap;
-use tower::ServiceBuilder;
+use tower::{Layer, ServiceBuilder};
 use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber;
 
@@ -12,8 +14,27 @@ mod models;
 mod handlers;
 mod database;
 mod auth;
+mod validation;
+mod middleware;
+mod markdown;
+mod import;
+mod maintenance;
+mod csv;
+mod diff;
+mod escalation;
+mod ical;
+mod export;
+mod notifications;
+mod retention;
+mod thumbnails;
+mod graph;
+mod remote;
+mod webhooks;
+#[cfg(test)]
+mod test_support;
 
 use handlers::*;
+use middleware::{canonical_task_ids, query_timeout, read_timeout, refresh_session, require_feature, require_permission, Feature, Permission};
 
 #[tokio::main]
 async fn main() {
@@ -23,10 +44,43 @@ async fn main() {
     // Initialize database
     let db_pool = database::init_db().await.expect("Failed to initialize database");
 
+    // Email notifications are a no-op unless SMTP_HOST is set
+    notifications::init(db_pool.clone());
+
+    // Trim the activity log to the configured retention window
+    retention::spawn(db_pool.clone());
+
+    // Task events are only delivered while the webhooks feature is on
+    webhooks::init(db_pool.clone());
+
+    // Analytics endpoints, available only while the analytics feature is on
+    // and only to sessions allowed to view analytics
+    let analytics_routes = Router::new()
+        .route("/api/analytics/summary", get(analytics_summary_handler))
+        .route("/api/analytics/burndown", get(analytics_burndown_handler))
+        .route("/api/analytics/throughput", get(analytics_throughput_handler))
+        .route("/api/analytics/workload", get(analytics_workload_handler))
+        .route("/api/analytics/health-score", get(analytics_health_score_handler))
+        .route("/api/sprints/:name/forecast", get(sprint_forecast_handler))
+        .route_layer(from_fn_with_state((db_pool.clone(), Permission::ViewAnalytics), require_permission))
+        .route_layer(from_fn_with_state((db_pool.clone(), Feature::Analytics), require_feature));
+
+    // Webhook subscriptions, available only while the webhooks feature is on
+    let webhook_routes = Router::new()
+        .route("/api/webhooks", get(webhooks_list_handler).post(webhooks_create_handler))
+        .route("/api/webhooks/deliveries", get(webhook_deliveries_handler))
+        .route("/api/webhooks/:id", delete(webhooks_delete_handler))
+        .route_layer(from_fn_with_state((db_pool.clone(), Feature::Webhooks), require_feature));
+
+    // Imports are checked against `limits.max_import_bytes` by the handlers,
+    // so the body limit only has to cover the largest value it may be set to
+    let import_body_limit = DefaultBodyLimit::max(2 * models::MAX_IMPORT_BYTES_CEILING as usize);
+
     // Build our application with routes
     let app = Router::new()
         // Health endpoint
         .route("/api/health", get(health_handler))
+        .route("/api/ready", get(ready_handler))
         
         // Authentication endpoints
         .route("/api/auth/verify", post(auth_verify_handler))
@@ -39,14 +93,51 @@ async fn main() {
         .route("/api/tasks", get(tasks_list_handler).post(tasks_create_handler))
         .route("/api/tasks/:id", get(tasks_get_handler).put(tasks_update_handler).delete(tasks_delete_handler))
         .route("/api/tasks/bulk", post(tasks_bulk_handler))
+        .route("/api/tasks/order", post(tasks_order_handler))
+        .route("/api/tasks/grouped", get(tasks_grouped_handler))
+        .route("/api/tasks/reassign", post(tasks_reassign_handler))
+        .route("/api/tasks/import-url", post(import_url_handler).layer(import_body_limit))
+        .route("/api/tasks/:id/revisions", get(tasks_revisions_handler))
+        .route("/api/tasks/:id/revisions/:rev", get(tasks_revision_get_handler))
+        .route("/api/tasks/:id/revisions/:rev/restore", post(tasks_revision_restore_handler))
+        .route("/api/tasks/:id/status", patch(tasks_status_patch_handler))
+        .route("/api/tasks/:id/priority", patch(tasks_priority_patch_handler))
+        .route("/api/tasks/:id/assignee", patch(tasks_assignee_patch_handler))
+        .route("/api/tasks/:id/criteria/:item_id", patch(tasks_criterion_patch_handler))
+        .route("/api/tasks/:id/technical/:item_id", patch(tasks_technical_patch_handler))
+        .route("/api/tasks/:id/archive", post(tasks_archive_handler))
+        .route("/api/tasks/:id/unarchive", post(tasks_unarchive_handler))
+        .route("/api/tasks/:id/blockers", get(tasks_blockers_handler))
+        .route("/api/tasks/:id/blocking", get(tasks_blocking_handler))
+        .route("/api/tasks/:id/thumbnail", get(tasks_thumbnail_get_handler).post(tasks_thumbnail_upload_handler))
+        .route("/api/tasks/:id/watch", post(tasks_watch_handler).delete(tasks_unwatch_handler))
+        .route("/api/me/recent-tasks", get(recent_tasks_handler))
+        .route("/api/tasks/:id/watchers", get(tasks_watchers_handler))
+        .route("/api/tasks/:id/export", get(tasks_export_handler))
+        
+        // Label endpoints
+        .route("/api/labels/:name/assign", post(labels_assign_handler))
+        .route("/api/labels/:name/unassign", post(labels_unassign_handler))
+        
+        // Epic endpoints
+        .route("/api/epics", get(epics_list_handler))
+        .route("/api/epics/:name", put(epics_update_handler))
+        .route("/api/epics/:name/tasks", get(epics_tasks_handler))
         
         // Import/Export endpoints
-        .route("/api/import/markdown", post(import_markdown_handler))
+        .route("/api/import/markdown", post(import_markdown_handler).layer(import_body_limit))
+        .route("/api/import/csv", post(import_csv_handler).layer(import_body_limit))
+        .route("/api/diff/markdown", post(diff_markdown_handler))
         .route("/api/export/markdown", get(export_markdown_handler))
+        .route("/api/export/ical", get(export_ical_handler))
+        .route("/api/export/bundle", get(export_bundle_handler))
+        .route("/api/export/stream", get(export_stream_handler))
         
         // Analytics endpoints
-        .route("/api/analytics/summary", get(analytics_summary_handler))
-        .route("/api/analytics/burndown", get(analytics_burndown_handler))
+        .merge(analytics_routes)
+        
+        // Webhook endpoints
+        .merge(webhook_routes)
         
         // User management endpoints
         .route("/api/users", get(users_list_handler).post(users_create_handler))
@@ -54,6 +145,11 @@ async fn main() {
         
         // Activity endpoint
         .route("/api/activity", get(activity_handler))
+        .route("/api/admin/activity/purge", post(activity_purge_handler))
+        .route("/api/admin/activity/export", get(activity_export_handler))
+        .route("/api/admin/analytics/rebuild", post(analytics_rebuild_handler))
+        .route("/api/admin/maintenance", post(maintenance_handler))
+        .route("/api/admin/age-escalation", post(age_escalation_handler))
         
         // Configuration endpoints
         .route("/api/config", get(config_get_handler).put(config_update_handler))
@@ -64,14 +160,20 @@ async fn main() {
                 .layer(
                     CorsLayer::new()
                         .allow_origin(Any)
-                        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-                        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+                        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+                        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, HeaderName::from_static("accept-version")])
+                        .expose_headers([HeaderName::from_static("x-session-token")])
                 )
+                .layer(from_fn(refresh_session))
+                .layer(from_fn_with_state(query_timeout(), read_timeout))
         )
-        .with_state(db_pool);
+        .with_state(db_pool.clone());
+
+    // Task ids in paths have to be resolved before the router matches them
+    let app = from_fn_with_state(db_pool, canonical_task_ids).layer(app);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3001").await.unwrap();
     tracing::info!("Server running on http://0.0.0.0:3001");
     
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, ServiceExt::<Request>::into_make_service(app)).await.unwrap();
 }
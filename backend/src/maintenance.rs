@@ -0,0 +1,76 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::database::{self, DbPool};
+use crate::models::MaintenanceResult;
+use crate::validation::ValidationError;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+// Clears RUNNING however the run ends
+struct RunGuard;
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// Recreates missing indexes, then runs `VACUUM` and `ANALYZE`. Only one run
+/// happens at a time; a second caller gets `MAINTENANCE_IN_PROGRESS`.
+pub async fn run(pool: &DbPool) -> Result<MaintenanceResult> {
+    if RUNNING.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_err() {
+        return Err(ValidationError::new(
+            StatusCode::CONFLICT,
+            "MAINTENANCE_IN_PROGRESS",
+            "Database maintenance is already running",
+        ).into());
+    }
+    let _guard = RunGuard;
+
+    let size_before = database::database_size(pool).await?;
+    let rebuilt_indexes = database::create_indexes(pool).await?;
+    database::vacuum_and_analyze(pool).await?;
+    let size_after = database::database_size(pool).await?;
+
+    Ok(MaintenanceResult {
+        size_before,
+        size_after,
+        reclaimed_bytes: (size_before - size_after).max(0),
+        rebuilt_indexes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+
+    #[tokio::test]
+    async fn maintenance_restores_missing_indexes() {
+        let db = db().await;
+        sqlx::query("DROP INDEX idx_tasks_epic").execute(&*db).await.unwrap();
+
+        let result = run(&db).await.unwrap();
+        assert_eq!(result.rebuilt_indexes, ["idx_tasks_epic"]);
+        assert!(result.reclaimed_bytes >= 0);
+        let indexes: Vec<String> = sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'index'")
+            .fetch_all(&*db)
+            .await
+            .unwrap();
+        assert!(indexes.iter().any(|index| index == "idx_tasks_epic"));
+        assert!(run(&db).await.unwrap().rebuilt_indexes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn maintenance_runs_one_at_a_time() {
+        let db = db().await;
+        RUNNING.store(true, Ordering::Release);
+        let error = run(&db).await.unwrap_err();
+        RUNNING.store(false, Ordering::Release);
+
+        assert_eq!(error.downcast_ref::<ValidationError>().unwrap().code, "MAINTENANCE_IN_PROGRESS");
+        assert!(run(&db).await.is_ok());
+    }
+}
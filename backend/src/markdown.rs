@@ -0,0 +1,355 @@
+use std::collections::HashSet;
+
+use crate::models::*;
+
+/// Board format version the export writes. Boards declaring another version
+/// are still imported, with a warning.
+pub const BOARD_FORMAT_VERSION: &str = "1";
+
+/// The `---`-delimited YAML block at the top of an exported board
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    pub workspace: Option<String>,
+    pub exported_at: Option<String>,
+    pub version: Option<String>,
+}
+
+impl FrontMatter {
+    pub fn new(workspace: &str) -> Self {
+        Self {
+            workspace: Some(workspace.to_string()),
+            exported_at: Some(chrono::Utc::now().to_rfc3339()),
+            version: Some(BOARD_FORMAT_VERSION.to_string()),
+        }
+    }
+
+    // Values are written as JSON strings, which YAML reads as quoted scalars
+    pub fn render(&self) -> String {
+        let fields = [
+            ("workspace", &self.workspace),
+            ("exported_at", &self.exported_at),
+            ("version", &self.version),
+        ];
+
+        let mut block = String::from("---\n");
+        for (key, value) in fields {
+            if let Some(value) = value {
+                block.push_str(&format!("{}: {}\n", key, serde_json::Value::from(value.as_str())));
+            }
+        }
+        block.push_str("---\n\n");
+        block
+    }
+
+    /// A warning for boards written in a format version other than this one
+    pub fn version_warning(&self) -> Option<String> {
+        let version = self.version.as_deref()?;
+        (version != BOARD_FORMAT_VERSION).then(|| format!(
+            "Board declares format version {} but this server reads version {}; some fields may not import as expected",
+            version, BOARD_FORMAT_VERSION,
+        ))
+    }
+}
+
+/// Parses a board along with its front matter, if it has any
+pub fn parse_document(markdown: &str) -> (FrontMatter, Vec<ParsedTask>) {
+    let (front_matter, board) = split_front_matter(markdown);
+    (front_matter, parse_board(board))
+}
+
+// Front matter only counts when `---` is the board's first line and the block
+// is closed; unknown keys are ignored
+fn split_front_matter(markdown: &str) -> (FrontMatter, &str) {
+    let mut front_matter = FrontMatter::default();
+    let Some(rest) = markdown.trim_start_matches('\u{feff}').strip_prefix("---") else {
+        return (front_matter, markdown);
+    };
+    let Some(rest) = rest.strip_prefix("\r\n").or_else(|| rest.strip_prefix('\n')) else {
+        return (front_matter, markdown);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim();
+        if line == "---" {
+            return (front_matter, &rest[offset..]);
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = Some(yaml_scalar(value.trim())).filter(|value| !value.is_empty());
+        match key.trim() {
+            "workspace" => front_matter.workspace = value,
+            "exported_at" => front_matter.exported_at = value,
+            "version" => front_matter.version = value,
+            _ => {}
+        }
+    }
+
+    (FrontMatter::default(), markdown)
+}
+
+// Plain, 'single' or "double" quoted
+fn yaml_scalar(value: &str) -> String {
+    if value.starts_with('"') {
+        if let Ok(value) = serde_json::from_str::<String>(value) {
+            return value;
+        }
+    }
+    value.strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+        .map(|value| value.replace("''", "'"))
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// A task card read from a Jira-style markdown board
+#[derive(Debug, Clone)]
+pub struct ParsedTask {
+    pub id: String,
+    pub title: String,
+    pub epic: Option<String>,
+    pub r#type: TaskType,
+    pub priority: Priority,
+    pub status: TaskStatus,
+    pub story_points: Option<f64>,
+    pub sprint: Option<String>,
+    pub assignee: Option<String>,
+    pub description: String,
+    pub acceptance_criteria: Vec<ChecklistItem>,
+    pub technical_tasks: Vec<ChecklistItem>,
+    pub dependencies: Vec<String>,
+    pub blocks: Vec<String>,
+    /// Fields the board actually gave, named as in `csv::FIELDS` (plus the
+    /// checklists); the rest hold defaults. `id` and `title` always count.
+    pub provided: HashSet<&'static str>,
+}
+
+impl ParsedTask {
+    pub fn new(id: &str, title: &str, epic: Option<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            epic,
+            r#type: TaskType::Task,
+            priority: Priority::Medium,
+            status: TaskStatus::Todo,
+            story_points: None,
+            sprint: None,
+            assignee: None,
+            description: String::new(),
+            acceptance_criteria: vec![],
+            technical_tasks: vec![],
+            dependencies: vec![],
+            blocks: vec![],
+            provided: HashSet::from(["id", "title"]),
+        }
+    }
+
+    pub fn to_create_request(&self) -> CreateTaskRequest {
+        CreateTaskRequest {
+            title: self.title.clone(),
+            r#type: self.r#type.clone(),
+            priority: self.priority.clone(),
+            status: self.status.clone(),
+            story_points: self.story_points,
+            sprint: self.sprint.clone(),
+            epic: self.epic.clone(),
+            description: self.description.clone(),
+            acceptance_criteria: self.acceptance_criteria.clone(),
+            technical_tasks: self.technical_tasks.clone(),
+            dependencies: self.dependencies.clone(),
+            blocks: self.blocks.clone(),
+            assignee: self.assignee.clone(),
+            is_favorite: None,
+            thumbnail: None,
+            due_date: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    AcceptanceCriteria,
+    TechnicalTasks,
+}
+
+/// How many tasks `parse_board` would find, without parsing them: a cheap
+/// check before committing to a large import
+pub fn count_tasks(markdown: &str) -> usize {
+    markdown.lines()
+        .filter_map(|line| line.trim().strip_prefix("### "))
+        .filter(|heading| heading.contains(':'))
+        .count()
+}
+
+/// Parses a markdown board in the same format as the frontend parser and the
+/// export endpoint. Cards under `## Epic: <name>` belong to that epic; cards
+/// under any other H2 (e.g. "Miscellaneous Tasks") or before the first H2 have none.
+pub fn parse_board(markdown: &str) -> Vec<ParsedTask> {
+    let mut tasks = Vec::new();
+    let mut current_epic: Option<String> = None;
+    // Whether an H2 has been seen; cards under "Miscellaneous Tasks" explicitly have no epic
+    let mut in_section = false;
+    let mut current: Option<ParsedTask> = None;
+    let mut section = Section::None;
+
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(heading) = line.strip_prefix("## ") {
+            tasks.extend(current.take());
+            current_epic = heading.strip_prefix("Epic:").map(|epic| epic.trim().to_string());
+            in_section = true;
+            section = Section::None;
+            continue;
+        }
+
+        if let Some(heading) = line.strip_prefix("### ") {
+            tasks.extend(current.take());
+            if let Some((id, title)) = heading.split_once(':') {
+                let mut task = ParsedTask::new(id.trim(), title.trim(), current_epic.clone());
+                if in_section {
+                    task.provided.insert("epic");
+                }
+                current = Some(task);
+            }
+            section = Section::None;
+            continue;
+        }
+
+        let Some(task) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some((field, value)) = parse_metadata(line) {
+            let value = value.trim();
+            let field = field.to_lowercase().replace(' ', "");
+            match field.as_str() {
+                "type" => task.r#type = parse_task_type(value),
+                "priority" => task.priority = parse_priority(value),
+                "status" => task.status = parse_status(value),
+                "storypoints" => task.story_points = value.parse().ok().filter(|points: &f64| points.is_finite()),
+                "sprint" => task.sprint = non_empty(value),
+                "assignee" => task.assignee = non_empty(value),
+                "description" => task.description = value.to_string(),
+                "dependencies" => task.dependencies = parse_id_list(value),
+                "blocks" => task.blocks = parse_id_list(value),
+                "acceptancecriteria" => section = Section::AcceptanceCriteria,
+                "technicaltasks" => section = Section::TechnicalTasks,
+                _ => {}
+            }
+            task.provided.extend(provided_field(&field));
+            continue;
+        }
+
+        if let Some(item) = parse_checklist_item(line) {
+            match section {
+                Section::AcceptanceCriteria => task.acceptance_criteria.push(item),
+                Section::TechnicalTasks => task.technical_tasks.push(item),
+                Section::None => {}
+            }
+            continue;
+        }
+
+        if line.starts_with("---") {
+            section = Section::None;
+        }
+    }
+
+    tasks.extend(current);
+    tasks
+}
+
+// Metadata label (lower-cased, without spaces) -> the field it fills
+fn provided_field(label: &str) -> Option<&'static str> {
+    Some(match label {
+        "type" => "type",
+        "priority" => "priority",
+        "status" => "status",
+        "storypoints" => "story_points",
+        "sprint" => "sprint",
+        "assignee" => "assignee",
+        "description" => "description",
+        "dependencies" => "dependencies",
+        "blocks" => "blocks",
+        "acceptancecriteria" => "acceptance_criteria",
+        "technicaltasks" => "technical_tasks",
+        _ => return None,
+    })
+}
+
+// "**Field**: value"
+fn parse_metadata(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("**")?;
+    let (field, value) = rest.split_once("**:")?;
+    Some((field, value))
+}
+
+// "- [ ] text" / "- [x] text"
+fn parse_checklist_item(line: &str) -> Option<ChecklistItem> {
+    let rest = line.strip_prefix("- [")?;
+    let (mark, text) = rest.split_once("] ")?;
+    let completed = match mark {
+        "x" | "X" => true,
+        " " => false,
+        _ => return None,
+    };
+
+    Some(ChecklistItem {
+        id: None,
+        text: text.trim().to_string(),
+        completed,
+        source: None,
+    })
+}
+
+fn parse_id_list(value: &str) -> Vec<String> {
+    if value.eq_ignore_ascii_case("none") {
+        return vec![];
+    }
+
+    value.split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+pub fn non_empty(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+pub fn parse_task_type(value: &str) -> TaskType {
+    match value.to_lowercase().as_str() {
+        "epic" => TaskType::Epic,
+        "story" => TaskType::Story,
+        "bug" => TaskType::Bug,
+        _ => TaskType::Task,
+    }
+}
+
+// Like statuses, unrecognised priorities may be custom ones
+pub fn parse_priority(value: &str) -> Priority {
+    if value.trim().is_empty() {
+        return Priority::Medium;
+    }
+    Priority::parse(value)
+}
+
+// Unlike the other fields, a status isn't defaulted when unrecognised; it
+// may be one of the workspace's custom statuses
+pub fn parse_status(value: &str) -> TaskStatus {
+    if value.trim().is_empty() {
+        return TaskStatus::Todo;
+    }
+    TaskStatus::parse(value)
+}
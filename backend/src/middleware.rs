@@ -0,0 +1,332 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, Method, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+
+use std::time::Duration;
+
+use crate::auth::{extract_auth_claims, AuthService, SESSION_TOKEN_HEADER};
+use crate::database::{self, DbPool};
+use crate::models::*;
+
+/// Optional workspace features that can be switched off in `workspace_config`
+#[derive(Debug, Clone, Copy)]
+pub enum Feature {
+    Analytics,
+    Webhooks,
+}
+
+impl Feature {
+    fn name(&self) -> &'static str {
+        match self {
+            Feature::Analytics => "analytics",
+            Feature::Webhooks => "webhooks",
+        }
+    }
+
+    fn is_enabled(&self, features: &WorkspaceFeatures) -> bool {
+        match self {
+            Feature::Analytics => features.analytics,
+            Feature::Webhooks => features.webhooks,
+        }
+    }
+}
+
+// Rejects requests to routes whose backing feature is disabled
+pub async fn require_feature(
+    State((pool, feature)): State<(DbPool, Feature)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = match database::get_workspace_config(&pool).await {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("Failed to get workspace config: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    if !feature.is_enabled(&config.features) {
+        let body = ApiResponse::<()>::error(
+            "FEATURE_DISABLED".to_string(),
+            format!("The {} feature is disabled for this workspace", feature.name()),
+        );
+        return (StatusCode::FORBIDDEN, Json(body)).into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Permissions a session needs for a group of routes
+#[derive(Debug, Clone, Copy)]
+pub enum Permission {
+    ViewAnalytics,
+}
+
+impl Permission {
+    fn name(&self) -> &'static str {
+        match self {
+            Permission::ViewAnalytics => "view_analytics",
+        }
+    }
+
+    /// Whether a session holding `permissions` has this one, either outright
+    /// or through a permission the workspace grants it to
+    pub fn is_granted(&self, permissions: &[String], features: &WorkspaceFeatures) -> bool {
+        let granted_by = match self {
+            Permission::ViewAnalytics => &features.analytics_permissions,
+        };
+        permissions.iter().any(|permission| permission == self.name() || granted_by.contains(permission))
+    }
+}
+
+// Rejects requests to routes the session lacks the permission for
+pub async fn require_permission(
+    State((pool, permission)): State<(DbPool, Permission)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorization = request.headers().get("authorization").and_then(|value| value.to_str().ok());
+    let Some(claims) = extract_auth_claims(authorization).ok().flatten() else {
+        let body = ApiResponse::<()>::error("UNAUTHORIZED".to_string(), "Authentication required".to_string());
+        return (StatusCode::UNAUTHORIZED, Json(body)).into_response();
+    };
+
+    let config = match database::get_workspace_config(&pool).await {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("Failed to get workspace config: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    if !permission.is_granted(&claims.permissions, &config.features) {
+        let body = ApiResponse::<()>::error(
+            "FORBIDDEN".to_string(),
+            format!("This requires the {} permission", permission.name()),
+        );
+        return (StatusCode::FORBIDDEN, Json(body)).into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Sends a refreshed session token back with every authenticated request, so
+/// clients that swap it in stay within the idle timeout while they're active
+pub async fn refresh_session(request: Request, next: Next) -> Response {
+    let token = request.headers().get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+
+    let mut response = next.run(request).await;
+
+    let refreshed = token.and_then(|token| AuthService::new().refresh_token(&token));
+    if let Some(value) = refreshed.and_then(|token| token.parse().ok()) {
+        response.headers_mut().insert(SESSION_TOKEN_HEADER, value);
+    }
+    response
+}
+
+/// How long a read may run before it is abandoned, from `QUERY_TIMEOUT_MS`
+pub fn query_timeout() -> Duration {
+    let millis = std::env::var("QUERY_TIMEOUT_MS").ok()
+        .and_then(|millis| millis.trim().parse::<u64>().ok())
+        .filter(|&millis| millis > 0)
+        .unwrap_or(30_000);
+    Duration::from_millis(millis)
+}
+
+/// Answers `QUERY_TIMEOUT` (504) when a GET request outlives `timeout`
+/// (see `query_timeout`), so one pathological query can't hold a request
+/// open forever. Dropping the handler abandons its query. Writes are left
+/// to finish.
+pub async fn read_timeout(State(timeout): State<Duration>, request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            let body = ApiResponse::<()>::error(
+                "QUERY_TIMEOUT".to_string(),
+                format!("The request did not finish within {} ms", timeout.as_millis()),
+            );
+            (StatusCode::GATEWAY_TIMEOUT, Json(body)).into_response()
+        }
+    }
+}
+
+// `?api_version=` wins over the `Accept-Version` header; neither means v1
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let from_query = parts.uri.query().and_then(|query| {
+            query.split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "api_version")
+                .map(|(_, value)| value.to_string())
+        });
+        let requested = from_query.or_else(|| {
+            parts.headers.get("accept-version")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        });
+
+        let Some(requested) = requested else {
+            return Ok(ApiVersion::default());
+        };
+        ApiVersion::parse(&requested).ok_or_else(|| {
+            let body = ApiResponse::<()>::error(
+                "UNSUPPORTED_API_VERSION".to_string(),
+                format!("API version {} is not supported; use one of {}", requested, ApiVersion::SUPPORTED.join(", ")),
+            );
+            (StatusCode::BAD_REQUEST, Json(body)).into_response()
+        })
+    }
+}
+
+// Fixed routes under /api/tasks/ that aren't task ids
+const TASK_ROUTES: &[&str] = &["bulk", "reassign", "import-url", "order", "grouped"];
+
+/// Rewrites `/api/tasks/<id>/...` to use the task's stored id when the
+/// workspace has `case_insensitive_ids` on, so every task route (not just
+/// lookups) finds `PROJ-1` from `proj-1`. Runs before routing.
+pub async fn canonical_task_ids(State(pool): State<DbPool>, mut request: Request, next: Next) -> Response {
+    if let Some(uri) = canonical_task_uri(&pool, request.uri()).await {
+        *request.uri_mut() = uri;
+    }
+
+    next.run(request).await
+}
+
+async fn canonical_task_uri(pool: &DbPool, uri: &Uri) -> Option<Uri> {
+    let rest = uri.path().strip_prefix("/api/tasks/")?;
+    let (id, tail) = rest.split_once('/').map_or((rest, ""), |(id, tail)| (id, tail));
+
+    // Encoded ids are left alone rather than decoded and re-encoded
+    if id.is_empty() || id.contains('%') || TASK_ROUTES.contains(&id) {
+        return None;
+    }
+
+    let canonical = match database::canonical_task_id(pool, id).await {
+        Ok(Some(canonical)) if canonical != id && !canonical.contains(['/', '?', '#', '%']) => canonical,
+        Ok(_) => return None,
+        Err(e) => {
+            tracing::error!("Failed to resolve task id {}: {}", id, e);
+            return None;
+        }
+    };
+
+    let mut path = format!("/api/tasks/{}", canonical);
+    if !tail.is_empty() || rest.contains('/') {
+        path.push('/');
+        path.push_str(tail);
+    }
+    if let Some(query) = uri.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+
+    path.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::{analytics_summary_handler, tasks_list_handler};
+    use crate::test_support::*;
+    use axum::{body::Body, http::HeaderMap, middleware::from_fn_with_state, routing::get, Router};
+    use tower::Service;
+
+    // The analytics and task list routes, gated as in main
+    fn app(pool: &DbPool) -> Router {
+        let analytics = Router::new()
+            .route("/api/analytics/summary", get(analytics_summary_handler))
+            .route_layer(from_fn_with_state((pool.clone(), Permission::ViewAnalytics), require_permission))
+            .route_layer(from_fn_with_state((pool.clone(), Feature::Analytics), require_feature));
+
+        Router::new()
+            .route("/api/tasks", get(tasks_list_handler))
+            .merge(analytics)
+            .with_state(pool.clone())
+    }
+
+    async fn status_of(pool: &DbPool, uri: &str, headers: HeaderMap) -> StatusCode {
+        let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        *request.headers_mut() = headers;
+        // Router is always ready, so it can be called without polling first
+        app(pool).call(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn disabled_features_reject_their_routes_only() {
+        let db = db().await;
+        assert_eq!(status_of(&db, "/api/analytics/summary", admin_headers()).await, StatusCode::OK);
+
+        update_config(&db, |config| config.features.analytics = false).await;
+        assert_eq!(status_of(&db, "/api/analytics/summary", admin_headers()).await, StatusCode::FORBIDDEN);
+        assert_eq!(status_of(&db, "/api/tasks", admin_headers()).await, StatusCode::OK);
+    }
+
+    async fn task_list(pool: &DbPool, uri: &str, headers: HeaderMap) -> serde_json::Value {
+        let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        *request.headers_mut() = headers;
+        let response = app(pool).call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()["data"].take()
+    }
+
+    #[tokio::test]
+    async fn only_v2_task_lists_carry_a_total_count() {
+        let db = db().await;
+        add_task(&db, new_task("Counted")).await;
+        let mut v2 = HeaderMap::new();
+        v2.insert("accept-version", "v2".parse().unwrap());
+
+        assert!(task_list(&db, "/api/tasks", HeaderMap::new()).await.get("totalCount").is_none());
+        assert_eq!(task_list(&db, "/api/tasks", v2.clone()).await["totalCount"], 1);
+        assert_eq!(task_list(&db, "/api/tasks?api_version=2", HeaderMap::new()).await["totalCount"], 1);
+        // The query parameter wins over the header
+        assert!(task_list(&db, "/api/tasks?api_version=1", v2).await.get("totalCount").is_none());
+        assert_eq!(status_of(&db, "/api/tasks?api_version=3", HeaderMap::new()).await, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn missing_tokens_and_missing_permissions_get_different_statuses() {
+        let db = db().await;
+        assert_eq!(status_of(&db, "/api/analytics/summary", HeaderMap::new()).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status_of(&db, "/api/analytics/summary", viewer_headers()).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn slow_reads_time_out_but_writes_finish() {
+        let slow = || async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            StatusCode::OK
+        };
+        let mut app = Router::new()
+            .route("/slow", get(slow).post(slow))
+            .route("/fast", get(|| async { StatusCode::OK }))
+            .layer(from_fn_with_state(Duration::from_millis(50), read_timeout));
+
+        let mut status_of = |method: Method, uri: &str| {
+            let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+            app.call(request)
+        };
+        let timed_out = status_of(Method::GET, "/slow").await.unwrap();
+        assert_eq!(timed_out.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body = axum::body::to_bytes(timed_out.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("QUERY_TIMEOUT"));
+
+        assert_eq!(status_of(Method::GET, "/fast").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(status_of(Method::POST, "/slow").await.unwrap().status(), StatusCode::OK);
+    }
+}
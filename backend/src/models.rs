@@ -16,6 +16,8 @@ pub struct ApiResponse<T> {
 pub struct ApiError {
     pub code: String,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 impl<T> ApiResponse<T> {
@@ -31,7 +33,15 @@ impl<T> ApiResponse<T> {
         Self {
             success: false,
             data: None,
-            error: Some(ApiError { code, message }),
+            error: Some(ApiError { code, message, details: None }),
+        }
+    }
+
+    pub fn error_with_details(code: String, message: String, details: serde_json::Value) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(ApiError { code, message, details: Some(details) }),
         }
     }
 }
@@ -46,24 +56,115 @@ pub enum TaskType {
     Bug,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, sqlx::Type)]
-#[sqlx(type_name = "TEXT", rename_all = "PascalCase")]
+/// A task's priority. The built-in priorities are always recognised;
+/// workspaces can define their own ordered scale through `available_priorities`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Priority {
     Critical,
     High,
     Medium,
     Low,
+    Custom(String),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, sqlx::Type)]
-#[sqlx(type_name = "TEXT", rename_all = "PascalCase")]
+impl Priority {
+    pub const BUILT_IN: [Priority; 4] = [Priority::Critical, Priority::High, Priority::Medium, Priority::Low];
+
+    /// Reads a built-in priority ignoring case; anything else is a custom priority
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value.to_lowercase().as_str() {
+            "critical" => Priority::Critical,
+            "high" => Priority::High,
+            "medium" => Priority::Medium,
+            "low" => Priority::Low,
+            _ => Priority::Custom(value.to_string()),
+        }
+    }
+
+    /// The name stored in the database and used in JSON
+    pub fn name(&self) -> &str {
+        match self {
+            Priority::Critical => "Critical",
+            Priority::High => "High",
+            Priority::Medium => "Medium",
+            Priority::Low => "Low",
+            Priority::Custom(name) => name,
+        }
+    }
+}
+
+impl Serialize for Priority {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Priority {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Priority::parse(&String::deserialize(deserializer)?))
+    }
+}
+
+/// A task's workflow status. The built-in statuses are always recognised;
+/// workspaces can add their own (e.g. "Blocked") through `available_statuses`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TaskStatus {
     Todo,
-    #[serde(rename = "In Progress")]
     InProgress,
-    #[serde(rename = "In Review")]
     InReview,
     Done,
+    Custom(String),
+}
+
+impl TaskStatus {
+    pub const BUILT_IN: [TaskStatus; 4] = [TaskStatus::Todo, TaskStatus::InProgress, TaskStatus::InReview, TaskStatus::Done];
+
+    /// Reads either form of a built-in status, ignoring case and spaces
+    /// ("In Progress", "inprogress"); anything else is a custom status
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value.to_lowercase().replace(' ', "").as_str() {
+            "todo" => TaskStatus::Todo,
+            "inprogress" => TaskStatus::InProgress,
+            "inreview" => TaskStatus::InReview,
+            "done" => TaskStatus::Done,
+            _ => TaskStatus::Custom(value.to_string()),
+        }
+    }
+
+    /// The name stored in the database and used for WIP limits and activity
+    /// details ("InProgress")
+    pub fn stored_name(&self) -> &str {
+        match self {
+            TaskStatus::Todo => "Todo",
+            TaskStatus::InProgress => "InProgress",
+            TaskStatus::InReview => "InReview",
+            TaskStatus::Done => "Done",
+            TaskStatus::Custom(name) => name,
+        }
+    }
+
+    /// The name used in JSON ("In Progress")
+    pub fn label(&self) -> &str {
+        match self {
+            TaskStatus::InProgress => "In Progress",
+            TaskStatus::InReview => "In Review",
+            status => status.stored_name(),
+        }
+    }
+}
+
+impl Serialize for TaskStatus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.label())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(TaskStatus::parse(&String::deserialize(deserializer)?))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -71,9 +172,15 @@ pub struct ChecklistItem {
     pub id: Option<String>,
     pub text: String,
     pub completed: bool,
+    /// Who wrote the item, one of `CHECKLIST_SOURCES`; stored as `human` when unset
+    #[serde(default)]
+    pub source: Option<String>,
 }
 
+pub const CHECKLIST_SOURCES: &[&str] = &["human", "ai"];
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Task {
     pub id: String,
     pub title: String,
@@ -81,28 +188,39 @@ pub struct Task {
     pub r#type: TaskType,
     pub priority: Priority,
     pub status: TaskStatus,
-    #[serde(skip_serializing_if = "Option::is_none", rename = "storyPoints")]
-    pub story_points: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub story_points: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sprint: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub epic: Option<String>,
     pub description: String,
-    #[serde(rename = "acceptanceCriteria")]
     pub acceptance_criteria: Vec<ChecklistItem>,
-    #[serde(rename = "technicalTasks")]
     pub technical_tasks: Vec<ChecklistItem>,
     pub dependencies: Vec<String>,
     pub blocks: Vec<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub assignee: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none", rename = "isFavorite")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_favorite: Option<bool>,
+    /// Whether any dependency isn't Done yet; only set while
+    /// `workflow.flag_blocked_tasks` is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_blocked: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thumbnail: Option<String>,
-    #[serde(rename = "createdAt")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<DateTime<Utc>>,
+    /// Removed from the board without being deleted or marked Done
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
     pub created_at: DateTime<Utc>,
-    #[serde(rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -113,13 +231,17 @@ pub struct TaskRow {
     pub task_type: String,
     pub priority: String,
     pub status: String,
-    pub story_points: Option<i32>,
+    pub story_points: Option<f64>,
     pub sprint: Option<String>,
     pub epic: Option<String>,
     pub description: String,
     pub assignee: Option<String>,
     pub is_favorite: Option<bool>,
     pub thumbnail: Option<String>,
+    pub due_date: Option<String>,
+    pub archived: bool,
+    pub created_by: Option<String>,
+    pub updated_by: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -130,7 +252,7 @@ pub struct CreateTaskRequest {
     pub r#type: TaskType,
     pub priority: Priority,
     pub status: TaskStatus,
-    pub story_points: Option<i32>,
+    pub story_points: Option<f64>,
     pub sprint: Option<String>,
     pub epic: Option<String>,
     pub description: String,
@@ -141,15 +263,16 @@ pub struct CreateTaskRequest {
     pub assignee: Option<String>,
     pub is_favorite: Option<bool>,
     pub thumbnail: Option<String>,
+    pub due_date: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default)]
 pub struct UpdateTaskRequest {
     pub title: Option<String>,
     pub r#type: Option<TaskType>,
     pub priority: Option<Priority>,
     pub status: Option<TaskStatus>,
-    pub story_points: Option<Option<i32>>,
+    pub story_points: Option<Option<f64>>,
     pub sprint: Option<Option<String>>,
     pub epic: Option<Option<String>>,
     pub description: Option<String>,
@@ -160,6 +283,144 @@ pub struct UpdateTaskRequest {
     pub assignee: Option<Option<String>>,
     pub is_favorite: Option<Option<bool>>,
     pub thumbnail: Option<Option<String>>,
+    pub due_date: Option<Option<DateTime<Utc>>>,
+}
+
+// Bodies of the single-field PATCH endpoints
+#[derive(Debug, Deserialize)]
+pub struct StatusPatch {
+    pub status: TaskStatus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PriorityPatch {
+    pub priority: Priority,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssigneePatch {
+    /// null unassigns the task
+    pub assignee: Option<String>,
+}
+
+/// Body of the checklist item PATCH endpoints; fields left out are unchanged
+#[derive(Debug, Deserialize)]
+pub struct ChecklistItemPatch {
+    pub text: Option<String>,
+    pub completed: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRevision {
+    pub task_id: String,
+    pub revision: u32,
+    pub snapshot: Task,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Task> for UpdateTaskRequest {
+    // A full update that puts every field back to the given task's values
+    fn from(task: Task) -> Self {
+        Self {
+            title: Some(task.title),
+            r#type: Some(task.r#type),
+            priority: Some(task.priority),
+            status: Some(task.status),
+            story_points: Some(task.story_points),
+            sprint: Some(task.sprint),
+            epic: Some(task.epic),
+            description: Some(task.description),
+            acceptance_criteria: Some(task.acceptance_criteria),
+            technical_tasks: Some(task.technical_tasks),
+            dependencies: Some(task.dependencies),
+            blocks: Some(task.blocks),
+            assignee: Some(task.assignee),
+            is_favorite: Some(task.is_favorite),
+            thumbnail: Some(task.thumbnail),
+            due_date: Some(task.due_date),
+        }
+    }
+}
+
+// Transitive blockers or blocked tasks of a task
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskChain {
+    pub task_id: String,
+    pub tasks: Vec<Task>,
+    /// The relationships loop back to the task itself
+    pub cycle_detected: bool,
+}
+
+// Tasks picked by id or by filter, for POST /api/tasks/order and the
+// label assign/unassign routes
+#[derive(Debug, Deserialize)]
+pub struct TaskSelection {
+    pub task_ids: Option<Vec<String>>,
+    pub filter: Option<TaskQueryParams>,
+}
+
+// Image upload for POST /api/tasks/:id/thumbnail
+#[derive(Debug, Deserialize)]
+pub struct ThumbnailUpload {
+    /// MIME type of the image; may be omitted when `data` is a data: URL
+    pub content_type: Option<String>,
+    /// Base64 image bytes, or a `data:image/...;base64,` URL
+    pub data: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskWatchers {
+    pub task_id: String,
+    pub watchers: Vec<String>,
+}
+
+// Epic taxonomy
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Epic {
+    pub name: String,
+    /// Sprint or milestone the epic is planned into
+    pub milestone: Option<String>,
+    /// Kept equal to `derived_status` while `workflow.auto_epic_status` is on
+    pub status: Option<TaskStatus>,
+    /// What the epic's tasks add up to; None while it has none
+    pub derived_status: Option<TaskStatus>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEpicRequest {
+    /// Null or empty clears the milestone
+    pub milestone: Option<String>,
+}
+
+// An epic with its tasks, from GET /api/epics/:name/tasks
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpicTasks {
+    pub epic: Epic,
+    pub tasks: Vec<Task>,
+    pub rollup: EpicRollup,
+}
+
+/// Done tasks against all tasks of an epic, overall and per sprint
+#[derive(Debug, Serialize)]
+pub struct EpicRollup {
+    pub completed: u32,
+    pub total: u32,
+    pub sprints: Vec<SprintProgress>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SprintProgress {
+    /// None for the epic's tasks that are in no sprint
+    pub sprint: Option<String>,
+    pub completed: u32,
+    pub total: u32,
 }
 
 // Authentication types
@@ -181,6 +442,7 @@ pub struct AuthCredentials {
 pub type AuthConfig = AuthCredentials;
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AuthVerificationResult {
     pub authenticated: bool,
     pub session_token: Option<String>,
@@ -189,6 +451,7 @@ pub struct AuthVerificationResult {
 }
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AuthResponse {
     pub authenticated: bool,
     pub session_token: Option<String>,
@@ -198,6 +461,7 @@ pub struct AuthResponse {
 
 // Workspace types
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WorkspaceInfo {
     pub id: String,
     pub name: String,
@@ -210,6 +474,7 @@ pub struct WorkspaceInfo {
 }
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WorkspaceOwner {
     pub id: String,
     pub username: String,
@@ -217,6 +482,7 @@ pub struct WorkspaceOwner {
 }
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WorkspacePermissions {
     pub can_manage_users: bool,
     pub can_modify_settings: bool,
@@ -235,6 +501,7 @@ pub struct HealthStatus {
 }
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DatabaseStatus {
     pub status: String,
     pub response_time: u64,
@@ -249,28 +516,105 @@ pub struct MemoryStatus {
 
 // Task sync response
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TaskSyncResponse {
     pub tasks: Vec<Task>,
     pub last_sync: DateTime<Utc>,
+    /// Always present from API v2 on; left out in v1
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub total_count: Option<u32>,
     pub has_more: Option<bool>,
 }
 
+/// Response shape a client asked for with `Accept-Version` or `?api_version=`.
+/// v1 is the original envelope; v2 adds `totalCount` to task lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const SUPPORTED: &'static [&'static str] = &["v1", "v2"];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().trim_start_matches('v') {
+            "1" => Some(ApiVersion::V1),
+            "2" => Some(ApiVersion::V2),
+            _ => None,
+        }
+    }
+}
+
 // Query parameters for tasks
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, Clone)]
 pub struct TaskQueryParams {
     pub last_sync: Option<String>,
-    pub epic: Option<String>,
+    /// Repeatable and/or comma-separated; matches any of the given epics
+    #[serde(default)]
+    pub epic: Vec<String>,
     pub status: Option<String>, // Changed from TaskStatus to String for easier filtering
-    pub assignee: Option<String>,
+    /// Repeatable and/or comma-separated; matches any of the given priorities
+    #[serde(default)]
+    pub priority: Vec<String>,
+    /// Repeatable and/or comma-separated; matches tasks of any of these types
+    #[serde(default)]
+    pub r#type: Vec<String>,
+    /// Repeatable and/or comma-separated; matches any of the given assignees
+    #[serde(default)]
+    pub assignee: Vec<String>,
+    /// Repeatable and/or comma-separated; matches tasks with any of the given labels
+    #[serde(default)]
+    pub label: Vec<String>,
+    pub created_by: Option<String>,
+    /// Repeatable and/or comma-separated; leaves out tasks in any of these statuses
+    #[serde(default)]
+    pub exclude_status: Vec<String>,
+    /// Repeatable and/or comma-separated; leaves out tasks of any of these types
+    #[serde(default)]
+    pub exclude_type: Vec<String>,
+    /// Only tasks past their due date that are not Done (or, when false, the rest)
+    pub overdue: Option<bool>,
+    /// Archived tasks are left out unless this is true
+    pub include_archived: Option<bool>,
+    /// Done tasks are left out when this is false
+    pub include_done: Option<bool>,
+    /// Only tasks without any acceptance criteria
+    pub missing_criteria: Option<bool>,
+    /// Only tasks without story points
+    pub missing_estimate: Option<bool>,
+    /// Only tasks that depend on others (or, when false, the rest)
+    pub has_dependencies: Option<bool>,
+    /// Only tasks others depend on (or, when false, the rest)
+    pub is_blocking: Option<bool>,
+    /// Story point bounds, inclusive. Tasks without an estimate are left out
+    /// of a bounded listing unless `include_unestimated` is true
+    pub min_points: Option<f64>,
+    pub max_points: Option<f64>,
+    pub include_unestimated: Option<bool>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
     pub sort: Option<String>,
     pub search: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReassignTasksRequest {
+    pub from: String,
+    pub to: Option<String>,
+}
+
+// Query parameters for task updates and imports
+#[derive(Debug, Deserialize, Default)]
+pub struct ForceParams {
+    /// Bypass workspace limits (WIP limits on update, max_tasks on import); only honoured for admins
+    pub force: Option<bool>,
+}
+
 // User types
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
 pub struct User {
     pub id: String,
     pub username: String,
@@ -291,6 +635,18 @@ pub enum UserRole {
     Viewer,
 }
 
+// Query parameters for GET /api/users
+#[derive(Debug, Deserialize)]
+pub struct UserQueryParams {
+    /// Matches username, display name or email
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub active: Option<bool>,
+    pub sort: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRequest {
     pub username: String,
@@ -302,6 +658,7 @@ pub struct CreateUserRequest {
 
 // Activity types
 #[derive(Debug, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
 pub struct Activity {
     pub id: String,
     pub user_id: String,
@@ -315,14 +672,63 @@ pub struct Activity {
     pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct ActivityDetails {
     pub field: Option<String>,
+    #[serde(alias = "old_value")]
     pub old_value: Option<serde_json::Value>,
+    #[serde(alias = "new_value")]
     pub new_value: Option<serde_json::Value>,
 }
 
+/// The user an activity entry is attributed to
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub user_id: String,
+    pub user_name: String,
+}
+
+impl Actor {
+    pub fn system() -> Self {
+        Self {
+            user_id: "system".to_string(),
+            user_name: "system".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ActivityQueryParams {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+    #[serde(rename = "taskId")]
+    pub task_id: Option<String>,
+    pub action: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+// Query parameters for GET /api/admin/activity/export
+#[derive(Debug, Deserialize)]
+pub struct ActivityExportParams {
+    pub format: Option<ActivityExportFormat>,
+    /// Entries at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Entries before this time
+    pub to: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ActivityResponse {
     pub activities: Vec<Activity>,
     pub total_count: u32,
@@ -331,18 +737,94 @@ pub struct ActivityResponse {
 
 // Analytics types
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AnalyticsSummary {
     pub total_tasks: u32,
     pub tasks_by_status: std::collections::HashMap<String, u32>,
     pub tasks_by_type: std::collections::HashMap<String, u32>,
     pub tasks_by_priority: std::collections::HashMap<String, u32>,
+    /// Story points per status; unestimated tasks count as zero
+    pub points_by_status: std::collections::HashMap<String, f64>,
     pub average_story_points: f32,
     pub completion_rate: f32,
+    pub overdue_tasks: u32,
     pub active_sprints: Vec<String>,
+    pub utilization: Utilization,
+    /// One message per limit whose usage is past `limits.usage_warning_percent`
+    pub warnings: Vec<String>,
     pub last_updated: DateTime<Utc>,
 }
 
+/// Stored tasks and active users against the workspace limits
+#[derive(Debug, Serialize)]
+pub struct Utilization {
+    pub tasks: LimitUsage,
+    pub users: LimitUsage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LimitUsage {
+    pub current: u32,
+    pub limit: u32,
+    pub percent: f64,
+}
+
+impl LimitUsage {
+    pub fn new(current: u32, limit: u32) -> Self {
+        let percent = if limit == 0 { 100.0 } else { current as f64 * 100.0 / limit as f64 };
+        Self { current, limit, percent }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EscalationParams {
+    pub dry_run: Option<bool>,
+}
+
+/// What `POST /api/admin/age-escalation` changed, or would change on a dry run
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscalationResult {
+    pub dry_run: bool,
+    /// Open tasks whose priority was last set before this were considered
+    pub older_than: DateTime<Utc>,
+    pub escalated: Vec<PriorityEscalation>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityEscalation {
+    pub id: String,
+    pub title: String,
+    pub from: Priority,
+    pub to: Priority,
+}
+
+/// What `POST /api/admin/maintenance` did; sizes are in bytes
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceResult {
+    pub size_before: i64,
+    pub size_after: i64,
+    pub reclaimed_bytes: i64,
+    /// Indexes that were missing and have been recreated
+    pub rebuilt_indexes: Vec<String>,
+}
+
+/// The part of the analytics summary kept in `analytics_cache`
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedAnalytics {
+    pub total_tasks: u32,
+    pub tasks_by_status: std::collections::HashMap<String, u32>,
+    pub tasks_by_type: std::collections::HashMap<String, u32>,
+    pub tasks_by_priority: std::collections::HashMap<String, u32>,
+    pub average_story_points: f32,
+    pub completion_rate: f32,
+}
+
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BurndownData {
     pub sprint: String,
     pub start_date: String,
@@ -352,6 +834,7 @@ pub struct BurndownData {
 }
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BurndownDataPoint {
     pub date: String,
     pub remaining_points: u32,
@@ -359,29 +842,427 @@ pub struct BurndownDataPoint {
     pub ideal_remaining: u32,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThroughputInterval {
+    #[default]
+    Week,
+    Month,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThroughputParams {
+    pub interval: Option<ThroughputInterval>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroughputData {
+    pub interval: ThroughputInterval,
+    pub data: Vec<ThroughputDataPoint>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroughputDataPoint {
+    /// First day of the week (Monday) or month, as YYYY-MM-DD
+    pub period_start: String,
+    pub completed: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SprintForecastParams {
+    /// Story points completed per day; measured from recent completions when unset
+    pub velocity: Option<f64>,
+    /// Overrides the sprint's end date, otherwise the latest due date among its tasks
+    pub end_date: Option<chrono::NaiveDate>,
+}
+
+// Projected completion of a sprint, from GET /api/sprints/:name/forecast
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintForecast {
+    pub sprint: String,
+    pub remaining_tasks: u32,
+    pub remaining_points: f64,
+    /// Story points completed per day
+    pub velocity: f64,
+    /// `query` when given as `?velocity=`, otherwise `recent`
+    pub velocity_source: String,
+    /// Days of work left at this velocity; None when the velocity is zero
+    pub days_remaining: Option<i64>,
+    pub forecast_date: Option<chrono::NaiveDate>,
+    pub end_date: Option<chrono::NaiveDate>,
+    /// Whether the forecast date falls on or before the end date; None
+    /// without an end date
+    pub on_track: Option<bool>,
+}
+
+// From GET /api/analytics/health-score
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthScore {
+    /// 0 to 100: the factors' percentages averaged by their weights
+    pub score: u32,
+    /// Open tasks the factors were measured over
+    pub tasks: u32,
+    pub factors: Vec<HealthFactor>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthFactor {
+    /// `estimated`, `withCriteria`, `assigned` or `onTime`
+    pub factor: String,
+    /// Share of open tasks meeting it, 0 to 100
+    pub percent: f64,
+    pub weight: u32,
+}
+
+// Open work per assignee, from GET /api/analytics/workload
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadEntry {
+    /// None for unassigned tasks; a pseudonym when analytics are anonymized
+    pub assignee: Option<String>,
+    pub tasks: u32,
+    pub story_points: f64,
+    pub tasks_by_status: std::collections::HashMap<String, u32>,
+}
+
+// Webhook subscriptions
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Key for the HMAC signature on each delivery; only returned when the
+    /// webhook is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Events delivered to this webhook; empty means all of them
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One event queued for one webhook. `status` is `pending` until it is
+/// `delivered`, or `dead` once every attempt has failed.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: u32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecentTasksParams {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveryParams {
+    pub status: Option<String>,
+    pub webhook_id: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Generated when omitted
+    pub secret: Option<String>,
+    pub events: Option<Vec<String>>,
+}
+
 // Configuration types
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WorkspaceConfig {
+    #[serde(alias = "workspace_name")]
     pub workspace_name: String,
     pub timezone: String,
+    #[serde(alias = "date_format")]
     pub date_format: String,
     pub features: WorkspaceFeatures,
     pub limits: WorkspaceLimits,
+    #[serde(default)]
+    pub workflow: WorkspaceWorkflow,
+    /// `column:direction` used to order task lists that don't pass `sort`
+    #[serde(default = "default_task_sort", alias = "default_sort")]
+    pub default_sort: String,
+    /// `created_at` or `updated_at`; orders tasks the sort leaves tied,
+    /// oldest first, before falling back to their id
+    #[serde(default = "default_sort_tiebreaker", alias = "sort_tiebreaker")]
+    pub sort_tiebreaker: String,
+    /// Statuses tasks may use, built-in or custom (e.g. "Blocked")
+    #[serde(default = "default_statuses", alias = "available_statuses")]
+    pub available_statuses: Vec<String>,
+    /// Priorities tasks may use, from most to least urgent
+    #[serde(default = "default_priorities", alias = "available_priorities")]
+    pub available_priorities: Vec<String>,
+    #[serde(default)]
+    pub theme: WorkspaceTheme,
+    /// Name of exported files, without the extension. `{workspace}`,
+    /// `{date}` and `{filter}` are filled in per export
+    #[serde(default = "default_export_filename", alias = "export_filename")]
+    pub export_filename: String,
+    /// Sprint the team is working in now, if any
+    #[serde(default, alias = "current_sprint")]
+    pub current_sprint: Option<String>,
+    #[serde(default, alias = "health_score_weights")]
+    pub health_score_weights: HealthScoreWeights,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How much each factor counts towards the board health score; only their
+/// proportions matter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HealthScoreWeights {
+    pub estimated: u32,
+    #[serde(alias = "with_criteria")]
+    pub with_criteria: u32,
+    pub assigned: u32,
+    #[serde(alias = "on_time")]
+    pub on_time: u32,
+}
+
+impl Default for HealthScoreWeights {
+    fn default() -> Self {
+        Self { estimated: 25, with_criteria: 25, assigned: 25, on_time: 25 }
+    }
+}
+
+/// Board colors, as `#rgb` or `#rrggbb` hex, so every client renders the
+/// workspace the same way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceTheme {
+    #[serde(alias = "primary_color")]
+    pub primary_color: String,
+    /// Keyed by status name, as in `available_statuses`
+    #[serde(alias = "status_colors")]
+    pub status_colors: std::collections::HashMap<String, String>,
+    /// Keyed by priority name, as in `available_priorities`
+    #[serde(alias = "priority_colors")]
+    pub priority_colors: std::collections::HashMap<String, String>,
+}
+
+impl Default for WorkspaceTheme {
+    fn default() -> Self {
+        let colors = |pairs: &[(&str, &str)]| pairs.iter()
+            .map(|(name, color)| (name.to_string(), color.to_string()))
+            .collect();
+
+        Self {
+            primary_color: "#3b82f6".to_string(),
+            status_colors: colors(&[
+                ("Todo", "#6b7280"),
+                ("In Progress", "#3b82f6"),
+                ("In Review", "#f59e0b"),
+                ("Done", "#10b981"),
+            ]),
+            priority_colors: colors(&[
+                ("Critical", "#dc2626"),
+                ("High", "#f97316"),
+                ("Medium", "#eab308"),
+                ("Low", "#22c55e"),
+            ]),
+        }
+    }
+}
+
+impl WorkspaceConfig {
+    /// The workspace's IANA timezone, falling back to UTC for names chrono-tz doesn't know
+    pub fn tz(&self) -> chrono_tz::Tz {
+        self.timezone.parse().unwrap_or(chrono_tz::UTC)
+    }
+}
+
+pub fn default_export_filename() -> String {
+    "taskdown-export-{date}".to_string()
+}
+
+pub fn default_sort_tiebreaker() -> String {
+    "created_at".to_string()
+}
+
+pub const SORT_TIEBREAKERS: &[&str] = &["created_at", "updated_at"];
+
+pub fn default_task_sort() -> String {
+    "updated_at:desc".to_string()
+}
+
+pub fn default_statuses() -> Vec<String> {
+    TaskStatus::BUILT_IN.iter().map(|status| status.label().to_string()).collect()
+}
+
+pub fn default_priorities() -> Vec<String> {
+    Priority::BUILT_IN.iter().map(|priority| priority.name().to_string()).collect()
+}
+
+pub fn default_max_relationships() -> u32 {
+    100
+}
+
+pub fn default_usage_warning_percent() -> u32 {
+    90
+}
+
+pub fn default_max_thumbnail_bytes() -> u32 {
+    1024 * 1024
+}
+
+/// Most `limits.max_import_bytes` may be set to. The import routes accept
+/// request bodies of twice this, since JSON escaping grows a document.
+pub const MAX_IMPORT_BYTES_CEILING: u32 = 50 * 1024 * 1024;
+
+pub fn default_max_import_bytes() -> u32 {
+    5 * 1024 * 1024
+}
+
+pub fn default_max_import_tasks() -> u32 {
+    1000
+}
+
+pub fn default_escalation_age_days() -> u32 {
+    30
+}
+
+pub fn default_thumbnail_types() -> Vec<String> {
+    crate::thumbnails::DEFAULT_CONTENT_TYPES.iter().map(|content_type| content_type.to_string()).collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WorkspaceFeatures {
     pub realtime: bool,
     pub analytics: bool,
     pub webhooks: bool,
+    #[serde(alias = "custom_fields")]
     pub custom_fields: bool,
+    #[serde(default)]
+    pub ai: bool,
+    /// Replace assignees in workload analytics with opaque pseudonyms
+    #[serde(default, alias = "anonymize_analytics")]
+    pub anonymize_analytics: bool,
+    /// Session permissions (e.g. "admin", "read") that grant `view_analytics`;
+    /// sessions holding `view_analytics` itself always have it
+    #[serde(default = "default_analytics_permissions", alias = "analytics_permissions")]
+    pub analytics_permissions: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+pub fn default_analytics_permissions() -> Vec<String> {
+    vec!["admin".to_string()]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WorkspaceLimits {
+    #[serde(alias = "max_tasks")]
     pub max_tasks: u32,
+    #[serde(alias = "max_users")]
     pub max_users: u32,
+    #[serde(alias = "api_rate_limit")]
     pub api_rate_limit: u32,
+    /// Maximum number of tasks per status, keyed by stored status name (e.g. "InProgress")
+    #[serde(default, alias = "wip_limits")]
+    pub wip_limits: std::collections::HashMap<String, u32>,
+    /// Days of activity history to keep; 0 keeps everything
+    #[serde(default, alias = "activity_retention_days")]
+    pub activity_retention_days: u32,
+    /// Most dependencies, and separately most blocks, a single task may list
+    #[serde(default = "default_max_relationships", alias = "max_relationships")]
+    pub max_relationships: u32,
+    /// Usage of `max_tasks` or `max_users`, in percent, past which the
+    /// analytics summary warns
+    #[serde(default = "default_usage_warning_percent", alias = "usage_warning_percent")]
+    pub usage_warning_percent: u32,
+    /// Largest decoded image accepted as a thumbnail
+    #[serde(default = "default_max_thumbnail_bytes", alias = "max_thumbnail_bytes")]
+    pub max_thumbnail_bytes: u32,
+    /// Content types thumbnails may be uploaded as
+    #[serde(default = "default_thumbnail_types", alias = "thumbnail_types")]
+    pub thumbnail_types: Vec<String>,
+    /// Largest Markdown or CSV document an import accepts
+    #[serde(default = "default_max_import_bytes", alias = "max_import_bytes")]
+    pub max_import_bytes: u32,
+    /// Most tasks a single import may contain
+    #[serde(default = "default_max_import_tasks", alias = "max_import_tasks")]
+    pub max_import_tasks: u32,
+    /// Age in days past which `POST /api/admin/age-escalation` raises an
+    /// open task's priority
+    #[serde(default = "default_escalation_age_days", alias = "escalation_age_days")]
+    pub escalation_age_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceWorkflow {
+    /// Refuse to move a task to Done while any acceptance criterion is open
+    #[serde(default, alias = "require_all_criteria_for_done")]
+    pub require_all_criteria_for_done: bool,
+    /// Refuse to move a task to In Progress while nobody is assigned to it
+    #[serde(default, alias = "require_assignee_for_in_progress")]
+    pub require_assignee_for_in_progress: bool,
+    /// Refuse two tasks with the same title (ignoring case) under one epic
+    #[serde(default, alias = "unique_title_per_epic")]
+    pub unique_title_per_epic: bool,
+    /// Status a task moves to once an update completes its last open
+    /// acceptance criterion; no automatic move when unset
+    #[serde(default, alias = "status_on_criteria_complete")]
+    pub status_on_criteria_complete: Option<TaskStatus>,
+    /// Regex that ids supplied by clients (e.g. `preserve_ids` imports) must
+    /// match in full; any non-empty id is accepted when unset
+    #[serde(default, alias = "task_id_pattern")]
+    pub task_id_pattern: Option<String>,
+    /// Give tasks created under an epic ids numbered within that epic
+    /// (`AUTH-1`, `AUTH-2`, ...) instead of UUIDs
+    #[serde(default, alias = "epic_scoped_ids")]
+    pub epic_scoped_ids: bool,
+    /// Find tasks by id regardless of case (`proj-1` finds `PROJ-1`); ids
+    /// are still stored as created
+    #[serde(default, alias = "case_insensitive_ids")]
+    pub case_insensitive_ids: bool,
+    /// Story point values tasks may use, e.g. `[0.5, 1, 2, 3]`; any whole
+    /// number is accepted when unset
+    #[serde(default, alias = "story_point_scale")]
+    pub story_point_scale: Option<Vec<f64>>,
+    /// Fewest characters a description needs, keyed by task type (e.g.
+    /// `{"Story": 20}`); types not listed have no minimum
+    #[serde(default, alias = "min_description_length")]
+    pub min_description_length: std::collections::HashMap<String, u32>,
+    /// Assign new tasks to their creator when the request leaves `assignee`
+    /// out; an explicit null still leaves the task unassigned
+    #[serde(default, alias = "self_assign_on_create")]
+    pub self_assign_on_create: bool,
+    /// Put new tasks in the configured `current_sprint` when the request
+    /// leaves `sprint` out; an explicit null still leaves the task unplanned
+    #[serde(default, alias = "default_to_current_sprint")]
+    pub default_to_current_sprint: bool,
+    /// Keep each epic's taxonomy `status` in step with its tasks
+    #[serde(default, alias = "auto_epic_status")]
+    pub auto_epic_status: bool,
+    /// Record each relationship on both tasks: listing B in A's `blocks`
+    /// adds A to B's `dependencies`, and removing it takes A back out
+    #[serde(default, alias = "enforce_symmetric_relations")]
+    pub enforce_symmetric_relations: bool,
+    /// Add `isBlocked` to task responses, true while any of the task's
+    /// dependencies isn't Done
+    #[serde(default, alias = "flag_blocked_tasks")]
+    pub flag_blocked_tasks: bool,
+    /// Drop checklist items whose text repeats an earlier item in the same
+    /// list (ignoring case and spacing) when a task is saved
+    #[serde(default, alias = "dedupe_checklist_items")]
+    pub dedupe_checklist_items: bool,
 }
 
 // Bulk operations
@@ -398,6 +1279,7 @@ pub struct BulkOperation {
 }
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BulkOperationResult {
     pub operation: String,
     pub task_id: String,
@@ -412,17 +1294,200 @@ pub struct ImportMarkdownRequest {
     pub options: Option<ImportOptions>,
 }
 
+// Body of POST /api/import/csv
 #[derive(Debug, Deserialize)]
+pub struct ImportCsvRequest {
+    pub csv: String,
+    /// CSV header -> task field, for columns not named after a field (e.g. `Summary` -> `title`)
+    #[serde(default)]
+    pub column_map: std::collections::HashMap<String, String>,
+    pub options: Option<ImportOptions>,
+}
+
+// Body of POST /api/diff/markdown
+#[derive(Debug, Deserialize)]
+pub struct DiffMarkdownRequest {
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct BoardDiff {
+    pub added: Vec<TaskSummary>,
+    pub removed: Vec<TaskSummary>,
+    pub changed: Vec<TaskChange>,
+}
+
+/// The fields a task is recognised by at a glance, for lists that don't need whole tasks
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSummary {
+    pub id: String,
+    pub title: String,
+    pub epic: Option<String>,
+    pub status: TaskStatus,
+    pub priority: Priority,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub story_points: Option<f64>,
+}
+
+impl From<&Task> for TaskSummary {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: task.id.clone(),
+            title: task.title.clone(),
+            epic: task.epic.clone(),
+            status: task.status.clone(),
+            priority: task.priority.clone(),
+            assignee: task.assignee.clone(),
+            story_points: task.story_points,
+        }
+    }
+}
+
+/// Fields `GET /api/tasks/grouped` can group by
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskGrouping {
+    #[default]
+    Status,
+    Epic,
+    Assignee,
+    Priority,
+    Sprint,
+}
+
+impl TaskGrouping {
+    /// The group a task falls in; tasks without an epic, assignee or sprint
+    /// are grouped under ""
+    pub fn key(&self, task: &Task) -> String {
+        match self {
+            TaskGrouping::Status => task.status.label().to_string(),
+            TaskGrouping::Priority => task.priority.name().to_string(),
+            TaskGrouping::Epic => task.epic.clone().unwrap_or_default(),
+            TaskGrouping::Assignee => task.assignee.clone().unwrap_or_default(),
+            TaskGrouping::Sprint => task.sprint.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskGroupingParams {
+    pub by: Option<TaskGrouping>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskChange {
+    pub id: String,
+    pub title: String,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+// Body of POST /api/tasks/import-url
+#[derive(Debug, Deserialize)]
+pub struct ImportUrlRequest {
+    pub url: String,
+    pub options: Option<ImportOptions>,
+}
+
+#[derive(Debug, Deserialize, Default)]
 pub struct ImportOptions {
     pub overwrite: Option<bool>,
     pub preserve_ids: Option<bool>,
+    /// Group tasks without an explicit epic under the prefix of their id (`AUTH-12` -> `AUTH`)
+    pub infer_epic_from_prefix: Option<bool>,
+    /// Assignee given to imported tasks that don't name one
+    pub default_assignee: Option<String>,
+    /// What to do with a `preserve_ids` task whose id already exists
+    pub on_conflict: Option<ConflictStrategy>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Leave the existing task as it is
+    Skip,
+    /// Replace every field of the existing task
+    #[default]
+    Overwrite,
+    /// Update only the fields the imported board gives
+    Merge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportOutcome {
+    Created,
+    Updated,
+    Merged,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskImportOutcome {
+    /// The id the task had in the imported board
+    pub id: String,
+    pub outcome: ImportOutcome,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ImportResult {
     pub imported: u32,
     pub updated: u32,
+    pub skipped: u32,
+    pub outcomes: Vec<TaskImportOutcome>,
     pub errors: Vec<String>,
+    /// Problems that didn't stop the import, e.g. a board format version mismatch
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelAssignResult {
+    pub label: String,
+    /// Tasks selected
+    pub matched: usize,
+    /// Selected tasks that gained (or lost) the label; the rest already had it (or didn't)
+    pub changed: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportStreamParams {
+    pub format: Option<ExportFormat>,
+    pub include_done: Option<bool>,
+    pub sort: Option<String>,
+}
+
+/// Query options shared by the Markdown exports
+#[derive(Debug, Default, Deserialize)]
+pub struct MarkdownOptions {
+    /// Leave out `**Dependencies**` and `**Blocks**` lines with nothing in
+    /// them instead of writing `None`
+    #[serde(default)]
+    pub omit_empty_sections: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskExportParams {
+    pub format: Option<ExportFormat>,
 }
 
 #[derive(Debug, Serialize)]
@@ -0,0 +1,305 @@
+use lettre::{
+    message::Mailbox,
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::sync::OnceLock;
+use tokio::sync::mpsc;
+
+use crate::database::{self, DbPool};
+
+static QUEUE: OnceLock<mpsc::UnboundedSender<Notification>> = OnceLock::new();
+
+/// Something a user should hear about by email
+#[derive(Debug)]
+enum Notification {
+    Assigned { task_id: String, task_title: String, assignee: String },
+    Reassigned { assignee: String, count: u32 },
+    Mentioned { task_id: String, task_title: String, username: String },
+}
+
+impl Notification {
+    fn recipient(&self) -> &str {
+        match self {
+            Notification::Assigned { assignee, .. } => assignee,
+            Notification::Reassigned { assignee, .. } => assignee,
+            Notification::Mentioned { username, .. } => username,
+        }
+    }
+
+    fn subject(&self) -> String {
+        match self {
+            Notification::Assigned { task_id, task_title, .. } => {
+                format!("[Taskdown] {} assigned to you: {}", task_id, task_title)
+            }
+            Notification::Reassigned { count, .. } => {
+                format!("[Taskdown] {} {} reassigned to you", count, tasks_noun(*count))
+            }
+            Notification::Mentioned { task_id, task_title, .. } => {
+                format!("[Taskdown] You were mentioned in {}: {}", task_id, task_title)
+            }
+        }
+    }
+
+    fn body(&self) -> String {
+        match self {
+            Notification::Assigned { task_id, task_title, .. } => {
+                format!("Task {} \"{}\" has been assigned to you.", task_id, task_title)
+            }
+            Notification::Reassigned { count, .. } => {
+                format!("{} {} been reassigned to you.", count, if *count == 1 { "task has" } else { "tasks have" })
+            }
+            Notification::Mentioned { task_id, task_title, .. } => {
+                format!("You were mentioned in the description of task {} \"{}\".", task_id, task_title)
+            }
+        }
+    }
+}
+
+fn tasks_noun(count: u32) -> &'static str {
+    if count == 1 { "task" } else { "tasks" }
+}
+
+struct SmtpConfig {
+    host: String,
+    port: Option<u16>,
+    tls: String,
+    credentials: Option<Credentials>,
+    from: Mailbox,
+}
+
+impl SmtpConfig {
+    /// Reads SMTP_* variables; None when SMTP_HOST is unset
+    fn from_env() -> Option<Self> {
+        let host = std::env::var("SMTP_HOST").ok().filter(|host| !host.is_empty())?;
+        let port = std::env::var("SMTP_PORT").ok().and_then(|port| port.parse().ok());
+        let tls = std::env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string());
+        let credentials = match (std::env::var("SMTP_USERNAME"), std::env::var("SMTP_PASSWORD")) {
+            (Ok(username), Ok(password)) => Some(Credentials::new(username, password)),
+            _ => None,
+        };
+        let from = std::env::var("SMTP_FROM")
+            .unwrap_or_else(|_| "Taskdown <taskdown@localhost>".to_string());
+        let from = match from.parse() {
+            Ok(from) => from,
+            Err(e) => {
+                tracing::error!("Invalid SMTP_FROM address {}: {}", from, e);
+                return None;
+            }
+        };
+
+        Some(Self { host, port, tls, credentials, from })
+    }
+
+    fn transport(&self) -> anyhow::Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let mut builder = match self.tls.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)?,
+        };
+        if let Some(port) = self.port {
+            builder = builder.port(port);
+        }
+        if let Some(credentials) = &self.credentials {
+            builder = builder.credentials(credentials.clone());
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Starts the background sender. When SMTP isn't configured nothing is
+/// started and every notification below is silently dropped.
+pub fn init(pool: DbPool) {
+    let Some(config) = SmtpConfig::from_env() else {
+        tracing::info!("SMTP_HOST not set; email notifications are disabled");
+        return;
+    };
+
+    let transport = match config.transport() {
+        Ok(transport) => transport,
+        Err(e) => {
+            tracing::error!("Failed to set up SMTP transport for {}: {}", config.host, e);
+            return;
+        }
+    };
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    if QUEUE.set(sender).is_ok() {
+        tracing::info!("Email notifications enabled via {}", config.host);
+        tokio::spawn(run_sender(pool, transport, config.from, receiver));
+    }
+}
+
+/// The task's new assignee gets an email, unless they assigned it themselves
+pub fn task_assigned(task_id: &str, task_title: &str, assignee: &str, actor: Option<&str>) {
+    if actor == Some(assignee) {
+        return;
+    }
+
+    enqueue(Notification::Assigned {
+        task_id: task_id.to_string(),
+        task_title: task_title.to_string(),
+        assignee: assignee.to_string(),
+    });
+}
+
+pub fn tasks_reassigned(assignee: &str, count: u32, actor: Option<&str>) {
+    if count == 0 || actor == Some(assignee) {
+        return;
+    }
+
+    enqueue(Notification::Reassigned {
+        assignee: assignee.to_string(),
+        count,
+    });
+}
+
+/// Emails users whose @username appears in `new_text` but not in `old_text`,
+/// so editing a description doesn't re-notify everyone already mentioned
+pub fn new_mentions(task_id: &str, task_title: &str, old_text: &str, new_text: &str, actor: Option<&str>) {
+    let already_mentioned = mentions(old_text);
+    for username in mentions(new_text) {
+        if already_mentioned.contains(&username) || actor == Some(username.as_str()) {
+            continue;
+        }
+
+        enqueue(Notification::Mentioned {
+            task_id: task_id.to_string(),
+            task_title: task_title.to_string(),
+            username,
+        });
+    }
+}
+
+fn enqueue(notification: Notification) {
+    if let Some(queue) = QUEUE.get() {
+        if queue.send(notification).is_err() {
+            tracing::error!("Email notification queue is closed");
+        }
+    }
+}
+
+// "@name" tokens, where a name is letters, digits, '_', '-' or '.'. An '@'
+// preceded by a word character (as in an email address) is not a mention.
+fn mentions(text: &str) -> HashSet<String> {
+    let mut found = HashSet::new();
+    let mut previous: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if c == '@' && !previous.is_some_and(|p| p.is_alphanumeric()) {
+            let mut end = start + 1;
+            while let Some(&(i, next)) = chars.peek() {
+                if next.is_alphanumeric() || matches!(next, '_' | '-' | '.') {
+                    end = i + next.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let username = text[start + 1..end].trim_end_matches('.');
+            if !username.is_empty() {
+                found.insert(username.to_string());
+            }
+            previous = text[..end].chars().next_back();
+        } else {
+            previous = Some(c);
+        }
+    }
+
+    found
+}
+
+async fn run_sender<T>(
+    pool: DbPool,
+    transport: T,
+    from: Mailbox,
+    mut receiver: mpsc::UnboundedReceiver<Notification>,
+) where
+    T: AsyncTransport + Sync,
+    T::Error: Display,
+{
+    while let Some(notification) = receiver.recv().await {
+        let Some(to) = resolve_recipient(&pool, notification.recipient()).await else {
+            tracing::debug!("No email address for {}, skipping notification", notification.recipient());
+            continue;
+        };
+
+        let message = Message::builder()
+            .from(from.clone())
+            .to(to)
+            .subject(notification.subject())
+            .body(notification.body());
+
+        match message {
+            Ok(message) => {
+                if let Err(e) = transport.send(message).await {
+                    tracing::error!("Failed to send email notification to {}: {}", notification.recipient(), e);
+                }
+            }
+            Err(e) => tracing::error!("Failed to build email notification: {}", e),
+        }
+    }
+}
+
+// Assignees and mentions name a user by id or username; an assignee that is
+// itself an email address is mailed directly
+async fn resolve_recipient(pool: &DbPool, name: &str) -> Option<Mailbox> {
+    match database::get_user_email(pool, name).await {
+        Ok(Some(email)) => return email.parse().ok(),
+        Ok(None) => {}
+        Err(e) => tracing::error!("Failed to look up email for {}: {}", name, e),
+    }
+
+    name.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UserRole;
+    use crate::test_support::*;
+    use lettre::transport::stub::AsyncStubTransport;
+
+    #[test]
+    fn mentions_skip_email_addresses_and_trailing_dots() {
+        let found = mentions("Thanks @alice and @bob.smith. Mail carol@example.com or @dave.");
+        let mut found: Vec<_> = found.into_iter().collect();
+        found.sort();
+        assert_eq!(found, ["alice", "bob.smith", "dave"]);
+    }
+
+    #[tokio::test]
+    async fn notifications_are_mailed_to_resolvable_recipients() {
+        let db = db().await;
+        add_user(&db, "alice", UserRole::User).await;
+
+        let transport = AsyncStubTransport::new_ok();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        sender.send(Notification::Assigned {
+            task_id: "TASK-1".to_string(),
+            task_title: "Ship it".to_string(),
+            assignee: "alice".to_string(),
+        }).unwrap();
+        sender.send(Notification::Mentioned {
+            task_id: "TASK-1".to_string(),
+            task_title: "Ship it".to_string(),
+            username: "nobody".to_string(),
+        }).unwrap();
+        sender.send(Notification::Reassigned { assignee: "bob@example.com".to_string(), count: 2 }).unwrap();
+        drop(sender);
+
+        let from: Mailbox = "Taskdown <taskdown@localhost>".parse().unwrap();
+        run_sender(db.pool.clone(), transport.clone(), from, receiver).await;
+
+        let messages = transport.messages().await;
+        let recipients: Vec<_> = messages.iter()
+            .map(|(envelope, _)| envelope.to().iter().map(|to| to.to_string()).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(recipients, [["alice@example.com"], ["bob@example.com"]]);
+        assert!(messages[0].1.contains("Subject: [Taskdown] TASK-1 assigned to you: Ship it"));
+        assert!(messages[1].1.contains("Subject: [Taskdown] 2 tasks reassigned to you"));
+    }
+}
@@ -0,0 +1,164 @@
+use axum::http::StatusCode;
+use reqwest::{redirect, Url};
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use crate::validation::ValidationError;
+
+/// Largest markdown document fetched for a URL import
+pub const MAX_REMOTE_BYTES: usize = 2 * 1024 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Downloads a markdown board for `POST /api/tasks/import-url`.
+///
+/// Only http(s) URLs are fetched, and only when every address the host
+/// resolves to is public, so the endpoint can't be used to reach services on
+/// the server's own network. Hosts listed in IMPORT_URL_ALLOW_HOSTS skip that
+/// check. The connection is pinned to the addresses that were checked and
+/// redirects are not followed, since either would let a request land
+/// somewhere that wasn't vetted.
+pub async fn fetch_markdown(url: &str) -> anyhow::Result<String> {
+    let url = Url::parse(url).map_err(|e| invalid_url(&format!("{} is not a valid URL: {}", url, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid_url("Only http and https URLs can be imported").into());
+    }
+    let host = url.host_str()
+        .ok_or_else(|| invalid_url("URL has no host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port)).await
+        .map_err(|e| fetch_failed(&format!("Could not resolve {}: {}", host, e)))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(fetch_failed(&format!("Could not resolve {}", host)).into());
+    }
+    if !host_allowed(&host) {
+        if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+            return Err(ValidationError::new(
+                StatusCode::BAD_REQUEST,
+                "URL_NOT_ALLOWED",
+                format!("{} resolves to a private or local address", host),
+            ).with_details(json!({ "address": addr.ip().to_string() })).into());
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .redirect(redirect::Policy::none())
+        .resolve_to_addrs(&host, &addrs)
+        .build()?;
+
+    let mut response = client.get(url.clone()).send().await
+        .map_err(|e| fetch_failed(&format!("Failed to fetch {}: {}", url, e)))?;
+    if !response.status().is_success() {
+        return Err(fetch_failed(&format!("{} returned {}", url, response.status())).into());
+    }
+    if response.content_length().is_some_and(|length| length > MAX_REMOTE_BYTES as u64) {
+        return Err(too_large().into());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await
+        .map_err(|e| fetch_failed(&format!("Failed to read {}: {}", url, e)))?
+    {
+        if body.len() + chunk.len() > MAX_REMOTE_BYTES {
+            return Err(too_large().into());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    String::from_utf8(body).map_err(|_| invalid_url("Remote document is not UTF-8 text").into())
+}
+
+fn host_allowed(host: &str) -> bool {
+    std::env::var("IMPORT_URL_ALLOW_HOSTS")
+        .map(|hosts| hosts.split(',').any(|allowed| allowed.trim().eq_ignore_ascii_case(host)))
+        .unwrap_or(false)
+}
+
+// Loopback, private, link-local, CGNAT, unspecified, multicast and
+// documentation ranges are all off limits
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80) // link local
+        }
+    }
+}
+
+fn invalid_url(message: &str) -> ValidationError {
+    ValidationError::new(StatusCode::BAD_REQUEST, "INVALID_URL", message)
+}
+
+fn fetch_failed(message: &str) -> ValidationError {
+    ValidationError::new(StatusCode::BAD_GATEWAY, "FETCH_FAILED", message)
+}
+
+fn too_large() -> ValidationError {
+    ValidationError::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "REMOTE_TOO_LARGE",
+        format!("Remote documents may be at most {} bytes", MAX_REMOTE_BYTES),
+    ).with_details(json!({ "maxBytes": MAX_REMOTE_BYTES }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn rejection_code(url: &str) -> &'static str {
+        let error = fetch_markdown(url).await.unwrap_err();
+        error.downcast_ref::<ValidationError>().expect("validation error").code
+    }
+
+    #[tokio::test]
+    async fn local_and_private_hosts_are_not_fetched() {
+        assert_eq!(rejection_code("http://localhost/board.md").await, "URL_NOT_ALLOWED");
+        assert_eq!(rejection_code("http://127.0.0.1:8080/board.md").await, "URL_NOT_ALLOWED");
+        assert_eq!(rejection_code("http://[::1]/board.md").await, "URL_NOT_ALLOWED");
+        assert_eq!(rejection_code("http://10.0.0.5/board.md").await, "URL_NOT_ALLOWED");
+        assert_eq!(rejection_code("http://169.254.169.254/latest/meta-data").await, "URL_NOT_ALLOWED");
+    }
+
+    #[tokio::test]
+    async fn only_http_urls_are_fetched() {
+        assert_eq!(rejection_code("file:///etc/passwd").await, "INVALID_URL");
+        assert_eq!(rejection_code("ftp://example.com/board.md").await, "INVALID_URL");
+        assert_eq!(rejection_code("not a url").await, "INVALID_URL");
+    }
+
+    #[test]
+    fn public_addresses_are_told_from_reserved_ones() {
+        for ip in ["93.184.216.34", "2606:2800:220:1::1"] {
+            assert!(is_public(ip.parse().unwrap()), "{} is public", ip);
+        }
+        for ip in ["192.168.1.1", "100.64.0.1", "0.0.0.0", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{} is not public", ip);
+        }
+    }
+}
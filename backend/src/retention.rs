@@ -0,0 +1,67 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use std::time::Duration as StdDuration;
+
+use crate::database::{self, DbPool};
+
+const PURGE_INTERVAL: StdDuration = StdDuration::from_secs(60 * 60);
+
+/// Deletes activities older than `limits.activity_retention_days`.
+/// Returns how many were removed; a retention of 0 keeps everything.
+pub async fn purge_expired_activities(pool: &DbPool) -> Result<u64> {
+    let config = database::get_workspace_config(pool).await?;
+    let days = config.limits.activity_retention_days;
+    if days == 0 {
+        return Ok(0);
+    }
+
+    let older_than = Utc::now() - Duration::days(i64::from(days));
+    database::purge_old_activities(pool, older_than).await
+}
+
+/// Applies the retention window once an hour for as long as the server runs
+pub fn spawn(pool: DbPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_expired_activities(&pool).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} activities past the retention window", purged),
+                Err(e) => tracing::error!("Failed to purge old activities: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Actor;
+    use crate::test_support::*;
+
+    async fn log_at(pool: &DbPool, action: &str, days_ago: i64) {
+        database::log_activity(pool, &Actor::system(), action, "task", "TASK-1", "Task", None).await.unwrap();
+        sqlx::query("UPDATE activities SET timestamp = ? WHERE action = ?")
+            .bind((Utc::now() - Duration::days(days_ago)).to_rfc3339())
+            .bind(action)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_activities_past_the_window_are_purged() {
+        let db = db().await;
+        log_at(&db, "old", 40).await;
+        log_at(&db, "recent", 5).await;
+
+        update_config(&db, |config| config.limits.activity_retention_days = 0).await;
+        assert_eq!(purge_expired_activities(&db).await.unwrap(), 0);
+
+        update_config(&db, |config| config.limits.activity_retention_days = 30).await;
+        assert_eq!(purge_expired_activities(&db).await.unwrap(), 1);
+        assert!(activities(&db, "old").await.is_empty());
+        assert_eq!(activities(&db, "recent").await.len(), 1);
+    }
+}
@@ -0,0 +1,136 @@
+//! Shared setup for unit tests: a migrated database per test, and request
+//! headers carrying a signed session token.
+
+use axum::extract::FromRequestParts;
+use axum::http::{HeaderMap, HeaderValue};
+use std::ops::Deref;
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::auth::{AuthService, Claims};
+use crate::database::{self, DbPool};
+use crate::models::*;
+
+// The workspace config cache is process-wide, so tests touching the
+// database take turns rather than seeing each other's config.
+static DB_LOCK: Mutex<()> = Mutex::const_new(());
+
+pub struct TestDb {
+    pub pool: DbPool,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Deref for TestDb {
+    type Target = DbPool;
+
+    fn deref(&self) -> &DbPool {
+        &self.pool
+    }
+}
+
+pub async fn db() -> TestDb {
+    let guard = DB_LOCK.lock().await;
+    let pool = database::test_db().await.expect("test database");
+    TestDb { pool, _guard: guard }
+}
+
+/// Headers signed in as `user_id` with the given permissions
+pub fn headers_as(user_id: &str, permissions: &[&str]) -> HeaderMap {
+    let claims = Claims::new(
+        user_id.to_string(),
+        user_id.to_string(),
+        permissions.iter().map(|permission| permission.to_string()).collect(),
+        1,
+    );
+    let token = AuthService::new().create_token(&claims).expect("token");
+
+    let mut headers = HeaderMap::new();
+    headers.insert("authorization", HeaderValue::from_str(&format!("Bearer {}", token)).unwrap());
+    headers
+}
+
+pub fn admin_headers() -> HeaderMap {
+    headers_as("admin", &["read", "write", "admin"])
+}
+
+pub fn viewer_headers() -> HeaderMap {
+    headers_as("viewer", &["read"])
+}
+
+pub async fn update_config(pool: &DbPool, change: impl FnOnce(&mut WorkspaceConfig)) {
+    let mut config = database::get_workspace_config(pool).await.expect("config");
+    change(&mut config);
+    database::update_workspace_config(pool, &config).await.expect("update config");
+}
+
+pub async fn task_count(pool: &DbPool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM tasks").fetch_one(pool).await.expect("count tasks")
+}
+
+/// A Todo task of type Task and priority Medium, otherwise empty
+pub fn new_task(title: &str) -> CreateTaskRequest {
+    serde_json::from_value(serde_json::json!({
+        "title": title,
+        "type": "Task",
+        "priority": "Medium",
+        "status": "Todo",
+        "description": "",
+        "acceptance_criteria": [],
+        "technical_tasks": [],
+        "dependencies": [],
+        "blocks": [],
+    }))
+    .expect("valid task request")
+}
+
+/// A user whose email is `<username>@example.com`
+pub async fn add_user(pool: &DbPool, username: &str, role: UserRole) {
+    let user = User {
+        id: uuid::Uuid::new_v4().to_string(),
+        username: username.to_string(),
+        display_name: username.to_string(),
+        email: format!("{}@example.com", username),
+        role,
+        avatar: None,
+        is_active: true,
+        last_seen: chrono::Utc::now(),
+    };
+    database::create_user(pool, &user, "hash").await.expect("create user");
+}
+
+pub fn checklist_item(text: &str) -> ChecklistItem {
+    ChecklistItem { id: None, text: text.to_string(), completed: false, source: None }
+}
+
+pub async fn add_task(pool: &DbPool, request: CreateTaskRequest) -> Task {
+    database::create_task(pool, &request, None).await.expect("create task")
+}
+
+/// Tasks matching a `GET /api/tasks` query string
+pub async fn list_tasks(pool: &DbPool, query: &str) -> Vec<Task> {
+    let params: TaskQueryParams = multi_query(query).await;
+    database::get_tasks(pool, &params).await.expect("list tasks")
+}
+
+/// Query parameters parsed the way `MultiQuery` parses them, repeated keys
+/// included; spaces may be written as they are
+pub async fn multi_query<T: serde::de::DeserializeOwned + Send>(query: &str) -> T {
+    let uri = format!("/?{}", query.replace(' ', "%20"));
+    let request = axum::http::Request::builder().uri(uri).body(()).unwrap();
+    let (mut parts, _) = request.into_parts();
+    let axum_extra::extract::Query(params) = axum_extra::extract::Query::<T>::from_request_parts(&mut parts, &())
+        .await
+        .expect("valid query");
+    params
+}
+
+/// Logged activity with the given action, newest first
+pub async fn activities(pool: &DbPool, action: &str) -> Vec<Activity> {
+    let params = ActivityQueryParams {
+        limit: None,
+        offset: None,
+        user_id: None,
+        task_id: None,
+        action: Some(action.to_string()),
+    };
+    database::get_activities(pool, &params).await.expect("activities").0
+}
@@ -0,0 +1,130 @@
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::json;
+
+use crate::models::{ThumbnailUpload, WorkspaceLimits};
+use crate::validation::ValidationError;
+
+/// Image types thumbnails can be; `limits.thumbnail_types` picks from these
+pub const SUPPORTED_CONTENT_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Types accepted until a workspace picks its own; GIF has to be opted into
+pub const DEFAULT_CONTENT_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp"];
+
+/// URL a stored thumbnail is served from, saved in the task's `thumbnail` field
+pub fn thumbnail_url(task_id: &str) -> String {
+    format!("/api/tasks/{}/thumbnail", task_id)
+}
+
+/// Decodes an uploaded image and checks its type and size. The content type
+/// comes from `content_type` or a `data:` URL prefix and must match the bytes.
+/// Allowed types and the size cap come from the workspace limits.
+pub fn decode_upload(upload: &ThumbnailUpload, limits: &WorkspaceLimits) -> Result<(String, Vec<u8>), ValidationError> {
+    let (data_url_type, encoded) = match upload.data.strip_prefix("data:") {
+        Some(rest) => {
+            let (media_type, encoded) = rest.split_once(";base64,")
+                .ok_or_else(|| invalid("Data URLs must be base64 encoded"))?;
+            (Some(media_type.to_string()), encoded)
+        }
+        None => (None, upload.data.as_str()),
+    };
+
+    let content_type = upload.content_type.clone()
+        .or(data_url_type)
+        .ok_or_else(|| invalid("content_type is required unless data is a data: URL"))?
+        .to_lowercase();
+
+    if !limits.thumbnail_types.contains(&content_type) {
+        return Err(ValidationError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "UNSUPPORTED_MEDIA_TYPE",
+            format!("{} is not a supported thumbnail type", content_type),
+        ).with_details(json!({ "allowed": limits.thumbnail_types })));
+    }
+
+    // Checked before decoding so oversized uploads aren't decoded at all
+    let max_bytes = limits.max_thumbnail_bytes as usize;
+    if encoded.len() / 4 * 3 > max_bytes + 2 {
+        return Err(too_large(max_bytes));
+    }
+
+    let bytes = STANDARD.decode(encoded.trim())
+        .map_err(|e| invalid(&format!("Thumbnail data is not valid base64: {}", e)))?;
+
+    if bytes.len() > max_bytes {
+        return Err(too_large(max_bytes));
+    }
+
+    if sniff_content_type(&bytes) != Some(content_type.as_str()) {
+        return Err(invalid(&format!("Thumbnail data does not match content type {}", content_type)));
+    }
+
+    Ok((content_type, bytes))
+}
+
+// Identifies an image by its magic bytes
+fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+fn invalid(message: &str) -> ValidationError {
+    ValidationError::new(StatusCode::BAD_REQUEST, "INVALID_THUMBNAIL", message)
+}
+
+fn too_large(max_bytes: usize) -> ValidationError {
+    ValidationError::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "THUMBNAIL_TOO_LARGE",
+        format!("Thumbnails may be at most {} bytes", max_bytes),
+    ).with_details(json!({ "maxBytes": max_bytes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn limits(max_thumbnail_bytes: u32) -> WorkspaceLimits {
+        let mut limits: WorkspaceLimits = serde_json::from_value(json!({"maxTasks": 100, "maxUsers": 10, "apiRateLimit": 100})).unwrap();
+        limits.max_thumbnail_bytes = max_thumbnail_bytes;
+        limits
+    }
+
+    fn upload(content_type: &str, bytes: &[u8]) -> ThumbnailUpload {
+        ThumbnailUpload { content_type: Some(content_type.to_string()), data: STANDARD.encode(bytes) }
+    }
+
+    #[test]
+    fn images_within_the_limits_are_accepted() {
+        let (content_type, bytes) = decode_upload(&upload("image/png", PNG), &limits(1024)).unwrap();
+        assert_eq!((content_type.as_str(), bytes.as_slice()), ("image/png", PNG));
+    }
+
+    #[test]
+    fn over_limit_images_are_rejected() {
+        let mut image = PNG.to_vec();
+        image.resize(2048, 0);
+
+        let error = decode_upload(&upload("image/png", &image), &limits(1024)).unwrap_err();
+        assert_eq!((error.status, error.code), (StatusCode::PAYLOAD_TOO_LARGE, "THUMBNAIL_TOO_LARGE"));
+    }
+
+    #[test]
+    fn unsupported_types_are_rejected() {
+        for content_type in ["image/gif", "image/svg+xml"] {
+            let error = decode_upload(&upload(content_type, b"GIF89a"), &limits(1024)).unwrap_err();
+            assert_eq!((error.status, error.code), (StatusCode::UNSUPPORTED_MEDIA_TYPE, "UNSUPPORTED_MEDIA_TYPE"));
+        }
+    }
+}
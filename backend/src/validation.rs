@@ -0,0 +1,868 @@
+use axum::http::StatusCode;
+use regex::Regex;
+use serde_json::json;
+use std::fmt;
+
+use crate::database::{self, DbPool};
+use crate::models::*;
+use crate::{thumbnails, webhooks};
+
+const MAX_LABEL_LENGTH: usize = 50;
+
+/// A request that breaks one of the workspace's rules. Handlers report these
+/// to the client with their own status and code rather than as a 500.
+#[derive(Debug)]
+pub struct ValidationError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl ValidationError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+// Checks a new task against the workspace rules before it is written
+pub async fn validate_task_create(pool: &DbPool, request: &CreateTaskRequest) -> anyhow::Result<()> {
+    let config = database::get_workspace_config(pool).await?;
+    validate_status(&config, &request.status)?;
+    validate_priority(&config, &request.priority)?;
+    validate_story_points(&config, request.story_points)?;
+    check_relationship_limit(&config, "dependencies", &request.dependencies)?;
+    check_relationship_limit(&config, "blocks", &request.blocks)?;
+    check_description_length(&config, &request.r#type, &request.description)?;
+    check_checklist_sources(&request.acceptance_criteria)?;
+    check_checklist_sources(&request.technical_tasks)?;
+
+    if let Some(epic) = request.epic.as_ref().filter(|_| config.workflow.unique_title_per_epic) {
+        check_unique_title(pool, epic, &request.title, None).await?;
+    }
+
+    if request.status == TaskStatus::InProgress && config.workflow.require_assignee_for_in_progress {
+        check_assigned(request.assignee.as_deref())?;
+    }
+
+    Ok(())
+}
+
+// Checks an update against the workspace rules before it is written
+pub async fn validate_task_update(
+    pool: &DbPool,
+    task: &Task,
+    request: &UpdateTaskRequest,
+    force: bool,
+) -> anyhow::Result<()> {
+    let config = database::get_workspace_config(pool).await?;
+
+    if let Some(status) = request.status.as_ref().filter(|status| **status != task.status) {
+        validate_status(&config, status)?;
+    }
+
+    if let Some(priority) = request.priority.as_ref().filter(|priority| **priority != task.priority) {
+        validate_priority(&config, priority)?;
+    }
+
+    if let Some(points) = request.story_points {
+        validate_story_points(&config, points)?;
+    }
+
+    if let Some(dependencies) = &request.dependencies {
+        check_relationship_limit(&config, "dependencies", dependencies)?;
+    }
+    if let Some(blocks) = &request.blocks {
+        check_relationship_limit(&config, "blocks", blocks)?;
+    }
+
+    if let Some(criteria) = &request.acceptance_criteria {
+        check_checklist_sources(criteria)?;
+    }
+    if let Some(technical_tasks) = &request.technical_tasks {
+        check_checklist_sources(technical_tasks)?;
+    }
+
+    if config.workflow.unique_title_per_epic && (request.title.is_some() || request.epic.is_some()) {
+        let epic = match &request.epic {
+            Some(epic) => epic.as_deref(),
+            None => task.epic.as_deref(),
+        };
+        if let Some(epic) = epic {
+            let title = request.title.as_ref().unwrap_or(&task.title);
+            check_unique_title(pool, epic, title, Some(&task.id)).await?;
+        }
+    }
+
+    // Only checked when touched, so older short descriptions don't block other edits
+    if request.description.is_some() || request.r#type.is_some() {
+        let task_type = request.r#type.as_ref().unwrap_or(&task.r#type);
+        let description = request.description.as_ref().unwrap_or(&task.description);
+        check_description_length(&config, task_type, description)?;
+    }
+
+    if request.status == Some(TaskStatus::Done) && config.workflow.require_all_criteria_for_done {
+        let criteria = request.acceptance_criteria.as_ref().unwrap_or(&task.acceptance_criteria);
+        check_criteria_complete(criteria)?;
+    }
+
+    let starting = request.status == Some(TaskStatus::InProgress) && task.status != TaskStatus::InProgress;
+    if starting && config.workflow.require_assignee_for_in_progress {
+        let assignee = match &request.assignee {
+            Some(assignee) => assignee.as_deref(),
+            None => task.assignee.as_deref(),
+        };
+        check_assigned(assignee)?;
+    }
+
+    if let Some(status) = &request.status {
+        if *status != task.status && !force {
+            check_wip_limit(pool, &config, status).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects adding `adding` tasks when that would take the workspace past
+/// `limits.max_tasks`. Archived tasks still count; only deletion frees room.
+/// `replacing_all` is for imports that clear the workspace first.
+pub async fn check_task_capacity(pool: &DbPool, adding: u32, replacing_all: bool) -> anyhow::Result<()> {
+    let config = database::get_workspace_config(pool).await?;
+    let limit = config.limits.max_tasks;
+    let current = if replacing_all { 0 } else { database::count_stored_tasks(pool).await? };
+
+    if current.saturating_add(adding) > limit {
+        return Err(ValidationError::new(
+            StatusCode::CONFLICT,
+            "TASK_LIMIT_REACHED",
+            format!("The workspace holds {} of {} allowed tasks", current, limit),
+        ).with_details(json!({ "current": current, "limit": limit, "adding": adding })).into());
+    }
+
+    Ok(())
+}
+
+/// Rejects an import document over `limits.max_import_bytes`, or holding
+/// more than `limits.max_import_tasks` tasks, before any of it is imported
+pub async fn check_import_size(pool: &DbPool, bytes: usize, tasks: usize) -> anyhow::Result<()> {
+    let limits = database::get_workspace_config(pool).await?.limits;
+    let (max_bytes, max_tasks) = (limits.max_import_bytes as usize, limits.max_import_tasks as usize);
+
+    let message = if bytes > max_bytes {
+        format!("Imports may be at most {} bytes, not {}", max_bytes, bytes)
+    } else if tasks > max_tasks {
+        format!("Imports may hold at most {} tasks, not {}", max_tasks, tasks)
+    } else {
+        return Ok(());
+    };
+
+    Err(ValidationError::new(StatusCode::PAYLOAD_TOO_LARGE, "IMPORT_TOO_LARGE", message)
+        .with_details(json!({ "bytes": bytes, "maxBytes": max_bytes, "tasks": tasks, "maxTasks": max_tasks }))
+        .into())
+}
+
+/// Rejects a task listing more than `limits.max_relationships` dependencies
+/// or blocks; that many is almost always a data error
+fn check_relationship_limit(config: &WorkspaceConfig, field: &str, task_ids: &[String]) -> Result<(), ValidationError> {
+    let limit = config.limits.max_relationships;
+    if task_ids.len() > limit as usize {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "TOO_MANY_RELATIONSHIPS",
+            format!("A task can have at most {} {}, not {}", limit, field, task_ids.len()),
+        ).with_details(json!({ "field": field, "count": task_ids.len(), "limit": limit })));
+    }
+
+    Ok(())
+}
+
+/// Rejects a new user once `limits.max_users` active users exist
+pub async fn check_user_capacity(pool: &DbPool) -> anyhow::Result<()> {
+    let config = database::get_workspace_config(pool).await?;
+    let limit = config.limits.max_users;
+    let current = database::count_active_users(pool).await?;
+
+    if current >= limit {
+        return Err(ValidationError::new(
+            StatusCode::CONFLICT,
+            "USER_LIMIT_REACHED",
+            format!("The workspace has {} of {} allowed active users", current, limit),
+        ).with_details(json!({ "current": current, "limit": limit })).into());
+    }
+
+    Ok(())
+}
+
+/// Rejects client-supplied task ids that are empty or don't match the
+/// workspace's `task_id_pattern`
+pub fn validate_task_ids(config: &WorkspaceConfig, ids: &[&str]) -> Result<(), ValidationError> {
+    let pattern = match &config.workflow.task_id_pattern {
+        Some(pattern) => Some(compile_task_id_pattern(pattern)?),
+        None => None,
+    };
+
+    let invalid: Vec<&str> = ids.iter()
+        .copied()
+        .filter(|id| id.is_empty() || pattern.as_ref().is_some_and(|pattern| !pattern.is_match(id)))
+        .collect();
+
+    if invalid.is_empty() {
+        return Ok(());
+    }
+
+    Err(ValidationError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "INVALID_TASK_ID",
+        format!("{} task ids don't match the workspace id pattern", invalid.len()),
+    ).with_details(json!({ "invalid": invalid, "pattern": config.workflow.task_id_pattern })))
+}
+
+// Checks a configuration before it is saved
+pub fn validate_workspace_config(config: &WorkspaceConfig) -> Result<(), ValidationError> {
+    if let Some(pattern) = &config.workflow.task_id_pattern {
+        compile_task_id_pattern(pattern)?;
+    }
+
+    check_value_list("available_statuses", &config.available_statuses, TaskStatus::parse)?;
+    check_value_list("available_priorities", &config.available_priorities, Priority::parse)?;
+
+    if let Some(status) = &config.workflow.status_on_criteria_complete {
+        validate_status(config, status).map_err(|e| ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            format!("status_on_criteria_complete: {}", e.message),
+        ))?;
+    }
+
+    check_theme(&config.theme)?;
+
+    let unsupported: Vec<&String> = config.limits.thumbnail_types.iter()
+        .filter(|content_type| !thumbnails::SUPPORTED_CONTENT_TYPES.contains(&content_type.as_str()))
+        .collect();
+    if !unsupported.is_empty() || config.limits.max_thumbnail_bytes == 0 {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            "thumbnail_types must be supported image types and max_thumbnail_bytes above 0",
+        ).with_details(json!({ "invalid": unsupported, "supported": thumbnails::SUPPORTED_CONTENT_TYPES })));
+    }
+
+    let weights = &config.health_score_weights;
+    if [weights.estimated, weights.with_criteria, weights.assigned, weights.on_time].iter().all(|&weight| weight == 0) {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            "health_score_weights must give at least one factor a weight above 0",
+        ));
+    }
+
+    if config.limits.max_import_bytes == 0 || config.limits.max_import_tasks == 0 {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            "max_import_bytes and max_import_tasks must be above 0",
+        ));
+    }
+
+    if config.limits.max_import_bytes > MAX_IMPORT_BYTES_CEILING {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            format!("max_import_bytes may be at most {}", MAX_IMPORT_BYTES_CEILING),
+        ));
+    }
+
+    if config.limits.escalation_age_days == 0 {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            "escalation_age_days must be above 0",
+        ));
+    }
+
+    if !(1..=100).contains(&config.limits.usage_warning_percent) {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            "usage_warning_percent must be between 1 and 100",
+        ));
+    }
+
+    let unknown_types: Vec<&String> = config.workflow.min_description_length.keys()
+        .filter(|task_type| serde_json::from_value::<TaskType>(json!(task_type)).is_err())
+        .collect();
+    if !unknown_types.is_empty() {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            "min_description_length is keyed by task type: Epic, Story, Task or Bug",
+        ).with_details(json!({ "invalid": unknown_types })));
+    }
+
+    if config.timezone.parse::<chrono_tz::Tz>().is_err() {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            format!("{} is not a known IANA timezone", config.timezone),
+        ));
+    }
+
+    if let Some(scale) = &config.workflow.story_point_scale {
+        if scale.is_empty() || scale.iter().any(|points| !points.is_finite() || *points < 0.0) {
+            return Err(ValidationError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "INVALID_CONFIG",
+                "story_point_scale must list at least one value, none of them negative",
+            ));
+        }
+    }
+
+    if !SORT_TIEBREAKERS.contains(&config.sort_tiebreaker.as_str()) {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            format!("sort_tiebreaker must be one of {}", SORT_TIEBREAKERS.join(", ")),
+        ));
+    }
+
+    if database::parse_sort(&config.default_sort).is_none() {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            format!("default_sort {} is not a valid sort", config.default_sort),
+        ).with_details(json!({ "allowedColumns": database::SORT_COLUMNS })));
+    }
+
+    Ok(())
+}
+
+// Every theme color must be `#rgb` or `#rrggbb`
+fn check_theme(theme: &WorkspaceTheme) -> Result<(), ValidationError> {
+    let is_hex = |color: &str| {
+        color.strip_prefix('#')
+            .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+    };
+
+    let colors = std::iter::once(("primaryColor".to_string(), &theme.primary_color))
+        .chain(theme.status_colors.iter().map(|(status, color)| (format!("statusColors.{}", status), color)))
+        .chain(theme.priority_colors.iter().map(|(priority, color)| (format!("priorityColors.{}", priority), color)));
+
+    let mut invalid: Vec<String> = colors
+        .filter(|(_, color)| !is_hex(color))
+        .map(|(field, color)| format!("{} = {:?}", field, color))
+        .collect();
+    invalid.sort();
+    if !invalid.is_empty() {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            "Theme colors must be hex colors like #1f2937 or #fff",
+        ).with_details(json!({ "invalid": invalid })));
+    }
+
+    Ok(())
+}
+
+// A configured list of statuses or priorities must be non-empty, with no
+// blank entries and no value listed twice
+fn check_value_list<T: Eq + std::hash::Hash>(field: &str, values: &[String], parse: fn(&str) -> T) -> Result<(), ValidationError> {
+    let mut seen = std::collections::HashSet::new();
+    for value in values {
+        if value.trim().is_empty() || !seen.insert(parse(value)) {
+            return Err(ValidationError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "INVALID_CONFIG",
+                format!("{} has an empty or repeated value: {:?}", field, value),
+            ));
+        }
+    }
+
+    if seen.is_empty() {
+        return Err(ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            format!("{} must list at least one value", field),
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects priorities that aren't in the workspace's `available_priorities`
+pub fn validate_priority(config: &WorkspaceConfig, priority: &Priority) -> Result<(), ValidationError> {
+    if config.available_priorities.iter().any(|available| Priority::parse(available) == *priority) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "INVALID_PRIORITY",
+        format!("{} is not one of the workspace's priorities", priority.name()),
+    ).with_details(json!({ "allowed": config.available_priorities })))
+}
+
+/// Rejects statuses that aren't in the workspace's `available_statuses`
+pub fn validate_status(config: &WorkspaceConfig, status: &TaskStatus) -> Result<(), ValidationError> {
+    if config.available_statuses.iter().any(|available| TaskStatus::parse(available) == *status) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "INVALID_STATUS",
+        format!("{} is not one of the workspace's statuses", status.label()),
+    ).with_details(json!({ "allowed": config.available_statuses })))
+}
+
+/// Rejects story points off the workspace's `story_point_scale`, or that
+/// aren't whole numbers when there's no scale
+pub fn validate_story_points(config: &WorkspaceConfig, points: Option<f64>) -> Result<(), ValidationError> {
+    let Some(points) = points else {
+        return Ok(());
+    };
+
+    let allowed = match &config.workflow.story_point_scale {
+        Some(scale) => scale.iter().any(|&value| (value - points).abs() < 1e-9),
+        None => points.is_finite() && points.fract() == 0.0,
+    };
+    if allowed {
+        return Ok(());
+    }
+
+    let error = ValidationError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "INVALID_STORY_POINTS",
+        format!("{} is not an allowed story point value", points),
+    );
+    Err(match &config.workflow.story_point_scale {
+        Some(scale) => error.with_details(json!({ "allowed": scale })),
+        None => error,
+    })
+}
+
+/// Checks the `min_points`/`max_points` range of a task listing
+pub fn validate_points_range(params: &TaskQueryParams) -> Result<(), ValidationError> {
+    let invalid = |message: String| {
+        ValidationError::new(StatusCode::BAD_REQUEST, "INVALID_FILTER", message)
+    };
+
+    for (name, value) in [("min_points", params.min_points), ("max_points", params.max_points)] {
+        if value.is_some_and(|points| !points.is_finite()) {
+            return Err(invalid(format!("{} must be a number", name)));
+        }
+        if value.is_some_and(|points| points < 0.0) {
+            return Err(invalid(format!("{} cannot be negative", name)));
+        }
+    }
+
+    if let (Some(min), Some(max)) = (params.min_points, params.max_points) {
+        if min > max {
+            return Err(invalid(format!("min_points ({}) is greater than max_points ({})", min, max)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects listings that ask for a status or type they also exclude
+pub fn validate_exclusions(params: &TaskQueryParams) -> Result<(), ValidationError> {
+    let excluded_statuses = database::normalize_filter(&params.exclude_status, database::normalize_status_filter);
+    let status = params.status.as_deref().map(database::normalize_status_filter);
+    let excluded_types = database::normalize_filter(&params.exclude_type, database::normalize_type_filter);
+    let types = database::normalize_filter(&params.r#type, database::normalize_type_filter);
+
+    let conflict = status.filter(|status| excluded_statuses.contains(status))
+        .or_else(|| (params.include_done == Some(true) && excluded_statuses.iter().any(|status| status == "Done")).then(|| "Done".to_string()))
+        .or_else(|| types.into_iter().find(|task_type| excluded_types.contains(task_type)));
+
+    match conflict {
+        Some(value) => Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "INVALID_FILTER",
+            format!("{} is both included and excluded", value),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Labels are free text, but can't be blank, overly long or contain commas
+/// (the `label` filter splits on them)
+pub fn validate_label(label: &str) -> Result<(), ValidationError> {
+    if label.trim().is_empty() || label.chars().count() > MAX_LABEL_LENGTH || label.contains(',') {
+        return Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "INVALID_LABEL",
+            format!("Labels must be 1 to {} characters without commas", MAX_LABEL_LENGTH),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a `sort` query parameter against the sortable columns
+pub fn validate_sort(sort: Option<&str>) -> Result<(), ValidationError> {
+    match sort {
+        Some(sort) if database::parse_sort(sort).is_none() => Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "INVALID_SORT",
+            format!("{} is not a valid sort; use <column>[:asc|desc]", sort),
+        ).with_details(json!({ "allowedColumns": database::SORT_COLUMNS }))),
+        _ => Ok(()),
+    }
+}
+
+/// Checks a `sort` query parameter on the users list
+pub fn validate_user_sort(sort: Option<&str>) -> Result<(), ValidationError> {
+    match sort {
+        Some(sort) if database::parse_user_sort(sort).is_none() => Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "INVALID_SORT",
+            format!("{} is not a valid sort; use <column>[:asc|desc]", sort),
+        ).with_details(json!({ "allowedColumns": database::USER_SORT_COLUMNS }))),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_webhook(request: &CreateWebhookRequest) -> Result<(), ValidationError> {
+    let url = reqwest::Url::parse(&request.url).ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"));
+    if url.is_none() {
+        return Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "INVALID_URL",
+            format!("{} is not a valid http(s) URL", request.url),
+        ));
+    }
+
+    if request.secret.as_ref().is_some_and(|secret| secret.is_empty()) {
+        return Err(ValidationError::new(StatusCode::BAD_REQUEST, "INVALID_SECRET", "Webhook secret cannot be empty"));
+    }
+
+    let unknown: Vec<&String> = request.events.iter().flatten()
+        .filter(|event| !webhooks::EVENTS.contains(&event.as_str()))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "INVALID_EVENT",
+            "Unknown webhook event",
+        ).with_details(json!({ "unknown": unknown, "allowed": webhooks::EVENTS })));
+    }
+
+    Ok(())
+}
+
+// The pattern has to match the whole id, not just part of it
+fn compile_task_id_pattern(pattern: &str) -> Result<Regex, ValidationError> {
+    Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+        ValidationError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "INVALID_CONFIG",
+            format!("task_id_pattern is not a valid regex: {}", e),
+        )
+    })
+}
+
+async fn check_wip_limit(pool: &DbPool, config: &WorkspaceConfig, status: &TaskStatus) -> anyhow::Result<()> {
+    let status = status.stored_name().to_string();
+    let Some(&limit) = config.limits.wip_limits.get(&status) else {
+        return Ok(());
+    };
+
+    let current = database::count_tasks_with_status(pool, &status).await?;
+    if current >= limit {
+        return Err(ValidationError::new(
+            StatusCode::CONFLICT,
+            "WIP_LIMIT_EXCEEDED",
+            format!("{} already holds {} of {} allowed tasks", status, current, limit),
+        ).with_details(json!({ "status": status, "current": current, "limit": limit })).into());
+    }
+
+    Ok(())
+}
+
+async fn check_unique_title(pool: &DbPool, epic: &str, title: &str, except_id: Option<&str>) -> anyhow::Result<()> {
+    let Some(existing_id) = database::find_task_titled_in_epic(pool, epic, title, except_id).await? else {
+        return Ok(());
+    };
+
+    Err(ValidationError::new(
+        StatusCode::CONFLICT,
+        "DUPLICATE_TITLE",
+        format!("Epic {} already has a task titled {:?}", epic, title.trim()),
+    ).with_details(json!({ "epic": epic, "existingId": existing_id })).into())
+}
+
+fn check_description_length(config: &WorkspaceConfig, task_type: &TaskType, description: &str) -> Result<(), ValidationError> {
+    let task_type = format!("{:?}", task_type);
+    let Some(&min_length) = config.workflow.min_description_length.get(&task_type) else {
+        return Ok(());
+    };
+
+    let length = description.trim().chars().count();
+    if length >= min_length as usize {
+        return Ok(());
+    }
+
+    Err(ValidationError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "DESCRIPTION_TOO_SHORT",
+        format!("A {} needs a description of at least {} characters", task_type, min_length),
+    ).with_details(json!({ "type": task_type, "minLength": min_length, "length": length })))
+}
+
+fn check_assigned(assignee: Option<&str>) -> Result<(), ValidationError> {
+    if assignee.is_some_and(|assignee| !assignee.trim().is_empty()) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "ASSIGNEE_REQUIRED",
+        "Assign the task before moving it to In Progress",
+    ))
+}
+
+fn check_checklist_sources(items: &[ChecklistItem]) -> Result<(), ValidationError> {
+    match items.iter().filter_map(|item| item.source.as_deref()).find(|source| !CHECKLIST_SOURCES.contains(source)) {
+        Some(source) => Err(ValidationError::new(
+            StatusCode::BAD_REQUEST,
+            "INVALID_CHECKLIST_SOURCE",
+            format!("Checklist item source must be one of {}, got {}", CHECKLIST_SOURCES.join(", "), source),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn check_criteria_complete(criteria: &[ChecklistItem]) -> Result<(), ValidationError> {
+    let unmet: Vec<&str> = criteria.iter()
+        .filter(|item| !item.completed)
+        .map(|item| item.text.as_str())
+        .collect();
+
+    if unmet.is_empty() {
+        return Ok(());
+    }
+
+    Err(ValidationError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "CRITERIA_INCOMPLETE",
+        format!("{} acceptance criteria must be completed before moving to Done", unmet.len()),
+    ).with_details(json!({ "unmet": unmet })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+
+    async fn exclusion_error(query: &str) -> Option<&'static str> {
+        validate_exclusions(&multi_query(query).await).err().map(|error| error.code)
+    }
+
+    #[tokio::test]
+    async fn including_and_excluding_the_same_value_is_rejected() {
+        assert_eq!(exclusion_error("status=Done&exclude_status=Done").await, Some("INVALID_FILTER"));
+        assert_eq!(exclusion_error("status=In Progress&exclude_status=InProgress").await, Some("INVALID_FILTER"));
+        assert_eq!(exclusion_error("include_done=true&exclude_status=done").await, Some("INVALID_FILTER"));
+        assert_eq!(exclusion_error("type=Bug&exclude_type=Bug").await, Some("INVALID_FILTER"));
+        assert_eq!(exclusion_error("type=story,bug&exclude_type=BUG").await, Some("INVALID_FILTER"));
+
+        assert_eq!(exclusion_error("status=Todo&exclude_status=Done").await, None);
+        assert_eq!(exclusion_error("type=Bug&exclude_type=Story").await, None);
+    }
+
+    fn titled_in(title: &str, epic: &str) -> CreateTaskRequest {
+        CreateTaskRequest { epic: Some(epic.to_string()), ..new_task(title) }
+    }
+
+    fn error_code(result: anyhow::Result<()>) -> Option<&'static str> {
+        result.err().map(|error| error.downcast_ref::<ValidationError>().expect("validation error").code)
+    }
+
+    #[tokio::test]
+    async fn titles_must_be_unique_within_an_epic_when_configured() {
+        let db = db().await;
+        let login = add_task(&db, titled_in("Login page", "Auth")).await;
+        let signup = add_task(&db, titled_in("Signup page", "Auth")).await;
+
+        assert_eq!(error_code(validate_task_create(&db, &titled_in(" login PAGE ", "Auth")).await), None);
+
+        update_config(&db, |config| config.workflow.unique_title_per_epic = true).await;
+        assert_eq!(error_code(validate_task_create(&db, &titled_in(" login PAGE ", "Auth")).await), Some("DUPLICATE_TITLE"));
+        assert_eq!(error_code(validate_task_create(&db, &titled_in("Login page", "Billing")).await), None);
+        assert_eq!(error_code(validate_task_create(&db, &new_task("Login page")).await), None);
+
+        let rename = UpdateTaskRequest { title: Some("Login Page".to_string()), ..Default::default() };
+        assert_eq!(error_code(validate_task_update(&db, &signup, &rename, false).await), Some("DUPLICATE_TITLE"));
+        assert_eq!(error_code(validate_task_update(&db, &login, &rename, false).await), None);
+
+        let billing = add_task(&db, titled_in("Login page", "Billing")).await;
+        let move_to_auth = UpdateTaskRequest { epic: Some(Some("Auth".to_string())), ..Default::default() };
+        assert_eq!(error_code(validate_task_update(&db, &billing, &move_to_auth, false).await), Some("DUPLICATE_TITLE"));
+    }
+
+    fn status_update(status: TaskStatus) -> UpdateTaskRequest {
+        UpdateTaskRequest { status: Some(status), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn open_criteria_block_done_only_when_required() {
+        let db = db().await;
+        let mut request = new_task("Criteria");
+        request.acceptance_criteria = vec![
+            ChecklistItem { completed: true, ..checklist_item("Met") },
+            checklist_item("Unmet"),
+        ];
+        let task = add_task(&db, request).await;
+
+        assert_eq!(error_code(validate_task_update(&db, &task, &status_update(TaskStatus::Done), false).await), None);
+
+        update_config(&db, |config| config.workflow.require_all_criteria_for_done = true).await;
+        assert_eq!(
+            error_code(validate_task_update(&db, &task, &status_update(TaskStatus::Done), false).await),
+            Some("CRITERIA_INCOMPLETE"),
+        );
+        assert_eq!(error_code(validate_task_update(&db, &task, &status_update(TaskStatus::InReview), false).await), None);
+
+        let completing = UpdateTaskRequest {
+            acceptance_criteria: Some(task.acceptance_criteria.iter()
+                .map(|item| ChecklistItem { completed: true, ..item.clone() })
+                .collect()),
+            ..status_update(TaskStatus::Done)
+        };
+        assert_eq!(error_code(validate_task_update(&db, &task, &completing, false).await), None);
+    }
+
+    #[tokio::test]
+    async fn wip_limits_block_moves_into_a_full_status() {
+        let db = db().await;
+        update_config(&db, |config| { config.limits.wip_limits.insert("InProgress".to_string(), 1); }).await;
+        let started = add_task(&db, new_task("Started")).await;
+        let waiting = add_task(&db, new_task("Waiting")).await;
+        let start = status_update(TaskStatus::InProgress);
+
+        assert_eq!(error_code(validate_task_update(&db, &started, &start, false).await), None);
+        database::update_task(&db, &started.id, &start, None).await.unwrap();
+
+        assert_eq!(error_code(validate_task_update(&db, &waiting, &start, false).await), Some("WIP_LIMIT_EXCEEDED"));
+        assert_eq!(error_code(validate_task_update(&db, &waiting, &start, true).await), None);
+        assert_eq!(error_code(validate_task_update(&db, &waiting, &status_update(TaskStatus::InReview), false).await), None);
+    }
+
+    #[tokio::test]
+    async fn point_ranges_must_be_non_negative_and_ordered() {
+        let range: TaskQueryParams = multi_query("min_points=2&max_points=8").await;
+        assert!(validate_points_range(&range).is_ok());
+
+        for query in ["min_points=-1", "min_points=8&max_points=2"] {
+            let range: TaskQueryParams = multi_query(query).await;
+            assert_eq!(validate_points_range(&range).unwrap_err().code, "INVALID_FILTER", "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn story_points_follow_the_configured_scale() {
+        let db = db().await;
+        let config = database::get_workspace_config(&db).await.unwrap();
+        assert!(validate_story_points(&config, Some(4.0)).is_ok());
+        assert!(validate_story_points(&config, Some(0.5)).is_err());
+
+        update_config(&db, |config| config.workflow.story_point_scale = Some(vec![0.5, 1.0, 2.0, 3.0])).await;
+        let config = database::get_workspace_config(&db).await.unwrap();
+        assert!(validate_story_points(&config, Some(0.5)).is_ok());
+        assert_eq!(validate_story_points(&config, Some(4.0)).unwrap_err().code, "INVALID_STORY_POINTS");
+
+        let task = add_task(&db, CreateTaskRequest { story_points: Some(0.5), ..new_task("Half") }).await;
+        let stored = database::get_task_by_id(&db, &task.id).await.unwrap().unwrap();
+        assert_eq!(stored.story_points, Some(0.5));
+    }
+
+    #[tokio::test]
+    async fn custom_statuses_are_accepted_once_configured_and_counted() {
+        let db = db().await;
+        let blocked = CreateTaskRequest { status: TaskStatus::Custom("Blocked".to_string()), ..new_task("Stuck") };
+        let error = validate_task_create(&db, &blocked).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<ValidationError>().unwrap().code, "INVALID_STATUS");
+
+        update_config(&db, |config| config.available_statuses.push("Blocked".to_string())).await;
+        validate_task_create(&db, &blocked).await.unwrap();
+        let task = add_task(&db, blocked).await;
+
+        assert_eq!(task.status, TaskStatus::Custom("Blocked".to_string()));
+        let analytics = database::compute_analytics(&db).await.unwrap();
+        assert_eq!(analytics.tasks_by_status.get("Blocked"), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn tasks_past_the_relationship_limit_are_rejected() {
+        let db = db().await;
+        update_config(&db, |config| config.limits.max_relationships = 2).await;
+        let mut ids = Vec::new();
+        for title in ["One", "Two", "Three"] {
+            ids.push(add_task(&db, new_task(title)).await.id);
+        }
+
+        let at_limit = CreateTaskRequest { dependencies: ids[..2].to_vec(), ..new_task("Two deps") };
+        assert_eq!(error_code(validate_task_create(&db, &at_limit).await), None);
+        let over_limit = CreateTaskRequest { dependencies: ids.clone(), ..new_task("Three deps") };
+        assert_eq!(error_code(validate_task_create(&db, &over_limit).await), Some("TOO_MANY_RELATIONSHIPS"));
+        let over_limit = CreateTaskRequest { blocks: ids, ..new_task("Blocks three") };
+        assert_eq!(error_code(validate_task_create(&db, &over_limit).await), Some("TOO_MANY_RELATIONSHIPS"));
+    }
+
+    #[tokio::test]
+    async fn starting_work_needs_an_assignee_when_configured() {
+        let db = db().await;
+        let unassigned = add_task(&db, new_task("Nobody's")).await;
+        let assigned = add_task(&db, CreateTaskRequest { assignee: Some("alice".to_string()), ..new_task("Alice's") }).await;
+        let start = status_update(TaskStatus::InProgress);
+
+        assert_eq!(error_code(validate_task_update(&db, &unassigned, &start, false).await), None);
+
+        update_config(&db, |config| config.workflow.require_assignee_for_in_progress = true).await;
+        assert_eq!(error_code(validate_task_update(&db, &unassigned, &start, false).await), Some("ASSIGNEE_REQUIRED"));
+        assert_eq!(error_code(validate_task_update(&db, &assigned, &start, false).await), None);
+
+        let assigning = UpdateTaskRequest { assignee: Some(Some("bob".to_string())), ..status_update(TaskStatus::InProgress) };
+        assert_eq!(error_code(validate_task_update(&db, &unassigned, &assigning, false).await), None);
+        let unassigning = UpdateTaskRequest { assignee: Some(None), ..status_update(TaskStatus::InProgress) };
+        assert_eq!(error_code(validate_task_update(&db, &assigned, &unassigning, false).await), Some("ASSIGNEE_REQUIRED"));
+    }
+
+    #[tokio::test]
+    async fn stories_need_the_configured_description_length() {
+        let db = db().await;
+        update_config(&db, |config| { config.workflow.min_description_length.insert("Story".to_string(), 20); }).await;
+        let described = |task_type: TaskType, description: &str| CreateTaskRequest {
+            r#type: task_type,
+            description: description.to_string(),
+            ..new_task("Described")
+        };
+
+        assert_eq!(error_code(validate_task_create(&db, &described(TaskType::Story, "Too short")).await), Some("DESCRIPTION_TOO_SHORT"));
+        assert_eq!(error_code(validate_task_create(&db, &described(TaskType::Story, "Long enough to explain the story")).await), None);
+        assert_eq!(error_code(validate_task_create(&db, &described(TaskType::Task, "Too short")).await), None);
+
+        let story = add_task(&db, described(TaskType::Story, "Long enough to explain the story")).await;
+        let shortening = UpdateTaskRequest { description: Some("Short".to_string()), ..Default::default() };
+        assert_eq!(error_code(validate_task_update(&db, &story, &shortening, false).await), Some("DESCRIPTION_TOO_SHORT"));
+    }
+}
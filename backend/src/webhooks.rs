@@ -0,0 +1,376 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+
+use crate::database::{self, DbPool};
+use crate::models::{Webhook, WebhookDelivery};
+
+/// Events a webhook can subscribe to
+pub const EVENTS: &[&str] = &["task.created", "task.updated", "task.deleted"];
+
+pub const SIGNATURE_HEADER: &str = "X-Taskdown-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Taskdown-Timestamp";
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How often the worker looks for retries that have come due
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const DELIVERY_BATCH: u32 = 50;
+
+/// Attempts per delivery before it is dead-lettered, and the wait before the
+/// first retry, which doubles after each further failure
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+}
+
+impl RetryPolicy {
+    fn from_env() -> Self {
+        let max_attempts = std::env::var("WEBHOOK_MAX_ATTEMPTS").ok()
+            .and_then(|attempts| attempts.parse().ok())
+            .filter(|&attempts| attempts > 0)
+            .unwrap_or(5);
+        let base_delay = std::env::var("WEBHOOK_RETRY_BASE_SECONDS").ok()
+            .and_then(|seconds| seconds.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(30));
+
+        Self { max_attempts, base_delay }
+    }
+
+    fn delay_after(&self, attempts: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+    }
+}
+
+static QUEUE: OnceLock<mpsc::UnboundedSender<TaskEvent>> = OnceLock::new();
+
+#[derive(Debug)]
+struct TaskEvent {
+    event: &'static str,
+    task_id: String,
+    /// Watchers read before the task was deleted; looked up at dispatch otherwise
+    watchers: Option<Vec<String>>,
+}
+
+/// Starts the background dispatcher and delivery worker. Events are queued
+/// so requests never wait on a webhook receiver, and every delivery is
+/// recorded in `webhook_deliveries` so failed ones are retried, with
+/// exponential backoff, across restarts.
+pub fn init(pool: DbPool) {
+    let client = match reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Failed to set up webhook client: {}", e);
+            return;
+        }
+    };
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    if QUEUE.set(sender).is_ok() {
+        let wake = Arc::new(Notify::new());
+        tokio::spawn(run_dispatcher(pool.clone(), receiver, wake.clone()));
+        tokio::spawn(run_worker(pool, client, RetryPolicy::from_env(), wake));
+    }
+}
+
+pub fn task_created(task_id: &str) {
+    enqueue("task.created", task_id, None);
+}
+
+pub fn task_updated(task_id: &str) {
+    enqueue("task.updated", task_id, None);
+}
+
+/// `watchers` as they were before the delete removed them with the task
+pub fn task_deleted(task_id: &str, watchers: Vec<String>) {
+    enqueue("task.deleted", task_id, Some(watchers));
+}
+
+fn enqueue(event: &'static str, task_id: &str, watchers: Option<Vec<String>>) {
+    if let Some(queue) = QUEUE.get() {
+        let event = TaskEvent { event, task_id: task_id.to_string(), watchers };
+        if queue.send(event).is_err() {
+            tracing::error!("Webhook queue is closed");
+        }
+    }
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `<timestamp>.<body>` keyed with the
+/// webhook's secret. Receivers recompute it from the timestamp header and the
+/// raw body, and reject stale timestamps so captured deliveries can't be replayed.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+
+    let digest: String = mac.finalize().into_bytes().iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+async fn run_dispatcher(pool: DbPool, mut receiver: mpsc::UnboundedReceiver<TaskEvent>, wake: Arc<Notify>) {
+    while let Some(event) = receiver.recv().await {
+        match database::get_workspace_config(&pool).await {
+            Ok(config) if !config.features.webhooks => continue,
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!("Failed to get workspace config: {}", e);
+                continue;
+            }
+        }
+
+        let webhooks = match database::get_webhooks_for_event(&pool, event.event).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                tracing::error!("Failed to load webhooks for {}: {}", event.event, e);
+                continue;
+            }
+        };
+        if webhooks.is_empty() {
+            continue;
+        }
+
+        let body = match payload(&pool, &event).await {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("Failed to build {} payload for task {}: {}", event.event, event.task_id, e);
+                continue;
+            }
+        };
+
+        for webhook in &webhooks {
+            if let Err(e) = database::enqueue_webhook_delivery(&pool, &webhook.id, event.event, &body).await {
+                tracing::error!("Failed to queue {} for webhook {}: {}", event.event, webhook.url, e);
+            }
+        }
+        wake.notify_one();
+    }
+}
+
+/// The JSON body delivered for `event`. Deleted tasks are gone by now, so
+/// their payload carries only the id and the watchers read before the delete.
+async fn payload(pool: &DbPool, event: &TaskEvent) -> anyhow::Result<String> {
+    let task = database::get_task_by_id(pool, &event.task_id).await?;
+    let watchers = match &event.watchers {
+        Some(watchers) => watchers.clone(),
+        None => database::get_task_watchers(pool, &event.task_id).await?,
+    };
+
+    Ok(serde_json::json!({
+        "event": event.event,
+        "timestamp": Utc::now(),
+        "data": {
+            "taskId": event.task_id,
+            "task": task,
+            "watchers": watchers,
+        },
+    }).to_string())
+}
+
+async fn run_worker(pool: DbPool, client: reqwest::Client, policy: RetryPolicy, wake: Arc<Notify>) {
+    loop {
+        match database::get_due_webhook_deliveries(&pool, DELIVERY_BATCH).await {
+            Ok(deliveries) => {
+                for delivery in &deliveries {
+                    attempt(&pool, &client, policy, delivery).await;
+                }
+            }
+            Err(e) => tracing::error!("Failed to load pending webhook deliveries: {}", e),
+        }
+
+        let _ = tokio::time::timeout(POLL_INTERVAL, wake.notified()).await;
+    }
+}
+
+async fn attempt(pool: &DbPool, client: &reqwest::Client, policy: RetryPolicy, delivery: &WebhookDelivery) {
+    let attempts = delivery.attempts + 1;
+
+    let result = match database::get_webhook(pool, &delivery.webhook_id).await {
+        Ok(Some(webhook)) => send(client, &webhook, &delivery.event, &delivery.payload.to_string()).await,
+        Ok(None) => Err("webhook no longer exists".to_string()),
+        Err(e) => {
+            tracing::error!("Failed to load webhook {}: {}", delivery.webhook_id, e);
+            return;
+        }
+    };
+
+    let recorded = match result {
+        Ok(()) => database::mark_webhook_delivered(pool, &delivery.id, attempts).await,
+        Err(error) => {
+            let retry_at = (attempts < policy.max_attempts)
+                .then(|| Utc::now() + policy.delay_after(attempts));
+            match retry_at {
+                Some(at) => tracing::warn!("Webhook delivery {} failed ({}), retrying at {}", delivery.id, error, at),
+                None => tracing::warn!("Webhook delivery {} failed ({}) after {} attempts, giving up", delivery.id, error, attempts),
+            }
+            database::mark_webhook_failed(pool, &delivery.id, attempts, &error, retry_at).await
+        }
+    };
+
+    if let Err(e) = recorded {
+        tracing::error!("Failed to record webhook delivery {}: {}", delivery.id, e);
+    }
+}
+
+async fn send(client: &reqwest::Client, webhook: &Webhook, event: &str, body: &str) -> Result<(), String> {
+    let timestamp = Utc::now().timestamp();
+    let response = client.post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Taskdown-Event", event)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, sign(&webhook.secret, timestamp, body))
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("receiver returned {}", response.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use std::sync::Mutex;
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    /// A local receiver answering every delivery with `status`, and what it received
+    async fn receiver(status: StatusCode) -> (String, Received) {
+        let received: Received = Arc::default();
+        let log = received.clone();
+        let app = axum::Router::new().route("/hook", axum::routing::post(move |headers: HeaderMap, body: String| {
+            let log = log.clone();
+            async move {
+                log.lock().unwrap().push((headers, body));
+                status
+            }
+        }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    fn webhook(url: &str, secret: &str) -> Webhook {
+        Webhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            secret: secret.to_string(),
+            events: vec![],
+            active: true,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn hex_decode(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn deliveries_carry_a_signature_that_verifies_against_the_payload() {
+        let (url, received) = receiver(StatusCode::OK).await;
+        let client = reqwest::Client::new();
+
+        send(&client, &webhook(&url, "s3cret"), "task.created", r#"{"event":"task.created"}"#).await.unwrap();
+
+        let (headers, body) = received.lock().unwrap().pop().unwrap();
+        assert_eq!(body, r#"{"event":"task.created"}"#);
+        let timestamp = headers[TIMESTAMP_HEADER].to_str().unwrap();
+        let signature = headers[SIGNATURE_HEADER].to_str().unwrap().strip_prefix("sha256=").unwrap();
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(format!("{}.{}", timestamp, body).as_bytes());
+        assert!(mac.clone().verify_slice(&hex_decode(signature)).is_ok());
+
+        let mut forged = Hmac::<Sha256>::new_from_slice(b"other").unwrap();
+        forged.update(format!("{}.{}", timestamp, body).as_bytes());
+        assert!(forged.verify_slice(&hex_decode(signature)).is_err());
+    }
+
+    #[tokio::test]
+    async fn payloads_carry_the_task_watchers() {
+        let db = db().await;
+        let task = add_task(&db, new_task("Watched")).await;
+        database::add_task_watcher(&db, &task.id, "alice").await.unwrap();
+        database::add_task_watcher(&db, &task.id, "bob").await.unwrap();
+
+        let updated = TaskEvent { event: "task.updated", task_id: task.id.clone(), watchers: None };
+        let body: serde_json::Value = serde_json::from_str(&payload(&db, &updated).await.unwrap()).unwrap();
+        assert_eq!(body["data"]["watchers"], serde_json::json!(["alice", "bob"]));
+        assert_eq!(body["data"]["task"]["title"], "Watched");
+
+        database::delete_task(&db, &task.id).await.unwrap();
+        let deleted = TaskEvent { event: "task.deleted", task_id: task.id.clone(), watchers: Some(vec!["alice".into()]) };
+        let body: serde_json::Value = serde_json::from_str(&payload(&db, &deleted).await.unwrap()).unwrap();
+        assert_eq!(body["data"]["watchers"], serde_json::json!(["alice"]));
+        assert!(body["data"]["task"].is_null());
+    }
+
+    async fn only_delivery(pool: &DbPool) -> WebhookDelivery {
+        let mut deliveries = database::get_webhook_deliveries(pool, None, None, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        deliveries.pop().unwrap()
+    }
+
+    #[tokio::test]
+    async fn deliveries_are_dead_lettered_after_max_attempts() {
+        let db = db().await;
+        let (url, received) = receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let hook = webhook(&url, "s3cret");
+        database::create_webhook(&db, &hook).await.unwrap();
+        database::enqueue_webhook_delivery(&db, &hook.id, "task.created", r#"{"event":"task.created"}"#).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let policy = RetryPolicy { max_attempts: 3, base_delay: Duration::ZERO };
+        for expected_attempts in 1..=3 {
+            let due = database::get_due_webhook_deliveries(&db, DELIVERY_BATCH).await.unwrap();
+            assert_eq!(due.len(), 1);
+            attempt(&db, &client, policy, &due[0]).await;
+
+            let delivery = only_delivery(&db).await;
+            assert_eq!(delivery.attempts, expected_attempts);
+            assert_eq!(delivery.status, if expected_attempts < 3 { "pending" } else { "dead" });
+            assert_eq!(delivery.last_error.as_deref(), Some("receiver returned 500 Internal Server Error"));
+        }
+
+        assert!(database::get_due_webhook_deliveries(&db, DELIVERY_BATCH).await.unwrap().is_empty());
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn successful_deliveries_are_marked_delivered() {
+        let db = db().await;
+        let (url, _) = receiver(StatusCode::OK).await;
+        let hook = webhook(&url, "s3cret");
+        database::create_webhook(&db, &hook).await.unwrap();
+        database::enqueue_webhook_delivery(&db, &hook.id, "task.created", "{}").await.unwrap();
+
+        let due = database::get_due_webhook_deliveries(&db, DELIVERY_BATCH).await.unwrap();
+        attempt(&db, &reqwest::Client::new(), RetryPolicy { max_attempts: 3, base_delay: Duration::ZERO }, &due[0]).await;
+
+        let delivery = only_delivery(&db).await;
+        assert_eq!((delivery.status.as_str(), delivery.attempts), ("delivered", 1));
+        assert!(delivery.next_attempt_at.is_none());
+    }
+
+    #[test]
+    fn retry_delays_double_after_each_failure() {
+        let policy = RetryPolicy { max_attempts: 5, base_delay: Duration::from_secs(30) };
+        let delays: Vec<u64> = (1..=4).map(|attempts| policy.delay_after(attempts).as_secs()).collect();
+        assert_eq!(delays, [30, 60, 120, 240]);
+    }
+}
@@ -26,6 +26,7 @@ CREATE TABLE IF NOT EXISTS checklist_items (
     item_type TEXT NOT NULL, -- 'acceptance_criteria' or 'technical_tasks'
     text TEXT NOT NULL,
     completed BOOLEAN NOT NULL DEFAULT FALSE,
+    source TEXT NOT NULL DEFAULT 'human', -- 'human' or 'ai'
     created_at TEXT NOT NULL,
     FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
 );
@@ -1,6 +1,18 @@
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use worker::*;
 
+use crate::database::{Database, AI_API_KEY_SECRET};
+use crate::models::{AIConfigValidation, ChecklistItem, Task, TaskListQuery, UpdateTaskRequest};
+
+/// Provider calls a batch AI request keeps in flight when `AI_MAX_CONCURRENCY` is unset
+pub const DEFAULT_AI_MAX_CONCURRENCY: usize = 4;
+
+/// Characters of task text (title, description, criteria...) sent to the provider
+/// when `AI_MAX_INPUT_CHARS` is unset
+pub const DEFAULT_AI_MAX_INPUT_CHARS: usize = 12_000;
+
 #[derive(Deserialize)]
 pub struct AITaskGenerationRequest {
     pub title: String,
@@ -10,6 +22,16 @@ pub struct AITaskGenerationRequest {
     pub epic: Option<String>,
 }
 
+impl AITaskGenerationRequest {
+    /// Cuts the input down to `budget` characters, returning whether anything was cut
+    pub fn truncate_input(&mut self, budget: usize) -> bool {
+        let mut fields = vec![&mut self.title];
+        fields.extend(self.epic.as_mut());
+        fields.extend(self.context.as_mut());
+        truncate_to_budget(&mut fields, budget)
+    }
+}
+
 #[derive(Serialize)]
 pub struct AITaskGenerationResponse {
     #[serde(rename = "suggestedTitle")]
@@ -38,6 +60,20 @@ pub struct AIAcceptanceCriteriaRequest {
     pub existing_criteria: Option<Vec<String>>,
 }
 
+impl AIAcceptanceCriteriaRequest {
+    /// Cuts the input down to `budget` characters, returning whether anything was cut
+    pub fn truncate_input(&mut self, budget: usize) -> bool {
+        let mut fields = vec![&mut self.title, &mut self.description];
+        fields.extend(self.existing_criteria.iter_mut().flatten());
+        let truncated = truncate_to_budget(&mut fields, budget);
+
+        if let Some(criteria) = self.existing_criteria.as_mut() {
+            criteria.retain(|criterion| !criterion.is_empty());
+        }
+        truncated
+    }
+}
+
 #[derive(Deserialize)]
 pub struct AIStoryPointEstimationRequest {
     pub title: String,
@@ -50,6 +86,60 @@ pub struct AIStoryPointEstimationRequest {
     pub task_type: String,
 }
 
+impl AIStoryPointEstimationRequest {
+    /// Characters of task text the estimate is based on
+    pub fn input_len(&self) -> usize {
+        [&self.title, &self.description].into_iter()
+            .chain(&self.acceptance_criteria)
+            .chain(&self.technical_tasks)
+            .map(|text| text.chars().count())
+            .sum()
+    }
+}
+
+impl From<&Task> for AIStoryPointEstimationRequest {
+    fn from(task: &Task) -> Self {
+        let texts = |items: &[ChecklistItem]| items.iter().map(|item| item.text.clone()).collect();
+        Self {
+            title: task.title.clone(),
+            description: task.description.clone(),
+            acceptance_criteria: texts(&task.acceptance_criteria),
+            technical_tasks: texts(&task.technical_tasks),
+            task_type: format!("{:?}", task.r#type),
+        }
+    }
+}
+
+/// Either `taskIds` or a `filter` selecting the tasks to estimate
+#[derive(Deserialize)]
+pub struct AIEstimateBacklogRequest {
+    #[serde(rename = "taskIds")]
+    pub task_ids: Option<Vec<String>>,
+    pub filter: Option<TaskListQuery>,
+    /// Save the estimates on tasks that have no story points yet
+    #[serde(default)]
+    pub apply: bool,
+}
+
+#[derive(Serialize)]
+pub struct BacklogEstimate {
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+    /// None when the task couldn't be estimated; see `error`
+    pub proposed: Option<u32>,
+    /// Whether `proposed` was saved on the task
+    pub applied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AIEstimateBacklogResponse {
+    pub estimates: Vec<BacklogEstimate>,
+    /// How many estimates were saved
+    pub applied: u32,
+}
+
 #[derive(Deserialize)]
 pub struct AIDependencyAnalysisRequest {
     pub task: TaskForAnalysis,
@@ -103,6 +193,46 @@ pub struct AISprintPlanningResponse {
     pub warnings: Option<Vec<String>>,
 }
 
+#[derive(Deserialize)]
+pub struct AIFindDuplicatesRequest {
+    pub tasks: Vec<DuplicateCandidate>,
+}
+
+impl AIFindDuplicatesRequest {
+    /// Cuts the input down to `budget` characters, returning whether anything
+    /// was cut. Titles are kept whole before any description.
+    pub fn truncate_input(&mut self, budget: usize) -> bool {
+        let (titles, descriptions): (Vec<_>, Vec<_>) = self.tasks.iter_mut()
+            .map(|task| (&mut task.title, task.description.as_mut()))
+            .unzip();
+        let mut fields: Vec<&mut String> = titles.into_iter()
+            .chain(descriptions.into_iter().flatten())
+            .collect();
+        truncate_to_budget(&mut fields, budget)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateCandidate {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateCluster {
+    #[serde(rename = "taskIds")]
+    pub task_ids: Vec<String>,
+    pub rationale: String,
+}
+
+#[derive(Serialize)]
+pub struct AIFindDuplicatesResponse {
+    pub clusters: Vec<DuplicateCluster>,
+    /// "ai", or "title_match" when no provider is configured
+    pub method: String,
+}
+
 #[derive(Deserialize)]
 pub struct AIConfig {
     pub enabled: bool,
@@ -121,6 +251,8 @@ pub struct OpenAIProvider {
     api_key: String,
     endpoint: String,
     model: String,
+    max_tokens: u32,
+    temperature: f32,
 }
 
 impl OpenAIProvider {
@@ -129,7 +261,38 @@ impl OpenAIProvider {
             api_key,
             endpoint: "https://api.openai.com/v1/chat/completions".to_string(),
             model: model.unwrap_or_else(|| "gpt-4".to_string()),
+            max_tokens: 1000,
+            temperature: 0.7,
+        }
+    }
+
+    /// A provider for a candidate config, before it is saved; only `openai`
+    /// (or any OpenAI-compatible `endpoint`) is supported
+    pub fn from_config(config: &AIConfig) -> Result<Self> {
+        if !config.provider.eq_ignore_ascii_case("openai") {
+            return Err(Error::from(format!("Unsupported AI provider: {}", config.provider)));
         }
+
+        let mut provider = Self::new(config.api_key.clone(), config.model.clone());
+        if let Some(endpoint) = &config.endpoint {
+            provider.endpoint = endpoint.clone();
+        }
+        if let Some(max_tokens) = config.max_tokens {
+            provider.max_tokens = max_tokens;
+        }
+        if let Some(temperature) = config.temperature {
+            provider.temperature = temperature;
+        }
+        Ok(provider)
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Makes the smallest useful call to confirm the key and model are accepted
+    pub async fn check_credentials(&self) -> Result<()> {
+        self.call_openai("Reply with OK").await.map(|_| ())
     }
 
     pub async fn generate_task_details(&self, request: &AITaskGenerationRequest) -> Result<AITaskGenerationResponse> {
@@ -205,6 +368,28 @@ impl OpenAIProvider {
         self.parse_number_response(&response)
     }
 
+    pub async fn find_duplicates(&self, request: &AIFindDuplicatesRequest) -> Result<Vec<DuplicateCluster>> {
+        let task_list = request.tasks.iter()
+            .map(|task| format!("- {}: {} | {}", task.id, task.title, task.description.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let prompt = format!(
+            "Find tasks in this backlog that describe the same work, even if worded differently. \
+             Each line is 'id: title | description'. \
+             \
+             {} \
+             \
+             Respond in JSON format as an array of groups of likely duplicates, leaving out tasks \
+             with no duplicate: \
+             [{{\"taskIds\": [\"id1\", \"id2\"], \"rationale\": \"why these are the same work\"}}]",
+            task_list
+        );
+
+        let response = self.call_openai(&prompt).await?;
+        self.parse_duplicates_response(&response, request)
+    }
+
     async fn call_openai(&self, prompt: &str) -> Result<String> {
         let headers = Headers::new();
         headers.set("Authorization", &format!("Bearer {}", self.api_key))?;
@@ -218,8 +403,8 @@ impl OpenAIProvider {
                     "content": prompt
                 }
             ],
-            "max_tokens": 1000,
-            "temperature": 0.7
+            "max_tokens": self.max_tokens,
+            "temperature": self.temperature
         });
 
         let mut init = RequestInit::new();
@@ -231,7 +416,13 @@ impl OpenAIProvider {
         let mut response = Fetch::Request(request).send().await?;
 
         if response.status_code() != 200 {
-            return Err(Error::from(format!("OpenAI API error: {}", response.status_code())));
+            // The provider's message can echo part of the key back
+            let message = response.json::<serde_json::Value>().await.ok()
+                .and_then(|body| body["error"]["message"].as_str().map(|message| message.replace(&self.api_key, &redact_key(&self.api_key))));
+            return Err(Error::from(match message {
+                Some(message) => format!("OpenAI API error: {}: {}", response.status_code(), message),
+                None => format!("OpenAI API error: {}", response.status_code()),
+            }));
         }
 
         let response_text = response.text().await?;
@@ -267,6 +458,24 @@ impl OpenAIProvider {
         })
     }
 
+    // Ids the model made up are dropped, as are groups left with fewer than two tasks
+    fn parse_duplicates_response(&self, response: &str, request: &AIFindDuplicatesRequest) -> Result<Vec<DuplicateCluster>> {
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(response)
+            .map_err(|e| Error::from(format!("Failed to parse duplicates response: {}", e)))?;
+
+        let known = |id: &str| request.tasks.iter().any(|task| task.id == id);
+        Ok(parsed.iter()
+            .map(|group| DuplicateCluster {
+                task_ids: group["taskIds"]
+                    .as_array()
+                    .map(|ids| ids.iter().filter_map(|id| id.as_str()).filter(|id| known(id)).map(|id| id.to_string()).collect())
+                    .unwrap_or_default(),
+                rationale: group["rationale"].as_str().unwrap_or("").to_string(),
+            })
+            .filter(|cluster| cluster.task_ids.len() > 1)
+            .collect())
+    }
+
     fn parse_array_response(&self, response: &str) -> Result<Vec<String>> {
         let parsed: Vec<String> = serde_json::from_str(response)
             .map_err(|e| Error::from(format!("Failed to parse array response: {}", e)))?;
@@ -280,9 +489,26 @@ impl OpenAIProvider {
     }
 }
 
-pub async fn get_ai_provider(env: &Env) -> Result<Option<OpenAIProvider>> {
-    // Get AI configuration from environment or database
-    // For now, we'll check for environment variables
+/// The provider from the workspace's stored `ai` config, or from the
+/// `AI_ENABLED`/`AI_API_KEY`/`AI_MODEL` environment when none is stored.
+/// The stored config's key lives in the secrets table, falling back to
+/// `AI_API_KEY`.
+pub async fn get_ai_provider(env: &Env, db: &Database) -> Result<Option<OpenAIProvider>> {
+    if let Some(stored) = db.get_workspace_config().await?.ai {
+        if !stored.enabled {
+            return Ok(None);
+        }
+        let api_key = match db.get_secret(AI_API_KEY_SECRET).await? {
+            Some(api_key) => api_key,
+            None => match env.secret("AI_API_KEY") {
+                Ok(api_key) => api_key.to_string(),
+                Err(_) => return Ok(None),
+            },
+        };
+
+        return stored_provider(stored, api_key).map(Some);
+    }
+
     if let (Ok(api_key), Ok(enabled)) = (
         env.secret("AI_API_KEY"),
         env.var("AI_ENABLED")
@@ -300,4 +526,439 @@ pub async fn get_ai_provider(env: &Env) -> Result<Option<OpenAIProvider>> {
     }
 
     Ok(None)
-}
\ No newline at end of file
+}
+
+// The provider for a stored workspace `ai` config, with the key it is kept
+// apart from
+fn stored_provider(stored: crate::models::AIConfig, api_key: String) -> Result<OpenAIProvider> {
+    OpenAIProvider::from_config(&AIConfig {
+        enabled: stored.enabled,
+        provider: stored.provider,
+        api_key,
+        endpoint: stored.endpoint,
+        model: stored.model,
+        max_tokens: stored.max_tokens,
+        temperature: stored.temperature,
+    })
+}
+
+/// An API key safe to show or log: only its last four characters are kept
+pub fn redact_key(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    format!("****{}", chars[chars.len() - 4..].iter().collect::<String>())
+}
+
+pub fn get_ai_max_concurrency(env: &Env) -> usize {
+    let concurrency = env.var("AI_MAX_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.to_string().parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_AI_MAX_CONCURRENCY);
+
+    console_log!("AI batch concurrency: {}", concurrency);
+    concurrency
+}
+
+pub fn get_ai_max_input_chars(env: &Env) -> usize {
+    env.var("AI_MAX_INPUT_CHARS")
+        .ok()
+        .and_then(|v| v.to_string().parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_AI_MAX_INPUT_CHARS)
+}
+
+/// Groups tasks whose titles match once case, punctuation and spacing are
+/// ignored; the fallback for `find-duplicates` without an AI provider
+pub fn find_title_duplicates(tasks: &[DuplicateCandidate]) -> Vec<DuplicateCluster> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for task in tasks {
+        let title = normalize_title(&task.title);
+        if title.is_empty() {
+            continue;
+        }
+
+        match groups.iter_mut().find(|(normalized, _)| *normalized == title) {
+            Some((_, task_ids)) => task_ids.push(task.id.clone()),
+            None => groups.push((title, vec![task.id.clone()])),
+        }
+    }
+
+    groups.into_iter()
+        .filter(|(_, task_ids)| task_ids.len() > 1)
+        .map(|(title, task_ids)| DuplicateCluster {
+            task_ids,
+            rationale: format!("Titles match when case, punctuation and spacing are ignored: \"{}\"", title),
+        })
+        .collect()
+}
+
+// Lowercase words of letters and digits, single-spaced
+fn normalize_title(title: &str) -> String {
+    title.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Shortens `fields` so that together they hold at most `budget` characters.
+// Earlier fields are kept whole first, so later (usually longer, less
+// important) text is what gets cut. Returns whether anything was cut.
+pub fn truncate_to_budget(fields: &mut [&mut String], budget: usize) -> bool {
+    let mut remaining = budget;
+    let mut truncated = false;
+
+    for field in fields.iter_mut() {
+        if let Some((end, _)) = field.char_indices().nth(remaining) {
+            field.truncate(end);
+            truncated = true;
+        }
+        remaining -= field.chars().count();
+    }
+
+    truncated
+}
+
+// Anything that can suggest acceptance criteria: the OpenAI provider, or a
+// canned one in tests
+pub trait CriteriaSource {
+    async fn generate_acceptance_criteria(&self, request: &AIAcceptanceCriteriaRequest) -> Result<Vec<String>>;
+}
+
+impl CriteriaSource for OpenAIProvider {
+    async fn generate_acceptance_criteria(&self, request: &AIAcceptanceCriteriaRequest) -> Result<Vec<String>> {
+        OpenAIProvider::generate_acceptance_criteria(self, request).await
+    }
+}
+
+// Anything whose credentials can be checked: the OpenAI provider, or a
+// canned one in tests
+pub trait CredentialsCheck {
+    fn model(&self) -> &str;
+    async fn check_credentials(&self) -> Result<()>;
+}
+
+impl CredentialsCheck for OpenAIProvider {
+    fn model(&self) -> &str {
+        OpenAIProvider::model(self)
+    }
+
+    async fn check_credentials(&self) -> Result<()> {
+        OpenAIProvider::check_credentials(self).await
+    }
+}
+
+// Checks the provider built from a candidate `config` and reports whether it
+// works, with the key redacted; nothing is saved
+pub async fn config_validation(config: &AIConfig, provider: Result<impl CredentialsCheck>) -> AIConfigValidation {
+    let (model, error) = match provider {
+        Ok(provider) => (Some(provider.model().to_string()), provider.check_credentials().await.err().map(|e| e.to_string())),
+        Err(e) => (config.model.clone(), Some(e.to_string())),
+    };
+
+    AIConfigValidation {
+        valid: error.is_none(),
+        provider: config.provider.clone(),
+        model,
+        api_key: redact_key(&config.api_key),
+        error,
+    }
+}
+
+// Asks `source` for acceptance criteria and builds the update attaching them
+// to the task, with `source` set to `ai`
+pub async fn enrichment_update(source: &impl CriteriaSource, request: &AIAcceptanceCriteriaRequest) -> Result<UpdateTaskRequest> {
+    let criteria = source.generate_acceptance_criteria(request).await?;
+    Ok(UpdateTaskRequest {
+        acceptance_criteria: Some(criteria.into_iter().map(|text| ChecklistItem {
+            id: Some(uuid::Uuid::new_v4().to_string()),
+            text,
+            completed: false,
+            source: Some("ai".to_string()),
+        }).collect()),
+        ..Default::default()
+    })
+}
+
+// Anything that can estimate story points: the OpenAI provider, or a canned
+// one in tests
+pub trait PointsEstimator {
+    async fn estimate_story_points(&self, request: &AIStoryPointEstimationRequest) -> Result<u32>;
+}
+
+impl PointsEstimator for OpenAIProvider {
+    async fn estimate_story_points(&self, request: &AIStoryPointEstimationRequest) -> Result<u32> {
+        OpenAIProvider::estimate_story_points(self, request).await
+    }
+}
+
+// Asks `estimator` for every task's points, at most `max_in_flight` at once,
+// pairing each estimate with the points to save: only with `apply`, and only
+// on tasks without story points. As with a single estimate, oversized tasks
+// are skipped rather than cut.
+pub async fn propose_estimates(
+    tasks: &[Task],
+    estimator: &impl PointsEstimator,
+    max_in_flight: usize,
+    max_input: usize,
+    apply: bool,
+) -> Vec<(BacklogEstimate, Option<u32>)> {
+    let proposals = run_bounded(tasks, max_in_flight, |task| async move {
+        let request = AIStoryPointEstimationRequest::from(task);
+        let input_len = request.input_len();
+        if input_len > max_input {
+            return Err(format!("Task text is {} characters; the limit is {}", input_len, max_input));
+        }
+        estimator.estimate_story_points(&request).await.map_err(|e| format!("AI estimation failed: {}", e))
+    })
+    .await;
+
+    tasks.iter().zip(proposals).map(|(task, proposal)| {
+        let (proposed, error) = match proposal {
+            Ok(points) => (Some(points), None),
+            Err(e) => (None, Some(e)),
+        };
+        let to_save = proposed.filter(|_| apply && task.story_points.is_none());
+        (BacklogEstimate { task_id: task.id.clone(), proposed, applied: false, error }, to_save)
+    })
+    .collect()
+}
+
+// Runs `f` over every input with at most `max_in_flight` calls pending at once,
+// returning the results in input order
+pub async fn run_bounded<I, T, F, Fut>(inputs: I, max_in_flight: usize, f: F) -> Vec<T>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = T>,
+{
+    stream::iter(inputs)
+        .map(f)
+        .buffered(max_in_flight.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CannedCriteria(Vec<&'static str>);
+
+    impl CriteriaSource for CannedCriteria {
+        async fn generate_acceptance_criteria(&self, _request: &AIAcceptanceCriteriaRequest) -> Result<Vec<String>> {
+            Ok(self.0.iter().map(|text| text.to_string()).collect())
+        }
+    }
+
+    #[test]
+    fn enrichment_attaches_generated_criteria() {
+        let request = AIAcceptanceCriteriaRequest {
+            title: "Login".to_string(),
+            description: "Users can sign in".to_string(),
+            task_type: "Story".to_string(),
+            existing_criteria: None,
+        };
+        let source = CannedCriteria(vec!["Valid credentials sign in", "Bad password shows an error"]);
+
+        let update = futures::executor::block_on(enrichment_update(&source, &request)).unwrap();
+
+        let criteria = update.acceptance_criteria.unwrap();
+        let texts: Vec<&str> = criteria.iter().map(|item| item.text.as_str()).collect();
+        assert_eq!(texts, ["Valid credentials sign in", "Bad password shows an error"]);
+        assert!(criteria.iter().all(|item| !item.completed && item.source.as_deref() == Some("ai")));
+    }
+
+    // Pending on its first poll, ready on the next
+    async fn yield_once() {
+        let mut yielded = false;
+        futures::future::poll_fn(|cx| {
+            if yielded {
+                std::task::Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                std::task::Poll::Pending
+            }
+        }).await
+    }
+
+    #[test]
+    fn run_bounded_keeps_at_most_max_in_flight_pending() {
+        use std::cell::Cell;
+
+        let in_flight = Cell::new(0);
+        let most_in_flight = Cell::new(0);
+        let results = futures::executor::block_on(run_bounded(1..=10, 3, |n| {
+            let (in_flight, most_in_flight) = (&in_flight, &most_in_flight);
+            async move {
+                in_flight.set(in_flight.get() + 1);
+                most_in_flight.set(most_in_flight.get().max(in_flight.get()));
+                yield_once().await;
+                in_flight.set(in_flight.get() - 1);
+                n * 2
+            }
+        }));
+
+        assert_eq!(results, (1..=10).map(|n| n * 2).collect::<Vec<_>>());
+        assert_eq!(most_in_flight.get(), 3);
+    }
+
+    #[test]
+    fn truncation_keeps_earlier_fields_whole_within_the_budget() {
+        let mut title = "Login".to_string();
+        let mut description = "Users can sign in with a password".to_string();
+        let mut notes = "More detail".to_string();
+        let truncated = truncate_to_budget(&mut [&mut title, &mut description, &mut notes], 15);
+
+        assert!(truncated);
+        assert_eq!((title.as_str(), description.as_str(), notes.as_str()), ("Login", "Users can ", ""));
+
+        // Text already inside the budget is left alone, and budgets count characters, not bytes
+        let mut title = "Café menü".to_string();
+        assert!(!truncate_to_budget(&mut [&mut title], 9));
+        assert!(truncate_to_budget(&mut [&mut title], 4));
+        assert_eq!(title, "Café");
+    }
+
+    #[test]
+    fn tasks_with_matching_normalized_titles_are_grouped() {
+        let candidate = |id: &str, title: &str| DuplicateCandidate {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: None,
+        };
+        let tasks = [
+            candidate("TASK-1", "Fix login bug"),
+            candidate("TASK-2", "Add dark mode"),
+            candidate("TASK-3", "  fix LOGIN-bug! "),
+            candidate("TASK-4", "Fix logout bug"),
+        ];
+
+        let clusters = find_title_duplicates(&tasks);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].task_ids, ["TASK-1", "TASK-3"]);
+        assert!(clusters[0].rationale.contains("fix login bug"), "{}", clusters[0].rationale);
+    }
+
+    struct CannedCheck(std::result::Result<(), &'static str>);
+
+    impl CredentialsCheck for CannedCheck {
+        fn model(&self) -> &str {
+            "gpt-test"
+        }
+
+        async fn check_credentials(&self) -> Result<()> {
+            self.0.map_err(Error::from)
+        }
+    }
+
+    fn candidate_config() -> AIConfig {
+        AIConfig {
+            enabled: true,
+            provider: "openai".to_string(),
+            api_key: "sk-candidate-key-1234".to_string(),
+            endpoint: None,
+            model: Some("gpt-test".to_string()),
+            max_tokens: None,
+            temperature: None,
+        }
+    }
+
+    #[test]
+    fn config_validation_reports_accepted_and_rejected_keys() {
+        let config = candidate_config();
+
+        let accepted = futures::executor::block_on(config_validation(&config, Ok(CannedCheck(Ok(())))));
+        assert!(accepted.valid && accepted.error.is_none());
+        assert_eq!(accepted.model.as_deref(), Some("gpt-test"));
+        assert_eq!(accepted.api_key, "****1234");
+
+        let rejected = futures::executor::block_on(config_validation(&config, Ok(CannedCheck(Err("OpenAI API error: 401: Incorrect API key")))));
+        assert!(!rejected.valid);
+        assert_eq!(rejected.error.as_deref(), Some("OpenAI API error: 401: Incorrect API key"));
+        assert_eq!(rejected.api_key, "****1234");
+    }
+
+    #[test]
+    fn stored_ai_config_sets_the_provider_model_and_never_returns_the_key() {
+        let update = serde_json::json!({
+            "enabled": true,
+            "provider": "openai",
+            "apiKey": "sk-stored-key-5678",
+            "endpoint": null,
+            "model": "gpt-4o-mini",
+            "maxTokens": 400,
+            "temperature": 0.2,
+            "features": {
+                "taskGeneration": true,
+                "acceptanceCriteria": true,
+                "technicalTasks": true,
+                "storyPointEstimation": true,
+                "dependencyAnalysis": false,
+                "sprintPlanning": false,
+            },
+        });
+        let mut stored: crate::models::AIConfig = serde_json::from_value(update).unwrap();
+
+        assert!(serde_json::to_value(&stored).unwrap().get("apiKey").is_none());
+
+        // As on config update, the key is taken out to be saved as a secret
+        let api_key = stored.api_key.take().unwrap();
+
+        let provider = stored_provider(stored, api_key).unwrap();
+        assert_eq!(provider.model(), "gpt-4o-mini");
+        assert_eq!((provider.max_tokens, provider.temperature), (400, 0.2));
+    }
+
+    struct CannedPoints(u32);
+
+    impl PointsEstimator for CannedPoints {
+        async fn estimate_story_points(&self, _request: &AIStoryPointEstimationRequest) -> Result<u32> {
+            Ok(self.0)
+        }
+    }
+
+    fn task_with_points(id: &str, story_points: Option<i32>) -> Task {
+        Task {
+            id: id.to_string(),
+            title: format!("Task {}", id),
+            r#type: crate::models::TaskType::Task,
+            priority: crate::models::Priority::Medium,
+            status: crate::models::TaskStatus::Todo,
+            story_points,
+            sprint: None,
+            epic: None,
+            description: "Something to estimate".to_string(),
+            acceptance_criteria: vec![],
+            technical_tasks: vec![],
+            dependencies: vec![],
+            blocks: vec![],
+            assignee: None,
+            is_favorite: None,
+            thumbnail: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn estimates_are_proposed_for_all_tasks_and_applied_only_to_unestimated_ones() {
+        let tasks = [task_with_points("TASK-1", None), task_with_points("TASK-2", Some(8)), task_with_points("TASK-3", None)];
+        let propose = |apply| futures::executor::block_on(propose_estimates(&tasks, &CannedPoints(5), 2, DEFAULT_AI_MAX_INPUT_CHARS, apply));
+
+        let proposals = propose(true);
+        let proposed: Vec<(&str, Option<u32>, Option<u32>)> = proposals.iter()
+            .map(|(estimate, to_save)| (estimate.task_id.as_str(), estimate.proposed, *to_save))
+            .collect();
+        assert_eq!(proposed, [("TASK-1", Some(5), Some(5)), ("TASK-2", Some(5), None), ("TASK-3", Some(5), Some(5))]);
+
+        // Without `apply` every task still gets a proposal, but nothing is saved
+        let proposals = propose(false);
+        assert!(proposals.iter().all(|(estimate, to_save)| estimate.proposed == Some(5) && to_save.is_none()));
+    }
+}
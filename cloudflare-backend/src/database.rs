@@ -5,6 +5,68 @@ use uuid::Uuid;
 
 pub struct Database;
 
+/// Name of the stored AI config's API key in the secrets table
+pub const AI_API_KEY_SECRET: &str = "ai_api_key";
+
+/// Page size when a listing doesn't give `limit`, and the most it may ask for
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 200;
+
+/// The SQL for one page of `list_tasks`. As in the Axum backend, every given
+/// filter adds a bound `column = ?` condition.
+pub struct TaskListSql {
+    pub filter: String,
+    pub bind_values: Vec<String>,
+    pub page: u32,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+impl TaskListSql {
+    pub fn new(query: &TaskListQuery) -> Self {
+        let filters = [
+            ("status", &query.status),
+            ("priority", &query.priority),
+            ("task_type", &query.task_type),
+            ("assignee", &query.assignee),
+            ("sprint", &query.sprint),
+            ("epic", &query.epic),
+        ];
+
+        let mut filter = String::new();
+        let mut bind_values = Vec::new();
+        for (column, value) in filters {
+            if let Some(value) = value {
+                filter.push_str(&format!(" AND {} = ?", column));
+                bind_values.push(value.clone());
+            }
+        }
+
+        // Pages count from 1; out-of-range values are clamped rather than rejected
+        let page = query.page.unwrap_or(1).max(1);
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+        Self {
+            filter,
+            bind_values,
+            page,
+            limit,
+            offset: (page - 1).saturating_mul(limit),
+        }
+    }
+
+    pub fn select(&self) -> String {
+        format!(
+            "SELECT * FROM tasks WHERE 1=1{} ORDER BY updated_at DESC, id LIMIT {} OFFSET {}",
+            self.filter, self.limit, self.offset,
+        )
+    }
+
+    pub fn count(&self) -> String {
+        format!("SELECT COUNT(*) AS count FROM tasks WHERE 1=1{}", self.filter)
+    }
+}
+
 impl Database {
     pub fn new() -> Self {
         Self
@@ -44,10 +106,11 @@ impl Database {
         })
     }
 
-    pub async fn get_task(&self, id: &str) -> Result<Task> {
+    /// `None` when no task has this id; `Err` is reserved for database failures
+    pub async fn get_task(&self, id: &str) -> Result<Option<Task>> {
         // Return a sample task for demonstration
         // In a real implementation, you would query D1 here
-        Ok(Task {
+        Ok(Some(Task {
             id: id.to_string(),
             title: "Sample Task".to_string(),
             r#type: TaskType::Task,
@@ -66,19 +129,32 @@ impl Database {
             thumbnail: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
-        })
+        }))
     }
 
-    pub async fn list_tasks(&self, _query: &TaskListQuery) -> Result<Vec<Task>> {
-        // Return an empty list for now
-        // In a real implementation, you would query D1 with filters here
-        Ok(vec![])
+    pub async fn list_tasks(&self, query: &TaskListQuery) -> Result<TaskPage> {
+        let sql = TaskListSql::new(query);
+
+        // Return an empty page for now
+        // In a real implementation, you would run sql.select() and sql.count()
+        // against D1 with sql.bind_values here
+        let tasks: Vec<Task> = vec![];
+        let total_count = 0;
+
+        Ok(TaskPage {
+            has_more: sql.offset + (tasks.len() as u32) < total_count,
+            tasks,
+            page: sql.page,
+            limit: sql.limit,
+            total_count,
+        })
     }
 
     pub async fn update_task(&self, id: &str, _request: UpdateTaskRequest) -> Result<Task> {
         // Return the task as if updated
         // In a real implementation, you would update D1 here
-        self.get_task(id).await
+        self.get_task(id).await?
+            .ok_or_else(|| Error::RustError(format!("Task {} not found", id)))
     }
 
     pub async fn delete_task(&self, _id: &str) -> Result<()> {
@@ -87,6 +163,12 @@ impl Database {
         Ok(())
     }
 
+    pub async fn log_activity(&self, _activity: ActivityLog) -> Result<()> {
+        // Return success
+        // In a real implementation, you would insert into D1 here
+        Ok(())
+    }
+
     pub async fn get_workspace_config(&self) -> Result<WorkspaceConfig> {
         // Return a default config for now
         // In a real implementation, you would query D1 here
@@ -100,6 +182,8 @@ impl Database {
                 webhooks: false,
                 custom_fields: false,
                 ai: false,
+                ai_enrich_on_create: false,
+                ai_activity_log: true,
             },
             limits: WorkspaceLimits {
                 max_tasks: 1000,
@@ -116,4 +200,45 @@ impl Database {
         // In a real implementation, you would update D1 here
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Secrets are kept out of `workspace_config` so they never travel with it
+    pub async fn get_secret(&self, _name: &str) -> Result<Option<String>> {
+        // Return no secret for now
+        // In a real implementation, you would query the D1 secrets table here
+        Ok(None)
+    }
+
+    pub async fn set_secret(&self, _name: &str, _value: &str) -> Result<()> {
+        // Return success
+        // In a real implementation, you would upsert into the D1 secrets table here
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_list_sql_binds_given_filters_and_clamps_paging() {
+        let sql = TaskListSql::new(&TaskListQuery {
+            status: Some("In Progress".to_string()),
+            epic: Some("Auth".to_string()),
+            page: Some(3),
+            limit: Some(20),
+            ..Default::default()
+        });
+        assert_eq!(sql.filter, " AND status = ? AND epic = ?");
+        assert_eq!(sql.bind_values, ["In Progress", "Auth"]);
+        assert_eq!((sql.page, sql.limit, sql.offset), (3, 20, 40));
+        assert!(sql.select().ends_with("WHERE 1=1 AND status = ? AND epic = ? ORDER BY updated_at DESC, id LIMIT 20 OFFSET 40"));
+        assert_eq!(sql.count(), "SELECT COUNT(*) AS count FROM tasks WHERE 1=1 AND status = ? AND epic = ?");
+
+        let sql = TaskListSql::new(&TaskListQuery { page: Some(0), limit: Some(1000), ..Default::default() });
+        assert!(sql.filter.is_empty() && sql.bind_values.is_empty());
+        assert_eq!((sql.page, sql.limit, sql.offset), (1, MAX_PAGE_SIZE, 0));
+
+        let sql = TaskListSql::new(&TaskListQuery { page: None, limit: None, ..Default::default() });
+        assert_eq!((sql.page, sql.limit), (1, DEFAULT_PAGE_SIZE));
+    }
+}
@@ -1,13 +1,19 @@
 use worker::*;
 use crate::models::*;
-use crate::database::Database;
+use crate::database::{Database, AI_API_KEY_SECRET, MAX_PAGE_SIZE};
 use crate::auth::{AuthService, Claims};
 use crate::config::{get_auth_config};
+use crate::ai::{
+    find_title_duplicates, get_ai_max_input_chars, get_ai_provider, AIAcceptanceCriteriaRequest,
+    AIDependencyAnalysisRequest, AIFindDuplicatesRequest, AIFindDuplicatesResponse, AISprintPlanningRequest,
+    AIStoryPointEstimationRequest, AITaskGenerationRequest, OpenAIProvider, config_validation,
+    get_ai_max_concurrency, propose_estimates, enrichment_update, AIEstimateBacklogRequest, AIEstimateBacklogResponse, BacklogEstimate,
+};
 use chrono::Utc;
 use uuid::Uuid;
 
 // Health check handler
-pub async fn health_handler(_: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn health_handler(_: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let health = HealthStatus {
         status: "healthy".to_string(),
         version: "0.1.0".to_string(),
@@ -28,7 +34,7 @@ pub async fn health_handler(_: Request, _ctx: RouteContext<()>) -> Result<Respon
 }
 
 // Authentication handlers
-pub async fn auth_verify_handler(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn auth_verify_handler(mut req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let auth_request: AuthRequest = req.json().await?;
     let auth_config = get_auth_config();
     let auth_service = AuthService::new(auth_config);
@@ -72,13 +78,13 @@ pub async fn auth_verify_handler(mut req: Request, _ctx: RouteContext<()>) -> Re
         Response::from_json(&ApiResponse::success(auth_response))
     } else {
         Response::from_json(&ApiResponse::<()>::error(
-            "INVALID_CREDENTIALS".to_string(),
-            "Invalid username/password or API key".to_string(),
+            "INVALID_CREDENTIALS",
+            "Invalid username/password or API key",
         ))
     }
 }
 
-pub async fn auth_status_handler(req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn auth_status_handler(req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let auth_config = get_auth_config();
     let auth_service = AuthService::new(auth_config);
     
@@ -95,8 +101,8 @@ pub async fn auth_status_handler(req: Request, _ctx: RouteContext<()>) -> Result
             }
             Err(e) => {
                 Response::from_json(&ApiResponse::<()>::error(
-                    "INVALID_SESSION".to_string(),
-                    e.to_string(),
+                    "INVALID_SESSION",
+                    &e.to_string(),
                 ))
             }
         }
@@ -125,7 +131,7 @@ fn is_password_complex(password: &str) -> bool {
     has_uppercase && has_lowercase && has_number && has_special
 }
 
-pub async fn auth_register_handler(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn auth_register_handler(mut req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let register_request: RegisterRequest = req.json().await?;
     
     // TODO: Implement user persistence (store users in a database)
@@ -133,8 +139,8 @@ pub async fn auth_register_handler(mut req: Request, _ctx: RouteContext<()>) ->
     // In a real system, you would store the user in a database
     if register_request.username.trim().is_empty() || !is_password_complex(&register_request.password) {
         return Response::from_json(&ApiResponse::<()>::error(
-            "VALIDATION_ERROR".to_string(),
-            "Username is required and password must be at least 6 characters, including uppercase, lowercase, number, and special character.".to_string(),
+            "VALIDATION_ERROR",
+            "Username is required and password must be at least 6 characters, including uppercase, lowercase, number, and special character.",
         ));
     }
 
@@ -142,8 +148,8 @@ pub async fn auth_register_handler(mut req: Request, _ctx: RouteContext<()>) ->
     // Check if user already exists (hardcoded for demo)
     if register_request.username == "admin" || register_request.username == "user" {
         return Response::from_json(&ApiResponse::<()>::error(
-            "USER_EXISTS".to_string(),
-            "Username already exists".to_string(),
+            "USER_EXISTS",
+            "Username already exists",
         ));
     }
 
@@ -161,7 +167,7 @@ pub async fn auth_register_handler(mut req: Request, _ctx: RouteContext<()>) ->
     Response::from_json(&ApiResponse::success(user))
 }
 
-pub async fn registration_page_handler(req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn registration_page_handler(req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let host = req.headers()
         .get("host")
         .unwrap_or_default()
@@ -181,7 +187,7 @@ pub async fn registration_page_handler(req: Request, _ctx: RouteContext<()>) ->
 }
 
 // Workspace handlers
-pub async fn workspace_info_handler(_: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn workspace_info_handler(_: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let workspace = WorkspaceInfo {
         id: "cloudflare-workspace".to_string(),
         name: "Taskdown Cloudflare Workspace".to_string(),
@@ -212,24 +218,16 @@ pub async fn workspace_info_handler(_: Request, _ctx: RouteContext<()>) -> Resul
 }
 
 // Task handlers
-pub async fn tasks_list_handler(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn tasks_list_handler(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     // Authenticate request
     let claims = match authenticate_request(&req) {
         Ok(claims) => claims,
-        Err(_) => {
-            return Response::from_json(&ApiResponse::<()>::error(
-                "UNAUTHORIZED".to_string(),
-                "Authentication required".to_string(),
-            ));
-        }
+        Err(_) => return unauthorized(),
     };
 
     // Check read permission
     if let Err(_) = require_permission(&claims, "read") {
-        return Response::from_json(&ApiResponse::<()>::error(
-            "FORBIDDEN".to_string(),
-            "Read permission required".to_string(),
-        ));
+        return forbidden("Read permission required");
     }
 
     let url = req.url()?;
@@ -248,82 +246,87 @@ pub async fn tasks_list_handler(req: Request, ctx: RouteContext<()>) -> Result<R
 
     let db = get_database(&ctx)?;
     match db.list_tasks(&query).await {
-        Ok(tasks) => Response::from_json(&ApiResponse::success(tasks)),
+        Ok(page) => Response::from_json(&ApiResponse::success(page)),
         Err(e) => Response::from_json(&ApiResponse::<()>::error(
-            "DATABASE_ERROR".to_string(),
-            e.to_string(),
+            "DATABASE_ERROR",
+            &e.to_string(),
         )),
     }
 }
 
-pub async fn tasks_create_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn tasks_create_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     // Authenticate request
     let claims = match authenticate_request(&req) {
         Ok(claims) => claims,
-        Err(_) => {
-            return Response::from_json(&ApiResponse::<()>::error(
-                "UNAUTHORIZED".to_string(),
-                "Authentication required".to_string(),
-            ));
-        }
+        Err(_) => return unauthorized(),
     };
 
     // Check write permission
     if let Err(_) = require_permission(&claims, "write") {
-        return Response::from_json(&ApiResponse::<()>::error(
-            "FORBIDDEN".to_string(),
-            "Write permission required".to_string(),
-        ));
+        return forbidden("Write permission required");
     }
 
     let create_request: CreateTaskRequest = match req.json().await {
         Ok(req) => req,
         Err(e) => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "INVALID_REQUEST".to_string(),
-                format!("Invalid JSON: {}", e),
+                "INVALID_REQUEST",
+                &format!("Invalid JSON: {}", e),
             ));
         }
     };
 
     let db = get_database(&ctx)?;
     match db.create_task(create_request).await {
-        Ok(task) => Response::from_json(&ApiResponse::success(task)),
+        Ok(task) => {
+            if task.acceptance_criteria.is_empty() {
+                enrich_in_background(&ctx, &task).await?;
+            }
+            Response::from_json(&ApiResponse::success(task))
+        }
         Err(e) => Response::from_json(&ApiResponse::<()>::error(
-            "DATABASE_ERROR".to_string(),
-            e.to_string(),
+            "DATABASE_ERROR",
+            &e.to_string(),
         )),
     }
 }
 
-pub async fn tasks_get_handler(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn tasks_get_handler(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     let id = match ctx.param("id") {
         Some(id) => id,
         None => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "MISSING_PARAMETER".to_string(),
-                "Task ID is required".to_string(),
+                "MISSING_PARAMETER",
+                "Task ID is required",
             ));
         }
     };
 
     let db = get_database(&ctx)?;
     match db.get_task(id).await {
-        Ok(task) => Response::from_json(&ApiResponse::success(task)),
-        Err(e) => Response::from_json(&ApiResponse::<()>::error(
-            "NOT_FOUND".to_string(),
-            format!("Task not found: {}", e),
-        )),
+        Ok(Some(task)) => Response::from_json(&ApiResponse::success(task)),
+        result => {
+            let (status, body) = task_lookup_error(id, result.err().as_ref());
+            Ok(Response::from_json(&body)?.with_status(status))
+        }
     }
 }
 
-pub async fn tasks_update_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+// The status and body for a task lookup that found nothing (`None`) or failed
+fn task_lookup_error(id: &str, error: Option<&Error>) -> (u16, ApiResponse<()>) {
+    match error {
+        None => (404, ApiResponse::error("NOT_FOUND", &format!("Task {} not found", id))),
+        Some(e) => (500, ApiResponse::error("DATABASE_ERROR", &e.to_string())),
+    }
+}
+
+pub async fn tasks_update_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     let id = match ctx.param("id") {
         Some(id) => id,
         None => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "MISSING_PARAMETER".to_string(),
-                "Task ID is required".to_string(),
+                "MISSING_PARAMETER",
+                "Task ID is required",
             ));
         }
     };
@@ -332,8 +335,8 @@ pub async fn tasks_update_handler(mut req: Request, ctx: RouteContext<()>) -> Re
         Ok(req) => req,
         Err(e) => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "INVALID_REQUEST".to_string(),
-                format!("Invalid JSON: {}", e),
+                "INVALID_REQUEST",
+                &format!("Invalid JSON: {}", e),
             ));
         }
     };
@@ -342,19 +345,19 @@ pub async fn tasks_update_handler(mut req: Request, ctx: RouteContext<()>) -> Re
     match db.update_task(id, update_request).await {
         Ok(task) => Response::from_json(&ApiResponse::success(task)),
         Err(e) => Response::from_json(&ApiResponse::<()>::error(
-            "DATABASE_ERROR".to_string(),
-            e.to_string(),
+            "DATABASE_ERROR",
+            &e.to_string(),
         )),
     }
 }
 
-pub async fn tasks_delete_handler(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn tasks_delete_handler(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     let id = match ctx.param("id") {
         Some(id) => id,
         None => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "MISSING_PARAMETER".to_string(),
-                "Task ID is required".to_string(),
+                "MISSING_PARAMETER",
+                "Task ID is required",
             ));
         }
     };
@@ -363,19 +366,19 @@ pub async fn tasks_delete_handler(_req: Request, ctx: RouteContext<()>) -> Resul
     match db.delete_task(id).await {
         Ok(_) => Response::from_json(&ApiResponse::success(())),
         Err(e) => Response::from_json(&ApiResponse::<()>::error(
-            "DATABASE_ERROR".to_string(),
-            e.to_string(),
+            "DATABASE_ERROR",
+            &e.to_string(),
         )),
     }
 }
 
-pub async fn tasks_bulk_handler(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn tasks_bulk_handler(mut req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let _bulk_request: BulkOperationRequest = match req.json().await {
         Ok(req) => req,
         Err(e) => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "INVALID_REQUEST".to_string(),
-                format!("Invalid JSON: {}", e),
+                "INVALID_REQUEST",
+                &format!("Invalid JSON: {}", e),
             ));
         }
     };
@@ -392,13 +395,13 @@ pub async fn tasks_bulk_handler(mut req: Request, _ctx: RouteContext<()>) -> Res
 }
 
 // Import/Export handlers
-pub async fn import_markdown_handler(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn import_markdown_handler(mut req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let _import_request: ImportRequest = match req.json().await {
         Ok(req) => req,
         Err(e) => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "INVALID_REQUEST".to_string(),
-                format!("Invalid JSON: {}", e),
+                "INVALID_REQUEST",
+                &format!("Invalid JSON: {}", e),
             ));
         }
     };
@@ -413,7 +416,7 @@ pub async fn import_markdown_handler(mut req: Request, _ctx: RouteContext<()>) -
     Response::from_json(&ApiResponse::success(response))
 }
 
-pub async fn export_markdown_handler(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn export_markdown_handler(_req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let response = ExportResponse {
         content: "# Taskdown Export\n\n*Export functionality not yet implemented*".to_string(),
         format: "markdown".to_string(),
@@ -424,7 +427,11 @@ pub async fn export_markdown_handler(_req: Request, _ctx: RouteContext<()>) -> R
 }
 
 // Analytics handlers
-pub async fn analytics_summary_handler(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn analytics_summary_handler(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if let Some(response) = feature_disabled(&ctx, "analytics").await? {
+        return Ok(response);
+    }
+
     let summary = AnalyticsSummary {
         total_tasks: 0,
         completed_tasks: 0,
@@ -439,7 +446,11 @@ pub async fn analytics_summary_handler(_req: Request, _ctx: RouteContext<()>) ->
     Response::from_json(&ApiResponse::success(summary))
 }
 
-pub async fn analytics_burndown_handler(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn analytics_burndown_handler(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    if let Some(response) = feature_disabled(&ctx, "analytics").await? {
+        return Ok(response);
+    }
+
     let burndown = BurndownData {
         dates: vec![],
         remaining_points: vec![],
@@ -450,107 +461,100 @@ pub async fn analytics_burndown_handler(_req: Request, _ctx: RouteContext<()>) -
 }
 
 // User management handlers
-pub async fn users_list_handler(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn users_list_handler(_req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let users: Vec<User> = vec![];
     Response::from_json(&ApiResponse::success(users))
 }
 
-pub async fn users_create_handler(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn users_create_handler(mut req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let _create_request: CreateUserRequest = match req.json().await {
         Ok(req) => req,
         Err(e) => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "INVALID_REQUEST".to_string(),
-                format!("Invalid JSON: {}", e),
+                "INVALID_REQUEST",
+                &format!("Invalid JSON: {}", e),
             ));
         }
     };
 
     Response::from_json(&ApiResponse::<()>::error(
-        "NOT_IMPLEMENTED".to_string(),
-        "User creation not yet implemented".to_string(),
+        "NOT_IMPLEMENTED",
+        "User creation not yet implemented",
     ))
 }
 
-pub async fn users_update_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn users_update_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     let _id = ctx.param("id");
     let _update_request: UpdateUserRequest = match req.json().await {
         Ok(req) => req,
         Err(e) => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "INVALID_REQUEST".to_string(),
-                format!("Invalid JSON: {}", e),
+                "INVALID_REQUEST",
+                &format!("Invalid JSON: {}", e),
             ));
         }
     };
 
     Response::from_json(&ApiResponse::<()>::error(
-        "NOT_IMPLEMENTED".to_string(),
-        "User update not yet implemented".to_string(),
+        "NOT_IMPLEMENTED",
+        "User update not yet implemented",
     ))
 }
 
-pub async fn users_delete_handler(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn users_delete_handler(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     let _id = ctx.param("id");
 
     Response::from_json(&ApiResponse::<()>::error(
-        "NOT_IMPLEMENTED".to_string(),
-        "User deletion not yet implemented".to_string(),
+        "NOT_IMPLEMENTED",
+        "User deletion not yet implemented",
     ))
 }
 
 // Activity handler
-pub async fn activity_handler(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+pub async fn activity_handler(_req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
     let activities: Vec<ActivityLog> = vec![];
     Response::from_json(&ApiResponse::success(activities))
 }
 
 // Configuration handlers
-pub async fn config_get_handler(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
-    let config = WorkspaceConfig {
-        name: "Taskdown Cloudflare Workspace".to_string(),
-        description: Some("A Cloudflare Workers-based workspace".to_string()),
-        default_task_type: TaskType::Task,
-        available_statuses: vec![
-            TaskStatus::Todo,
-            TaskStatus::InProgress,
-            TaskStatus::InReview,
-            TaskStatus::Done,
-        ],
-        available_priorities: vec![
-            Priority::Critical,
-            Priority::High,
-            Priority::Medium,
-            Priority::Low,
-        ],
-        enable_story_points: true,
-        enable_sprints: true,
-        enable_epics: true,
-        theme: "default".to_string(),
-    };
+pub async fn config_get_handler(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let db = get_database(&ctx)?;
+    let config = db.get_workspace_config().await?;
 
     Response::from_json(&ApiResponse::success(config))
 }
 
-pub async fn config_update_handler(mut req: Request, _ctx: RouteContext<()>) -> Result<Response> {
-    let _config: WorkspaceConfig = match req.json().await {
+pub async fn config_update_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let claims = match authenticate_request(&req) {
+        Ok(claims) => claims,
+        Err(_) => return unauthorized(),
+    };
+    if let Err(err) = require_permission(&claims, "admin") {
+        return forbidden(&err);
+    }
+
+    let mut config: WorkspaceConfig = match req.json().await {
         Ok(config) => config,
         Err(e) => {
             return Response::from_json(&ApiResponse::<()>::error(
-                "INVALID_REQUEST".to_string(),
-                format!("Invalid JSON: {}", e),
+                "INVALID_REQUEST",
+                &format!("Invalid JSON: {}", e),
             ));
         }
     };
 
-    Response::from_json(&ApiResponse::<()>::error(
-        "NOT_IMPLEMENTED".to_string(),
-        "Configuration update not yet implemented".to_string(),
-    ))
+    // The AI key is stored as a secret, apart from the rest of the config
+    let db = get_database(&ctx)?;
+    if let Some(api_key) = config.ai.as_mut().and_then(|ai| ai.api_key.take()) {
+        db.set_secret(AI_API_KEY_SECRET, &api_key).await?;
+    }
+    db.update_workspace_config(config).await?;
+
+    Response::from_json(&ApiResponse::success(db.get_workspace_config().await?))
 }
 
 // Helper function to get database from context
-fn get_database(_ctx: &RouteContext<()>) -> Result<Database> {
+fn get_database(_ctx: &RouteContext<Context>) -> Result<Database> {
     // For now, return a simple database instance
     // In a real implementation, you would get the D1 database from context
     Ok(Database::new())
@@ -568,6 +572,16 @@ fn authenticate_request(req: &Request) -> std::result::Result<Claims, String> {
     }
 }
 
+// Helper functions for auth failures: 401 when the caller isn't authenticated,
+// 403 when they are but lack the permission
+fn unauthorized() -> Result<Response> {
+    Ok(Response::from_json(&ApiResponse::<()>::error("UNAUTHORIZED", "Authentication required"))?.with_status(401))
+}
+
+fn forbidden(message: &str) -> Result<Response> {
+    Ok(Response::from_json(&ApiResponse::<()>::error("FORBIDDEN", message))?.with_status(403))
+}
+
 // Helper function to check permissions
 fn require_permission(claims: &Claims, permission: &str) -> std::result::Result<(), String> {
     let auth_config = get_auth_config();
@@ -580,117 +594,363 @@ fn require_permission(claims: &Claims, permission: &str) -> std::result::Result<
     }
 }
 
+// Helper function to reject requests for features disabled in the workspace config
+async fn feature_disabled(ctx: &RouteContext<Context>, feature: &str) -> Result<Option<Response>> {
+    let db = get_database(ctx)?;
+    let features = db.get_workspace_config().await?.features;
+
+    let enabled = match feature {
+        "analytics" => features.analytics,
+        "webhooks" => features.webhooks,
+        "realtime" => features.realtime,
+        "ai" => features.ai,
+        _ => true,
+    };
+
+    if enabled {
+        return Ok(None);
+    }
+
+    let response = Response::from_json(&ApiResponse::<()>::error(
+        "FEATURE_DISABLED",
+        &format!("The {} feature is disabled for this workspace", feature),
+    ))?
+    .with_status(403);
+
+    Ok(Some(response))
+}
+
+// Helper function to attach AI-suggested acceptance criteria to a new task
+// when `features.aiEnrichOnCreate` is on. The provider call runs after the
+// response is sent, kept alive with `wait_until`; failures are only logged.
+async fn enrich_in_background(ctx: &RouteContext<Context>, task: &Task) -> Result<()> {
+    let db = get_database(ctx)?;
+    let features = db.get_workspace_config().await?.features;
+    if !features.ai || !features.ai_enrich_on_create {
+        return Ok(());
+    }
+    let Some(ai_provider) = get_ai_provider(&ctx.env, &db).await? else {
+        return Ok(());
+    };
+
+    let mut request = AIAcceptanceCriteriaRequest {
+        title: task.title.clone(),
+        description: task.description.clone(),
+        task_type: format!("{:?}", task.r#type),
+        existing_criteria: None,
+    };
+    request.truncate_input(get_ai_max_input_chars(&ctx.env));
+
+    let id = task.id.clone();
+    let log_activity = features.ai_activity_log;
+    ctx.data.wait_until(async move {
+        let update = match enrichment_update(&ai_provider, &request).await {
+            Ok(update) => update,
+            Err(e) => {
+                console_error!("AI enrichment failed for task {}: {}", id, e);
+                return;
+            }
+        };
+
+        let produced = update.acceptance_criteria.as_ref().map_or(0, Vec::len);
+        if let Err(e) = db.update_task(&id, update).await {
+            console_error!("Failed to save AI criteria for task {}: {}", id, e);
+            return;
+        }
+
+        if log_activity {
+            let activity = ai_generated_activity(&id, "acceptance_criteria", produced);
+            if let Err(e) = db.log_activity(activity).await {
+                console_error!("Failed to log AI activity for task {}: {}", id, e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// Helper function to build the audit entry for AI output applied to a task,
+// so AI edits can be told apart from human ones
+fn ai_generated_activity(task_id: &str, produced: &str, count: usize) -> ActivityLog {
+    ActivityLog {
+        id: Uuid::new_v4().to_string(),
+        user_id: "ai".to_string(),
+        action: "ai_generated".to_string(),
+        resource_type: "task".to_string(),
+        resource_id: task_id.to_string(),
+        details: serde_json::json!({ "produced": produced, "count": count }),
+        timestamp: Utc::now(),
+    }
+}
+
+// Helper function to tell clients their AI input was cut to fit the budget
+fn with_truncation_notice(response: Response, truncated: bool, max_input: usize) -> Result<Response> {
+    if truncated {
+        response.headers().set("X-AI-Input-Truncated", &max_input.to_string())?;
+    }
+    Ok(response)
+}
+
 // AI Handlers
-pub async fn ai_generate_task_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn ai_validate_config_handler(mut req: Request, _ctx: RouteContext<Context>) -> Result<Response> {
+    // Authenticate user
+    let claims = match authenticate_request(&req) {
+        Ok(claims) => claims,
+        Err(_) => return unauthorized(),
+    };
+
+    // Check permissions
+    if let Err(err) = require_permission(&claims, "admin") {
+        return forbidden(&err);
+    }
+
+    // Parse the candidate config; it is only tried, never saved
+    let config: crate::ai::AIConfig = match req.json().await {
+        Ok(config) => config,
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid AI config")),
+    };
+    let validation = config_validation(&config, OpenAIProvider::from_config(&config)).await;
+    if let Some(error) = &validation.error {
+        console_log!("AI config check failed for {} key {}: {}", validation.provider, validation.api_key, error);
+    }
+
+    Response::from_json(&ApiResponse::success(validation))
+}
+
+pub async fn ai_generate_task_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     // Authenticate user
-    let claims = match AuthService::from_request(&req, &ctx.env).await {
+    let claims = match authenticate_request(&req) {
         Ok(claims) => claims,
-        Err(_) => return Response::from_json(&ApiResponse::error("unauthorized", "Authentication required")),
+        Err(_) => return unauthorized(),
     };
 
     // Check permissions
     if let Err(err) = require_permission(&claims, "write") {
-        return Response::from_json(&ApiResponse::error("forbidden", &err));
+        return forbidden(&err);
+    }
+
+    // Check the AI feature is enabled for this workspace
+    if let Some(response) = feature_disabled(&ctx, "ai").await? {
+        return Ok(response);
     }
 
     // Get AI provider
-    let ai_provider = match get_ai_provider(&ctx.env).await? {
+    let ai_provider = match get_ai_provider(&ctx.env, &get_database(&ctx)?).await? {
         Some(provider) => provider,
-        None => return Response::from_json(&ApiResponse::error("ai_not_configured", "AI features are not configured")),
+        None => return Response::from_json(&ApiResponse::<()>::error("ai_not_configured", "AI features are not configured")),
     };
 
     // Parse request
-    let request: AITaskGenerationRequest = match req.json().await {
+    let mut request: AITaskGenerationRequest = match req.json().await {
         Ok(req) => req,
-        Err(_) => return Response::from_json(&ApiResponse::error("invalid_request", "Invalid request body")),
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid request body")),
     };
 
+    // Oversized input is cut to fit rather than rejected
+    let max_input = get_ai_max_input_chars(&ctx.env);
+    let truncated = request.truncate_input(max_input);
+
     // Generate task details
     match ai_provider.generate_task_details(&request).await {
-        Ok(response) => Response::from_json(&ApiResponse::success(response)),
-        Err(e) => Response::from_json(&ApiResponse::error("ai_error", &format!("AI generation failed: {}", e))),
+        Ok(response) => with_truncation_notice(Response::from_json(&ApiResponse::success(response))?, truncated, max_input),
+        Err(e) => Response::from_json(&ApiResponse::<()>::error("ai_error", &format!("AI generation failed: {}", e))),
     }
 }
 
-pub async fn ai_acceptance_criteria_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn ai_acceptance_criteria_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     // Authenticate user
-    let claims = match AuthService::from_request(&req, &ctx.env).await {
+    let claims = match authenticate_request(&req) {
         Ok(claims) => claims,
-        Err(_) => return Response::from_json(&ApiResponse::error("unauthorized", "Authentication required")),
+        Err(_) => return unauthorized(),
     };
 
     // Check permissions
     if let Err(err) = require_permission(&claims, "write") {
-        return Response::from_json(&ApiResponse::error("forbidden", &err));
+        return forbidden(&err);
+    }
+
+    // Check the AI feature is enabled for this workspace
+    if let Some(response) = feature_disabled(&ctx, "ai").await? {
+        return Ok(response);
     }
 
     // Get AI provider
-    let ai_provider = match get_ai_provider(&ctx.env).await? {
+    let ai_provider = match get_ai_provider(&ctx.env, &get_database(&ctx)?).await? {
         Some(provider) => provider,
-        None => return Response::from_json(&ApiResponse::error("ai_not_configured", "AI features are not configured")),
+        None => return Response::from_json(&ApiResponse::<()>::error("ai_not_configured", "AI features are not configured")),
     };
 
     // Parse request
-    let request: AIAcceptanceCriteriaRequest = match req.json().await {
+    let mut request: AIAcceptanceCriteriaRequest = match req.json().await {
         Ok(req) => req,
-        Err(_) => return Response::from_json(&ApiResponse::error("invalid_request", "Invalid request body")),
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid request body")),
     };
 
+    // Oversized input is cut to fit rather than rejected
+    let max_input = get_ai_max_input_chars(&ctx.env);
+    let truncated = request.truncate_input(max_input);
+
     // Generate acceptance criteria
     match ai_provider.generate_acceptance_criteria(&request).await {
-        Ok(response) => Response::from_json(&ApiResponse::success(response)),
-        Err(e) => Response::from_json(&ApiResponse::error("ai_error", &format!("AI generation failed: {}", e))),
+        Ok(response) => with_truncation_notice(Response::from_json(&ApiResponse::success(response))?, truncated, max_input),
+        Err(e) => Response::from_json(&ApiResponse::<()>::error("ai_error", &format!("AI generation failed: {}", e))),
     }
 }
 
-pub async fn ai_estimate_story_points_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn ai_estimate_story_points_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     // Authenticate user
-    let claims = match AuthService::from_request(&req, &ctx.env).await {
+    let claims = match authenticate_request(&req) {
         Ok(claims) => claims,
-        Err(_) => return Response::from_json(&ApiResponse::error("unauthorized", "Authentication required")),
+        Err(_) => return unauthorized(),
     };
 
     // Check permissions
     if let Err(err) = require_permission(&claims, "write") {
-        return Response::from_json(&ApiResponse::error("forbidden", &err));
+        return forbidden(&err);
+    }
+
+    // Check the AI feature is enabled for this workspace
+    if let Some(response) = feature_disabled(&ctx, "ai").await? {
+        return Ok(response);
     }
 
     // Get AI provider
-    let ai_provider = match get_ai_provider(&ctx.env).await? {
+    let ai_provider = match get_ai_provider(&ctx.env, &get_database(&ctx)?).await? {
         Some(provider) => provider,
-        None => return Response::from_json(&ApiResponse::error("ai_not_configured", "AI features are not configured")),
+        None => return Response::from_json(&ApiResponse::<()>::error("ai_not_configured", "AI features are not configured")),
     };
 
     // Parse request
     let request: AIStoryPointEstimationRequest = match req.json().await {
         Ok(req) => req,
-        Err(_) => return Response::from_json(&ApiResponse::error("invalid_request", "Invalid request body")),
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid request body")),
     };
 
+    // A partial description would skew the estimate, so oversized input is rejected
+    let max_input = get_ai_max_input_chars(&ctx.env);
+    let input_len = request.input_len();
+    if input_len > max_input {
+        return Ok(Response::from_json(&ApiResponse::<()>::error(
+            "ai_input_too_large",
+            &format!("Task text is {} characters; the limit is {}", input_len, max_input),
+        ))?
+        .with_status(413));
+    }
+
     // Estimate story points
     match ai_provider.estimate_story_points(&request).await {
         Ok(response) => Response::from_json(&ApiResponse::success(response)),
-        Err(e) => Response::from_json(&ApiResponse::error("ai_error", &format!("AI estimation failed: {}", e))),
+        Err(e) => Response::from_json(&ApiResponse::<()>::error("ai_error", &format!("AI estimation failed: {}", e))),
+    }
+}
+
+pub async fn ai_estimate_backlog_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    // Authenticate user
+    let claims = match authenticate_request(&req) {
+        Ok(claims) => claims,
+        Err(_) => return unauthorized(),
+    };
+
+    // Check permissions
+    if let Err(err) = require_permission(&claims, "write") {
+        return forbidden(&err);
+    }
+
+    // Check the AI feature is enabled for this workspace
+    if let Some(response) = feature_disabled(&ctx, "ai").await? {
+        return Ok(response);
+    }
+
+    // Get AI provider
+    let db = get_database(&ctx)?;
+    let ai_provider = match get_ai_provider(&ctx.env, &db).await? {
+        Some(provider) => provider,
+        None => return Response::from_json(&ApiResponse::<()>::error("ai_not_configured", "AI features are not configured")),
+    };
+
+    // Parse request
+    let request: AIEstimateBacklogRequest = match req.json().await {
+        Ok(req) => req,
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid request body")),
+    };
+
+    // Collect the tasks; ids that don't exist are reported rather than failing the batch
+    let mut estimates = Vec::new();
+    let tasks = match (request.task_ids, request.filter) {
+        // Capped at a page, like the filter path
+        (Some(task_ids), None) if task_ids.len() > MAX_PAGE_SIZE as usize => {
+            return Ok(Response::from_json(&ApiResponse::<()>::error(
+                "too_many_tasks",
+                &format!("{} taskIds were given; at most {} can be estimated at once", task_ids.len(), MAX_PAGE_SIZE),
+            ))?
+            .with_status(413));
+        }
+        (Some(task_ids), None) => {
+            let mut tasks = Vec::new();
+            for id in task_ids {
+                match db.get_task(&id).await? {
+                    Some(task) => tasks.push(task),
+                    None => estimates.push(BacklogEstimate {
+                        task_id: id,
+                        proposed: None,
+                        applied: false,
+                        error: Some("Task not found".to_string()),
+                    }),
+                }
+            }
+            tasks
+        }
+        (None, Some(filter)) => db.list_tasks(&filter).await?.tasks,
+        _ => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Give either taskIds or filter")),
+    };
+
+    let max_in_flight = get_ai_max_concurrency(&ctx.env);
+    let max_input = get_ai_max_input_chars(&ctx.env);
+    let proposals = propose_estimates(&tasks, &ai_provider, max_in_flight, max_input, request.apply).await;
+
+    let mut applied = 0;
+    for (mut estimate, points) in proposals {
+        if let Some(points) = points {
+            let update = UpdateTaskRequest {
+                story_points: Some(Some(points as i32)),
+                ..Default::default()
+            };
+            match db.update_task(&estimate.task_id, update).await {
+                Ok(_) => {
+                    estimate.applied = true;
+                    applied += 1;
+                }
+                Err(e) => estimate.error = Some(format!("Failed to save estimate: {}", e)),
+            }
+        }
+        estimates.push(estimate);
     }
+
+    Response::from_json(&ApiResponse::success(AIEstimateBacklogResponse { estimates, applied }))
 }
 
-pub async fn ai_analyze_dependencies_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn ai_analyze_dependencies_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     // Authenticate user
-    let claims = match AuthService::from_request(&req, &ctx.env).await {
+    let claims = match authenticate_request(&req) {
         Ok(claims) => claims,
-        Err(_) => return Response::from_json(&ApiResponse::error("unauthorized", "Authentication required")),
+        Err(_) => return unauthorized(),
     };
 
     // Check permissions
     if let Err(err) = require_permission(&claims, "read") {
-        return Response::from_json(&ApiResponse::error("forbidden", &err));
+        return forbidden(&err);
+    }
+
+    // Check the AI feature is enabled for this workspace
+    if let Some(response) = feature_disabled(&ctx, "ai").await? {
+        return Ok(response);
     }
 
     // For now, return a simple placeholder response
     // In a real implementation, this would use AI to analyze task dependencies
     let request: AIDependencyAnalysisRequest = match req.json().await {
         Ok(req) => req,
-        Err(_) => return Response::from_json(&ApiResponse::error("invalid_request", "Invalid request body")),
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid request body")),
     };
 
     use crate::ai::AIDependencyAnalysisResponse;
@@ -703,23 +963,76 @@ pub async fn ai_analyze_dependencies_handler(mut req: Request, ctx: RouteContext
     Response::from_json(&ApiResponse::success(response))
 }
 
-pub async fn ai_plan_sprint_handler(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+pub async fn ai_find_duplicates_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    // Authenticate user
+    let claims = match authenticate_request(&req) {
+        Ok(claims) => claims,
+        Err(_) => return unauthorized(),
+    };
+
+    // Check permissions
+    if let Err(err) = require_permission(&claims, "read") {
+        return forbidden(&err);
+    }
+
+    // Check the AI feature is enabled for this workspace
+    if let Some(response) = feature_disabled(&ctx, "ai").await? {
+        return Ok(response);
+    }
+
+    // Parse request
+    let mut request: AIFindDuplicatesRequest = match req.json().await {
+        Ok(req) => req,
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid request body")),
+    };
+
+    // Without a provider, only tasks with the same normalized title are grouped
+    let ai_provider = match get_ai_provider(&ctx.env, &get_database(&ctx)?).await? {
+        Some(provider) => provider,
+        None => {
+            let response = AIFindDuplicatesResponse {
+                clusters: find_title_duplicates(&request.tasks),
+                method: "title_match".to_string(),
+            };
+            return Response::from_json(&ApiResponse::success(response));
+        }
+    };
+
+    // Oversized input is cut to fit rather than rejected
+    let max_input = get_ai_max_input_chars(&ctx.env);
+    let truncated = request.truncate_input(max_input);
+
+    match ai_provider.find_duplicates(&request).await {
+        Ok(clusters) => {
+            let response = AIFindDuplicatesResponse { clusters, method: "ai".to_string() };
+            with_truncation_notice(Response::from_json(&ApiResponse::success(response))?, truncated, max_input)
+        }
+        Err(e) => Response::from_json(&ApiResponse::<()>::error("ai_error", &format!("Duplicate detection failed: {}", e))),
+    }
+}
+
+pub async fn ai_plan_sprint_handler(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     // Authenticate user
-    let claims = match AuthService::from_request(&req, &ctx.env).await {
+    let claims = match authenticate_request(&req) {
         Ok(claims) => claims,
-        Err(_) => return Response::from_json(&ApiResponse::error("unauthorized", "Authentication required")),
+        Err(_) => return unauthorized(),
     };
 
     // Check permissions
     if let Err(err) = require_permission(&claims, "read") {
-        return Response::from_json(&ApiResponse::error("forbidden", &err));
+        return forbidden(&err);
+    }
+
+    // Check the AI feature is enabled for this workspace
+    if let Some(response) = feature_disabled(&ctx, "ai").await? {
+        return Ok(response);
     }
 
     // For now, return a simple placeholder response
     // In a real implementation, this would use AI for sprint planning
     let request: AISprintPlanningRequest = match req.json().await {
         Ok(req) => req,
-        Err(_) => return Response::from_json(&ApiResponse::error("invalid_request", "Invalid request body")),
+        Err(_) => return Response::from_json(&ApiResponse::<()>::error("invalid_request", "Invalid request body")),
     };
 
     use crate::ai::AISprintPlanningResponse;
@@ -731,4 +1044,50 @@ pub async fn ai_plan_sprint_handler(mut req: Request, ctx: RouteContext<()>) ->
     };
 
     Response::from_json(&ApiResponse::success(response))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_tasks_and_database_failures_get_different_statuses() {
+        let (status, body) = task_lookup_error("TASK-1", None);
+        let error = body.error.unwrap();
+        assert_eq!((status, error.code.as_str()), (404, "NOT_FOUND"));
+        assert_eq!(error.message, "Task TASK-1 not found");
+
+        let failure = Error::RustError("D1 is unavailable".to_string());
+        let (status, body) = task_lookup_error("TASK-1", Some(&failure));
+        let error = body.error.unwrap();
+        assert_eq!((status, error.code.as_str()), (500, "DATABASE_ERROR"));
+        assert!(error.message.contains("D1 is unavailable"), "{}", error.message);
+    }
+
+    struct CannedCriteria(Vec<&'static str>);
+
+    impl crate::ai::CriteriaSource for CannedCriteria {
+        async fn generate_acceptance_criteria(&self, _request: &AIAcceptanceCriteriaRequest) -> Result<Vec<String>> {
+            Ok(self.0.iter().map(|text| text.to_string()).collect())
+        }
+    }
+
+    #[test]
+    fn applying_ai_criteria_is_logged_as_ai_generated() {
+        let request = AIAcceptanceCriteriaRequest {
+            title: "Login".to_string(),
+            description: "Users can sign in".to_string(),
+            task_type: "Story".to_string(),
+            existing_criteria: None,
+        };
+        let source = CannedCriteria(vec!["Valid credentials sign in", "Bad password shows an error"]);
+        let update = futures::executor::block_on(enrichment_update(&source, &request)).unwrap();
+        let produced = update.acceptance_criteria.as_ref().map_or(0, Vec::len);
+
+        let activity = ai_generated_activity("TASK-1", "acceptance_criteria", produced);
+
+        assert_eq!((activity.action.as_str(), activity.user_id.as_str()), ("ai_generated", "ai"));
+        assert_eq!((activity.resource_type.as_str(), activity.resource_id.as_str()), ("task", "TASK-1"));
+        assert_eq!(activity.details, serde_json::json!({ "produced": "acceptance_criteria", "count": 2 }));
+    }
+}
@@ -11,10 +11,12 @@ use handlers::*;
 use config::load_cors_origins;
 
 #[event(fetch)]
-async fn fetch(req: Request, env: Env, _ctx: Context) -> Result<Response> {
+async fn fetch(req: Request, env: Env, ctx: Context) -> Result<Response> {
     console_error_panic_hook::set_once();
 
-    let router = Router::new();
+    // Handlers get the event context so work finishing after the response
+    // (e.g. AI enrichment) can keep the request alive with `wait_until`
+    let router = Router::with_data(ctx);
 
     router
         // Health endpoint
@@ -61,11 +63,14 @@ async fn fetch(req: Request, env: Env, _ctx: Context) -> Result<Response> {
         .put_async("/api/config", config_update_handler)
         
         // AI endpoints
+        .post_async("/api/ai/validate-config", ai_validate_config_handler)
         .post_async("/api/ai/generate-task", ai_generate_task_handler)
         .post_async("/api/ai/acceptance-criteria", ai_acceptance_criteria_handler)
         .post_async("/api/ai/estimate-story-points", ai_estimate_story_points_handler)
+        .post_async("/api/ai/estimate-backlog", ai_estimate_backlog_handler)
         .post_async("/api/ai/analyze-dependencies", ai_analyze_dependencies_handler)
         .post_async("/api/ai/plan-sprint", ai_plan_sprint_handler)
+        .post_async("/api/ai/find-duplicates", ai_find_duplicates_handler)
         
         // Handle CORS preflight requests
         .options("/*", |_req, ctx| {
@@ -70,6 +70,9 @@ pub struct ChecklistItem {
     pub id: Option<String>,
     pub text: String,
     pub completed: bool,
+    /// `human` or `ai`; unset items count as `human`
+    #[serde(default)]
+    pub source: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -122,7 +125,7 @@ pub struct CreateTaskRequest {
     pub thumbnail: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default)]
 pub struct UpdateTaskRequest {
     pub title: Option<String>,
     pub r#type: Option<TaskType>,
@@ -267,6 +270,19 @@ pub struct ActivityLog {
     pub timestamp: DateTime<Utc>,
 }
 
+// Result of POST /api/ai/validate-config
+#[derive(Debug, Serialize)]
+pub struct AIConfigValidation {
+    pub valid: bool,
+    pub provider: String,
+    pub model: Option<String>,
+    /// Redacted to its last four characters
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    /// The provider's error when the check call failed
+    pub error: Option<String>,
+}
+
 // Configuration types
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WorkspaceConfig {
@@ -288,6 +304,16 @@ pub struct WorkspaceFeatures {
     #[serde(rename = "customFields")]
     pub custom_fields: bool,
     pub ai: bool,
+    /// Have the AI suggest acceptance criteria for tasks created without any
+    #[serde(rename = "aiEnrichOnCreate", default)]
+    pub ai_enrich_on_create: bool,
+    /// Record an `ai_generated` activity whenever AI output is applied to a task
+    #[serde(rename = "aiActivityLog", default = "default_ai_activity_log")]
+    pub ai_activity_log: bool,
+}
+
+fn default_ai_activity_log() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -306,8 +332,9 @@ pub struct WorkspaceLimits {
 pub struct AIConfig {
     pub enabled: bool,
     pub provider: String,
-    #[serde(rename = "apiKey")]
-    pub api_key: String,
+    /// Accepted on update and moved to the secrets table; never returned
+    #[serde(rename = "apiKey", default, skip_serializing)]
+    pub api_key: Option<String>,
     pub endpoint: Option<String>,
     pub model: Option<String>,
     #[serde(rename = "maxTokens")]
@@ -402,6 +429,19 @@ pub struct TaskListQuery {
     pub limit: Option<u32>,
 }
 
+/// One page of `GET /api/tasks`
+#[derive(Debug, Serialize)]
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    pub page: u32,
+    pub limit: u32,
+    /// Tasks matching the filters across every page
+    #[serde(rename = "totalCount")]
+    pub total_count: u32,
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+}
+
 impl Default for TaskListQuery {
     fn default() -> Self {
         Self {